@@ -0,0 +1,291 @@
+//! Derive macros for `actix-json-validator`. Use them through the re-exports of the main
+//! crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Expr, Fields, LitStr, Type,
+};
+
+/// Derive `actix_json_validator::Describe` from the `serde_valid` and serde attributes of
+/// a struct. See the trait's documentation.
+#[proc_macro_derive(Describe, attributes(app_json))]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "`Describe` can only be derived for structs",
+        ));
+    };
+
+    let rename_all = serde_rename_all(&input.attrs)?;
+    let struct_constraints = constraints(&input.attrs)?.constraints;
+
+    let mut fields = Vec::new();
+    let named = matches!(data.fields, Fields::Named(_));
+    for (index, field) in data.fields.iter().enumerate() {
+        let serde = serde_field(&field.attrs)?;
+        if serde.skip {
+            continue;
+        }
+        let name = match (&serde.rename, &field.ident) {
+            (Some(rename), _) => rename.clone(),
+            (None, Some(ident)) => {
+                let ident = ident.to_string();
+                let ident = ident.strip_prefix("r#").unwrap_or(&ident).to_string();
+                apply_rename_all(&ident, rename_all.as_deref())
+            }
+            (None, None) => index.to_string(),
+        };
+        let label = match app_json_label(&field.attrs)? {
+            Some(label) => quote!(::core::option::Option::Some(#label)),
+            None => quote!(::core::option::Option::None),
+        };
+        let ty = &field.ty;
+        let ty_name = quote!(#ty).to_string().replace(' ', "");
+        let required = named && !serde.default && !is_option(ty);
+
+        let validate = constraints(&field.attrs)?;
+        let field_constraints = validate.constraints;
+        let nested = if validate.nested {
+            quote! {
+                ::core::option::Option::Some(
+                    <#ty as ::actix_json_validator::__private::Nested>::nested_metadata()
+                )
+            }
+        } else {
+            quote!(::core::option::Option::None)
+        };
+
+        fields.push(quote! {
+            ::actix_json_validator::FieldMetadata {
+                name: #name,
+                label: #label,
+                ty: #ty_name,
+                required: #required,
+                constraints: ::std::vec![#(#field_constraints),*],
+                nested: #nested,
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::actix_json_validator::Describe for #ident #ty_generics #where_clause {
+            fn metadata() -> ::actix_json_validator::TypeMetadata {
+                ::actix_json_validator::TypeMetadata {
+                    name: #name,
+                    constraints: ::std::vec![#(#struct_constraints),*],
+                    fields: ::std::vec![#(#fields),*],
+                }
+            }
+        }
+    })
+}
+
+struct Validate {
+    constraints: Vec<TokenStream2>,
+    nested: bool,
+}
+
+/// Collect the constraints of the `#[validate(...)]` attributes. A bare `#[validate]`
+/// marks a nested payload.
+fn constraints(attrs: &[Attribute]) -> syn::Result<Validate> {
+    let mut validate = Validate {
+        constraints: Vec::new(),
+        nested: false,
+    };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+        if let syn::Meta::Path(_) = attr.meta {
+            validate.nested = true;
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            let Some(ident) = meta.path.get_ident() else {
+                return Err(meta.error("unsupported validate attribute"));
+            };
+            let code = ident.to_string();
+            match code.as_str() {
+                // Message customization, not constraints.
+                "message" | "message_fn" | "message_l10n" | "fluent" => {
+                    skip(&meta)?;
+                    return Ok(());
+                }
+                "unique_items" => {
+                    validate.constraints.push(constraint(&code, quote!(true)));
+                    return Ok(());
+                }
+                "custom" => {
+                    let value = if meta.input.peek(syn::Token![=]) {
+                        let expr: Expr = meta.value()?.parse()?;
+                        quote!(#expr).to_string()
+                    } else {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        content.parse::<TokenStream2>()?.to_string()
+                    };
+                    validate.constraints.push(constraint(&code, quote!(#value)));
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            let value: Expr = meta.value()?.parse()?;
+            validate.constraints.push(constraint(&code, quote!(#value)));
+            // Options following the constraint, such as `message = "..."`, are skipped
+            // by the next iterations.
+            Ok(())
+        })?;
+    }
+    Ok(validate)
+}
+
+fn constraint(code: &str, value: TokenStream2) -> TokenStream2 {
+    quote! {
+        ::actix_json_validator::Constraint {
+            code: #code,
+            value: ::actix_json_validator::__private::serde_json::json!(#value),
+        }
+    }
+}
+
+/// Consume the value of an attribute option we do not interpret.
+fn skip(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream2>()?;
+    }
+    Ok(())
+}
+
+fn app_json_label(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut label = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("app_json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("label") {
+                label = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown app_json attribute, expected `label`"))
+            }
+        })?;
+    }
+    Ok(label)
+}
+
+#[derive(Default)]
+struct SerdeField {
+    rename: Option<String>,
+    skip: bool,
+    default: bool,
+}
+
+fn serde_field(attrs: &[Attribute]) -> syn::Result<SerdeField> {
+    let mut serde = SerdeField::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                if meta.input.peek(syn::Token![=]) {
+                    serde.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    meta.parse_nested_meta(|inner| {
+                        let value = inner.value()?.parse::<LitStr>()?.value();
+                        if inner.path.is_ident("deserialize") {
+                            serde.rename = Some(value);
+                        }
+                        Ok(())
+                    })?;
+                }
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                serde.skip = true;
+            } else if meta.path.is_ident("default") {
+                serde.default = true;
+                skip(&meta)?;
+            } else {
+                skip(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(serde)
+}
+
+fn serde_rename_all(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut rename_all = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                if meta.input.peek(syn::Token![=]) {
+                    rename_all = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    meta.parse_nested_meta(|inner| {
+                        let value = inner.value()?.parse::<LitStr>()?.value();
+                        if inner.path.is_ident("deserialize") {
+                            rename_all = Some(value);
+                        }
+                        Ok(())
+                    })?;
+                }
+            } else {
+                skip(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(rename_all)
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field name.
+fn apply_rename_all(field: &str, rule: Option<&str>) -> String {
+    let words = field.split('_').filter(|word| !word.is_empty());
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    match rule {
+        Some("lowercase") => field.to_lowercase(),
+        Some("UPPERCASE") => field.to_uppercase(),
+        Some("PascalCase") => words.map(capitalize).collect(),
+        Some("camelCase") => words
+            .enumerate()
+            .map(|(index, word)| {
+                if index == 0 {
+                    word.to_string()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+        Some("SCREAMING_SNAKE_CASE") => field.to_uppercase(),
+        Some("kebab-case") => field.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => field.replace('_', "-").to_uppercase(),
+        _ => field.to_string(),
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
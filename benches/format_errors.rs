@@ -0,0 +1,78 @@
+//! Rendering of large error trees: `cargo bench --bench format_errors`.
+//!
+//! Bodies with many failing items are extracted with `AppJson`, so most of the time goes
+//! into turning the `serde_valid` errors into the response body.
+
+use actix_json_validator::{AppJson, JsonConfig};
+use actix_web::{http::header::ContentType, rt::System, test::TestRequest, FromRequest};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde::Deserialize;
+use serde_json::json;
+use serde_valid::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+struct Import {
+    #[validate(min_length = 3)]
+    source: String,
+    #[validate]
+    rows: Vec<Row>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct Row {
+    #[validate(min_length = 1)]
+    name: String,
+    #[validate(minimum = 1)]
+    quantity: u32,
+    #[validate(max_length = 2)]
+    #[validate(max_items = 2)]
+    tags: Vec<String>,
+    #[validate]
+    address: Address,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct Address {
+    #[validate(max_length = 5)]
+    zip: String,
+}
+
+fn body(rows: usize) -> Vec<u8> {
+    let rows: Vec<_> = (0..rows)
+        .map(|_| {
+            json!({
+                "name": "",
+                "quantity": 0,
+                "tags": ["long", "ok", "longer"],
+                "address": {"zip": "123456"}
+            })
+        })
+        .collect();
+    serde_json::to_vec(&json!({"source": "x", "rows": rows})).unwrap()
+}
+
+fn extraction(c: &mut Criterion) {
+    let system = System::new();
+    let mut group = c.benchmark_group("invalid_rows");
+    for rows in [10, 100, 1000] {
+        let body = body(rows);
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &body, |b, body| {
+            b.iter(|| {
+                system.block_on(async {
+                    let (req, mut payload) = TestRequest::post()
+                        .app_data(JsonConfig::default().limit(usize::MAX))
+                        .insert_header(ContentType::json())
+                        .set_payload(body.clone())
+                        .to_http_parts();
+                    AppJson::<Import>::from_request(&req, &mut payload)
+                        .await
+                        .unwrap_err()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, extraction);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+//! Parsing of large bodies as they arrive: `cargo bench --bench streaming`.
+//!
+//! Valid bodies are extracted with `AppJson` above its
+//! [`streaming_threshold`](JsonConfig::streaming_threshold), so most of the time goes
+//! into reading the body into the parser on the blocking thread pool.
+
+use actix_json_validator::{AppJson, JsonConfig};
+use actix_web::{http::header::ContentType, rt::System, test::TestRequest, FromRequest};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::Deserialize;
+use serde_json::json;
+use serde_valid::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+struct Import {
+    #[validate]
+    rows: Vec<Row>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct Row {
+    #[validate(min_length = 1)]
+    name: String,
+    #[validate(minimum = 1)]
+    quantity: u32,
+}
+
+fn body(rows: usize) -> Vec<u8> {
+    let rows: Vec<_> = (0..rows)
+        .map(|row| json!({"name": format!("item-{row}"), "quantity": row + 1}))
+        .collect();
+    serde_json::to_vec(&json!({"rows": rows})).unwrap()
+}
+
+fn extraction(c: &mut Criterion) {
+    let system = System::new();
+    let mut group = c.benchmark_group("streamed_rows");
+    for rows in [1_000, 10_000, 100_000] {
+        let body = body(rows);
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &body, |b, body| {
+            b.iter(|| {
+                system.block_on(async {
+                    let (req, mut payload) = TestRequest::post()
+                        .app_data(
+                            JsonConfig::default()
+                                .limit(usize::MAX)
+                                .streaming_threshold(0),
+                        )
+                        .insert_header(ContentType::json())
+                        .set_payload(body.clone())
+                        .to_http_parts();
+                    AppJson::<Import>::from_request(&req, &mut payload)
+                        .await
+                        .unwrap()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, extraction);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+//! Feeds arbitrary `serde_valid` error trees through the error formatter, whose recursive
+//! merging of nested objects, arrays and per-element errors must never panic.
+
+#![no_main]
+
+use actix_json_validator::__fuzz::format_errors;
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use serde_valid::{
+    validation::{
+        error::{Format, Message},
+        ArrayErrors, Error, Errors, ObjectErrors,
+    },
+    MaxItemsError, MinLengthError,
+};
+
+#[derive(Debug, Arbitrary)]
+enum Tree {
+    Array(Vec<Leaf>, Vec<(u8, Tree)>),
+    Object(Vec<Leaf>, Vec<(String, Tree)>),
+    NewType(Vec<Leaf>),
+}
+
+#[derive(Debug, Arbitrary)]
+enum Leaf {
+    Custom(String),
+    MinLength(usize),
+    MaxItems(usize),
+    Items(Vec<Leaf>, Vec<(u8, Tree)>),
+    Properties(Vec<Leaf>, Vec<(String, Tree)>),
+}
+
+impl From<Tree> for Errors {
+    fn from(tree: Tree) -> Self {
+        match tree {
+            Tree::Array(errors, items) => Errors::Array(array(errors, items)),
+            Tree::Object(errors, properties) => Errors::Object(object(errors, properties)),
+            Tree::NewType(errors) => Errors::NewType(leaves(errors)),
+        }
+    }
+}
+
+impl From<Leaf> for Error {
+    fn from(leaf: Leaf) -> Self {
+        match leaf {
+            Leaf::Custom(message) => Error::Custom(message),
+            Leaf::MinLength(min_length) => {
+                Error::MinLength(Message::new(MinLengthError { min_length }, Format::Default))
+            }
+            Leaf::MaxItems(max_items) => {
+                Error::MaxItems(Message::new(MaxItemsError { max_items }, Format::Default))
+            }
+            Leaf::Items(errors, items) => Error::Items(array(errors, items)),
+            Leaf::Properties(errors, properties) => Error::Properties(object(errors, properties)),
+        }
+    }
+}
+
+fn leaves(errors: Vec<Leaf>) -> Vec<Error> {
+    errors.into_iter().map(Error::from).collect()
+}
+
+fn array(errors: Vec<Leaf>, items: Vec<(u8, Tree)>) -> ArrayErrors {
+    let items = items
+        .into_iter()
+        .map(|(index, tree)| (usize::from(index), tree.into()))
+        .collect();
+    ArrayErrors::new(leaves(errors), items)
+}
+
+fn object(errors: Vec<Leaf>, properties: Vec<(String, Tree)>) -> ObjectErrors {
+    let properties = properties
+        .into_iter()
+        .map(|(name, tree)| (name, tree.into()))
+        .collect();
+    ObjectErrors::new(leaves(errors), properties)
+}
+
+fuzz_target!(|tree: Tree| {
+    let formatted = format_errors(tree.into());
+    serde_json::to_string(&formatted).unwrap();
+});
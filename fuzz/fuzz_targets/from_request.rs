@@ -0,0 +1,45 @@
+//! Feeds arbitrary bodies through `AppJson` extraction and error rendering, with debug
+//! details on so their diagnostics are exercised too.
+
+#![no_main]
+
+use actix_json_validator::{AppJson, JsonConfig};
+use actix_web::{
+    http::header::ContentType, rt::System, test::TestRequest, FromRequest, ResponseError,
+};
+use libfuzzer_sys::fuzz_target;
+use serde::Deserialize;
+use serde_valid::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+struct Order {
+    #[validate(min_length = 1)]
+    #[validate(max_length = 8)]
+    reference: String,
+    #[validate(min_items = 1)]
+    #[validate]
+    lines: Vec<Line>,
+    #[validate(max_length = 3)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct Line {
+    #[validate(minimum = 1)]
+    quantity: u32,
+    #[validate(enumerate = ["kg", "pcs"])]
+    unit: String,
+}
+
+fuzz_target!(|body: &[u8]| {
+    System::new().block_on(async {
+        let (req, mut payload) = TestRequest::post()
+            .app_data(JsonConfig::default().debug_errors(true))
+            .insert_header(ContentType::json())
+            .set_payload(body.to_vec())
+            .to_http_parts();
+        if let Err(err) = AppJson::<Order>::from_request(&req, &mut payload).await {
+            err.error_response();
+        }
+    });
+});
@@ -0,0 +1,126 @@
+use actix_web::{
+    dev::{ServiceFactory, ServiceRequest},
+    web, App, Error,
+};
+use serde_valid::Validate;
+
+use crate::AppError;
+
+/// Validate a configuration struct at startup, logging failures as `log::error!` in the
+/// shape clients get for payloads:
+///
+/// ```
+/// use actix_json_validator::validate_app_config;
+/// use serde_valid::Validate;
+///
+/// #[derive(serde::Deserialize, Validate)]
+/// struct Settings {
+///     #[validate(minimum = 1)]
+///     workers: usize,
+///     #[validate(pattern = "^https://")]
+///     upstream: String,
+/// }
+///
+/// let settings = Settings { workers: 4, upstream: "https://api.example.com".into() };
+/// let settings = validate_app_config(settings).expect("invalid settings");
+/// ```
+///
+/// An invalid config is logged as e.g.
+/// `invalid app config my_app::Settings: {"upstream":["The value must match the pattern of \"^https://\"."]}`.
+pub fn validate_app_config<T: Validate>(config: T) -> Result<T, AppError> {
+    config.validate().map(|()| config).map_err(|errors| {
+        let err = AppError::from(errors);
+        log::error!("{}", describe::<T>(&err));
+        err
+    })
+}
+
+fn describe<T>(err: &AppError) -> String {
+    let errors = match err {
+        AppError::ValidationError(errors) => serde_json::json!(errors),
+        AppError::Handled(err) => serde_json::json!(err.to_string()),
+    };
+    format!(
+        "invalid app config {}: {errors}",
+        std::any::type_name::<T>()
+    )
+}
+
+/// Registers app data once it passed [`validate_app_config`], so a misconfigured app
+/// fails to start instead of misbehaving on the first request:
+///
+/// ```should_panic
+/// use actix_json_validator::ValidatedAppData;
+/// use actix_web::App;
+/// # #[derive(serde_valid::Validate)]
+/// # struct Settings { #[validate(minimum = 1)] workers: usize }
+///
+/// // Panics with `invalid app config my_app::Settings: {"workers":["The number must be `>= 1`."]}`.
+/// let app = App::new().validated_data(Settings { workers: 0 });
+/// ```
+pub trait ValidatedAppData {
+    /// Like [`App::app_data`], for a validated `data`.
+    fn validated_app_data<U: Validate + 'static>(self, data: U) -> Self;
+
+    /// Like `App::app_data(web::Data::new(data))`, for a validated `data`, extracted as
+    /// `web::Data<U>`.
+    fn validated_data<U: Validate + 'static>(self, data: U) -> Self;
+}
+
+impl<T> ValidatedAppData for App<T>
+where
+    T: ServiceFactory<ServiceRequest, Config = (), Error = Error, InitError = ()>,
+{
+    #[track_caller]
+    fn validated_app_data<U: Validate + 'static>(self, data: U) -> Self {
+        self.app_data(validated(data))
+    }
+
+    #[track_caller]
+    fn validated_data<U: Validate + 'static>(self, data: U) -> Self {
+        self.app_data(web::Data::new(validated(data)))
+    }
+}
+
+#[track_caller]
+fn validated<U: Validate>(data: U) -> U {
+    validate_app_config(data).unwrap_or_else(|err| panic!("{}", describe::<U>(&err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, HttpResponse};
+
+    #[derive(Validate)]
+    struct Settings {
+        #[validate(minimum = 1)]
+        workers: usize,
+    }
+
+    #[actix_web::test]
+    async fn test_valid_config_is_registered() {
+        let app = test::init_service(
+            App::new()
+                .validated_data(Settings { workers: 4 })
+                .default_service(web::to(|settings: web::Data<Settings>| async move {
+                    HttpResponse::Ok().body(settings.workers.to_string())
+                })),
+        )
+        .await;
+        let res = test::call_service(&app, test::TestRequest::get().to_request()).await;
+        assert_eq!(test::read_body(res).await, "4");
+
+        let Err(err) = validate_app_config(Settings { workers: 0 }) else {
+            panic!("invalid settings passed validation");
+        };
+        assert_eq!(
+            describe::<Settings>(&err),
+            format!(
+                "invalid app config {}: {}",
+                std::any::type_name::<Settings>(),
+                r#"{"workers":["The number must be `>= 1`."]}"#
+            )
+        );
+    }
+}
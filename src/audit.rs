@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use actix_web::HttpRequest;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{severity::path_matches, JsonConfig};
+
+/// Receives the records of [`JsonConfig::audit`].
+pub(crate) type AuditSink = Arc<dyn Fn(AuditRecord) + Send + Sync>;
+
+/// A payload accepted by an extractor, as handed to the sink of [`JsonConfig::audit`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// [`std::any::type_name`] of the payload type.
+    pub type_name: &'static str,
+    pub method: String,
+    pub path: String,
+    /// The body as received, with the paths of [`JsonConfig::redact`] masked.
+    pub payload: Value,
+}
+
+/// Hand the accepted `body` of `req` to the audit sink, if any.
+pub(crate) fn record(config: &JsonConfig, req: &HttpRequest, type_name: &'static str, body: &[u8]) {
+    let Some(sink) = &config.audit else {
+        return;
+    };
+    // Already deserialized into the payload type, so well-formed.
+    let mut payload: Value = serde_json::from_slice(body).unwrap_or_default();
+    redact(&mut payload, "", &config.redactions);
+    sink(AuditRecord {
+        type_name,
+        method: req.method().to_string(),
+        path: req.path().to_string(),
+        payload,
+    });
+}
+
+fn redact(value: &mut Value, path: &str, patterns: &[String]) {
+    let children: Box<dyn Iterator<Item = (String, &mut Value)>> = match value {
+        Value::Object(fields) => Box::new(fields.iter_mut().map(|(k, v)| (k.clone(), v))),
+        Value::Array(items) => Box::new(
+            items
+                .iter_mut()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v)),
+        ),
+        _ => return,
+    };
+    for (key, child) in children {
+        let path = if path.is_empty() {
+            key
+        } else {
+            format!("{path}.{key}")
+        };
+        if patterns.iter().any(|pattern| path_matches(pattern, &path)) {
+            *child = Value::String("[REDACTED]".to_string());
+        } else {
+            redact(child, &path, patterns);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppJson;
+    use actix_web::{test, FromRequest};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+    use std::sync::mpsc;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        username: String,
+        #[allow(dead_code)]
+        password: String,
+        #[allow(dead_code)]
+        cards: Vec<Value>,
+    }
+
+    #[actix_web::test]
+    async fn test_accepted_payloads_are_audited_redacted() {
+        let (sender, records) = mpsc::channel();
+        let config = JsonConfig::default()
+            .redact("password")
+            .redact("cards.*.number")
+            .audit(move |record| sender.send(record).unwrap());
+
+        let (req, mut payload) = test::TestRequest::post()
+            .uri("/signup")
+            .app_data(config.clone())
+            .set_json(json!({
+                "username": "ada",
+                "password": "hunter2",
+                "cards": [{"number": "4111", "holder": "Ada"}]
+            }))
+            .to_http_parts();
+        AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        let record = records.try_recv().unwrap();
+        assert_eq!(
+            (record.method.as_str(), record.path.as_str()),
+            ("POST", "/signup")
+        );
+        assert_eq!(
+            record.payload,
+            json!({
+                "username": "ada",
+                "password": "[REDACTED]",
+                "cards": [{"number": "[REDACTED]", "holder": "Ada"}]
+            })
+        );
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"username": "al", "password": "x", "cards": []}))
+            .to_http_parts();
+        assert!(AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .is_err());
+        assert!(records.try_recv().is_err());
+    }
+}
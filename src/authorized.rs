@@ -0,0 +1,196 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpMessage, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{extract, format, AppError, AppJson, ErrorMap, FailureClass};
+
+/// Validation rules that depend on who sends the payload, checked by [`Authorized`]
+/// after validation.
+pub trait PrincipalRules {
+    /// The authenticated principal, as inserted into the request extensions by the
+    /// authentication middleware.
+    type Principal: 'static;
+
+    /// The fields `principal` may not send as they are, by dotted path, with a message.
+    /// `principal` is `None` for anonymous requests.
+    fn check(&self, principal: Option<&Self::Principal>) -> Vec<(&'static str, String)>;
+}
+
+/// Like [`AppJson`], then rejects the fields that the [`PrincipalRules`] of the payload
+/// refuse to the authenticated principal, so rules such as "only admins may feature a
+/// post" live with the other validation rules:
+///
+/// ```
+/// use actix_json_validator::{Authorized, PrincipalRules};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Post { #[validate(min_length = 1)] title: String, featured: bool }
+///
+/// /// Inserted into the request extensions by the authentication middleware.
+/// struct User {
+///     admin: bool,
+/// }
+///
+/// impl PrincipalRules for Post {
+///     type Principal = User;
+///
+///     fn check(&self, user: Option<&User>) -> Vec<(&'static str, String)> {
+///         let admin = user.is_some_and(|user| user.admin);
+///         if self.featured && !admin {
+///             return vec![("featured", "Only admins may feature a post.".to_string())];
+///         }
+///         Vec::new()
+///     }
+/// }
+///
+/// async fn create(post: Authorized<Post>) -> String {
+///     post.title.clone()
+/// }
+/// ```
+///
+/// Refused fields are reported like validation errors, nested by path, e.g.
+/// `{"featured": ["Only admins may feature a post."]}`, with `403 Forbidden`, and
+/// recorded in the [`ValidationReport`](crate::ValidationReport).
+#[derive(Debug)]
+pub struct Authorized<T>(pub T);
+
+impl<T> Authorized<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for Authorized<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for Authorized<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Authorized<T>
+where
+    T: DeserializeOwned + Validate + PrincipalRules + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let extraction = AppJson::<T>::from_request(req, payload);
+        let req = req.clone();
+
+        Box::pin(async move {
+            let data = extraction.await?.into_inner();
+            let started = Instant::now();
+            let refused = data.check(req.extensions().get::<T::Principal>());
+            if refused.is_empty() {
+                return Ok(Authorized(data));
+            }
+
+            let mut errors = ErrorMap::new();
+            for (path, message) in refused {
+                format::insert(&mut errors, path, message);
+            }
+            let config = extract::config(&req);
+            extract::classify(&req, FailureClass::Semantic);
+            let result = extract::finish(
+                &config,
+                &req,
+                std::any::type_name::<T>(),
+                started,
+                Err(AppError::ValidationError(errors)),
+            );
+            result.map_err(|err| match err {
+                AppError::ValidationError(_) => extract::with_status(err, StatusCode::FORBIDDEN),
+                err => err,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    struct User {
+        admin: bool,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Post {
+        #[validate(min_length = 1)]
+        title: String,
+        featured: bool,
+        #[validate]
+        seo: Seo,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Seo {
+        canonical: Option<String>,
+    }
+
+    impl PrincipalRules for Post {
+        type Principal = User;
+
+        fn check(&self, user: Option<&User>) -> Vec<(&'static str, String)> {
+            let admin = user.is_some_and(|user| user.admin);
+            let mut refused = Vec::new();
+            if self.featured && !admin {
+                refused.push(("featured", "Only admins may feature a post.".to_string()));
+            }
+            if self.seo.canonical.is_some() && user.is_none() {
+                refused.push(("seo.canonical", "Sign in to set it.".to_string()));
+            }
+            refused
+        }
+    }
+
+    async fn extract(user: Option<User>, body: Value) -> Result<Authorized<Post>, AppError> {
+        let (req, mut payload) = test::TestRequest::post().set_json(body).to_http_parts();
+        if let Some(user) = user {
+            req.extensions_mut().insert(user);
+        }
+        Authorized::<Post>::from_request(&req, &mut payload).await
+    }
+
+    #[actix_web::test]
+    async fn test_rules_see_the_principal() {
+        let body = json!({"title": "Hi", "featured": true, "seo": {"canonical": "/hi"}});
+
+        let err = extract(None, body.clone()).await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+        let bytes = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&bytes).unwrap(),
+            json!({
+                "featured": ["Only admins may feature a post."],
+                "seo": {"canonical": ["Sign in to set it."]}
+            })
+        );
+
+        let err = extract(Some(User { admin: false }), body.clone())
+            .await
+            .unwrap_err();
+        let bytes = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&bytes).unwrap(),
+            json!({"featured": ["Only admins may feature a post."]})
+        );
+
+        let post = extract(Some(User { admin: true }), body).await.unwrap();
+        assert!(post.featured);
+    }
+}
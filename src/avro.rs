@@ -0,0 +1,293 @@
+use std::{collections::HashMap, ops::Deref, sync::Arc, time::Instant};
+
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use apache_avro::{rabin::Rabin, Schema};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ConstraintKind, JsonConfig};
+
+/// Header carrying the hex-encoded Rabin (CRC-64-AVRO) fingerprint of the writer schema.
+pub const AVRO_FINGERPRINT_HEADER: &str = "x-avro-schema-fingerprint";
+
+/// Writer schemas available to [`AppAvro`], registered as app data.
+///
+/// Requests carrying an [`AVRO_FINGERPRINT_HEADER`] are decoded with the registered schema
+/// matching that fingerprint; other requests use the default schema.
+#[derive(Clone, Default)]
+pub struct AvroSchemas {
+    default: Option<Arc<Schema>>,
+    by_fingerprint: HashMap<String, Arc<Schema>>,
+}
+
+impl AvroSchemas {
+    /// Create a registry whose default schema is `schema`. The schema is also
+    /// selectable by its fingerprint.
+    pub fn new(schema: Schema) -> Self {
+        let schema = Arc::new(schema);
+        AvroSchemas {
+            default: Some(Arc::clone(&schema)),
+            by_fingerprint: HashMap::from([(fingerprint(&schema), schema)]),
+        }
+    }
+
+    /// Register an additional schema, selectable by its fingerprint.
+    pub fn register(mut self, schema: Schema) -> Self {
+        self.by_fingerprint
+            .insert(fingerprint(&schema), Arc::new(schema));
+        self
+    }
+
+    fn resolve(&self, req: &HttpRequest) -> Result<Arc<Schema>, String> {
+        match req.headers().get(AVRO_FINGERPRINT_HEADER) {
+            Some(value) => value
+                .to_str()
+                .ok()
+                .and_then(|value| self.by_fingerprint.get(&value.to_ascii_lowercase()))
+                .cloned()
+                .ok_or_else(|| "Unknown Avro schema fingerprint".to_string()),
+            None => self
+                .default
+                .clone()
+                .ok_or_else(|| "Missing Avro schema fingerprint".to_string()),
+        }
+    }
+}
+
+fn fingerprint(schema: &Schema) -> String {
+    schema.fingerprint::<Rabin>().to_string()
+}
+
+/// Extractor for `avro/binary` bodies, decoded with a schema from [`AvroSchemas`] and then
+/// validated like [`AppJson`](crate::AppJson).
+///
+/// Limits, error handler and debug settings are read from [`JsonConfig`].
+#[derive(Debug)]
+pub struct AppAvro<T>(pub T);
+
+impl<T> AppAvro<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppAvro<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppAvro<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppAvro<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let schemas = req.app_data::<AvroSchemas>().cloned().unwrap_or_default();
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                if config.content_type_required && !is_avro(&req, &config) {
+                    extract::fail(&req, ConstraintKind::ContentType);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "content_type",
+                        "Content type error".to_string(),
+                    ));
+                }
+
+                let schema = schemas.resolve(&req).map_err(|message| {
+                    extract::fail(&req, ConstraintKind::Deserialization);
+                    extract::decode_error(&config, type_name, "schema", message)
+                })?;
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let body = extract::read_body(&req, payload, config.limit)
+                    .await
+                    .map_err(|err| extract::body_error(&config, &req, type_name, err))?;
+                extract::soft_limit(&config, &req, body.len());
+
+                let data = apache_avro::from_avro_datum(&schema, &mut &body[..], None)
+                    .and_then(|value| apache_avro::from_value::<T>(&value))
+                    .map_err(|err| {
+                        extract::fail(&req, ConstraintKind::Deserialization);
+                        extract::decode_error(
+                            &config,
+                            type_name,
+                            "deserialization",
+                            err.to_string(),
+                        )
+                    })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppAvro)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+fn is_avro(req: &HttpRequest, config: &JsonConfig) -> bool {
+    match req.mime_type() {
+        Ok(Some(mime)) => {
+            (mime.type_() == "avro" && mime.subtype() == "binary")
+                || (mime.type_() == mime::APPLICATION && mime.subtype() == "avro")
+                || config
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|predicate| predicate(mime))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use actix_web::{body::MessageBody, http::StatusCode, test, ResponseError};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Reading",
+        "fields": [
+            {"name": "sensor", "type": "string"},
+            {"name": "value", "type": "int"}
+        ]
+    }"#;
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct Reading {
+        #[validate(min_length = 3)]
+        sensor: String,
+        #[validate(maximum = 100)]
+        value: i32,
+    }
+
+    fn encode(reading: &Reading) -> Vec<u8> {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        apache_avro::to_avro_datum(&schema, apache_avro::to_value(reading).unwrap()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_valid_avro_body() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(AvroSchemas::new(schema))
+            .insert_header(("content-type", "avro/binary"))
+            .set_payload(encode(&Reading {
+                sensor: "thermo-1".into(),
+                value: 21,
+            }))
+            .to_http_parts();
+
+        let reading = AppAvro::<Reading>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(reading.sensor, "thermo-1");
+        assert_eq!(reading.value, 21);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_avro_body_by_fingerprint() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(AvroSchemas::default().register(schema.clone()))
+            .insert_header((AVRO_FINGERPRINT_HEADER, fingerprint(&schema)))
+            .set_payload(encode(&Reading {
+                sensor: "t1".into(),
+                value: 250,
+            }))
+            .to_http_parts();
+
+        let res = AppAvro::<Reading>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "sensor": ["The length of the value must be `>= 3`."],
+                "value": ["The number must be `<= 100`."]
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_fingerprint() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(AvroSchemas::new(Schema::parse_str(SCHEMA).unwrap()))
+            .insert_header((AVRO_FINGERPRINT_HEADER, "00"))
+            .set_payload(vec![0u8])
+            .to_http_parts();
+
+        let res = AppAvro::<Reading>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            body,
+            json!({"error": ["Unknown Avro schema fingerprint"]}).to_string()
+        );
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+
+    #[actix_web::test]
+    async fn test_failures_are_classified() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let extract = |content_type: &'static str, body: Vec<u8>| {
+            let (req, mut payload) = test::TestRequest::post()
+                .app_data(AvroSchemas::new(schema.clone()))
+                .app_data(JsonConfig::default().content_type_required(true))
+                .insert_header(("content-type", content_type))
+                .set_payload(body)
+                .to_http_parts();
+            async move {
+                AppAvro::<Reading>::from_request(&req, &mut payload)
+                    .await
+                    .unwrap_err();
+                let extensions = req.extensions();
+                extensions.get::<ValidationReport>().unwrap().failures[0]
+                    .kind
+                    .clone()
+            }
+        };
+
+        assert_eq!(
+            extract("application/json", vec![0u8]).await,
+            ConstraintKind::ContentType
+        );
+        assert_eq!(
+            extract("avro/binary", vec![0xff]).await,
+            ConstraintKind::Deserialization
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use std::{any::Any, collections::HashMap, sync::Arc, time::Instant};
+
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ErrorMap, JsonConfig};
+
+type Parse =
+    dyn Fn(&[u8], &JsonConfig, &HttpRequest) -> Result<Box<dyn Any>, AppError> + Send + Sync;
+
+/// Part types accepted by [`AppBatch`], registered as app data.
+#[derive(Clone)]
+pub struct BatchConfig {
+    types: HashMap<String, Arc<Parse>>,
+    type_header: String,
+    max_parts: usize,
+}
+
+impl BatchConfig {
+    /// Accept parts of type `name`, deserialized and validated as `T`.
+    pub fn part<T>(mut self, name: impl Into<String>) -> Self
+    where
+        T: DeserializeOwned + Validate + 'static,
+    {
+        let parse = |body: &[u8], config: &JsonConfig, req: &HttpRequest| {
+            let type_name = std::any::type_name::<T>();
+            let data = extract::deserialize::<T>(config, req, type_name, body)?;
+            let data = extract::validate(data, config, req, type_name)?;
+            Ok(Box::new(data) as Box<dyn Any>)
+        };
+        self.types.insert(name.into(), Arc::new(parse));
+        self
+    }
+
+    /// Read the type of each part from the part header `name` instead of `X-Part-Type`.
+    pub fn type_header(mut self, name: impl Into<String>) -> Self {
+        self.type_header = name.into();
+        self
+    }
+
+    /// Reject batches of more than `max_parts` parts, 100 by default.
+    pub fn max_parts(mut self, max_parts: usize) -> Self {
+        self.max_parts = max_parts;
+        self
+    }
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig {
+            types: HashMap::new(),
+            type_header: "x-part-type".to_string(),
+            max_parts: 100,
+        }
+    }
+}
+
+/// A validated part of an [`AppBatch`].
+#[derive(Debug)]
+pub struct BatchPart {
+    /// The `Content-ID` of the part, if any.
+    pub id: Option<String>,
+    /// The type of the part, as registered with [`BatchConfig::part`].
+    pub kind: String,
+    value: Box<dyn Any>,
+}
+
+impl BatchPart {
+    /// The payload, if the part is of type `T`.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// The payload, if the part is of type `T`; the part itself otherwise.
+    pub fn downcast<T: 'static>(self) -> Result<T, Self> {
+        match self.value.downcast() {
+            Ok(value) => Ok(*value),
+            Err(value) => Err(BatchPart { value, ..self }),
+        }
+    }
+}
+
+/// Extractor for `multipart/mixed` batch requests whose parts are JSON documents. Each
+/// part names its type in an `X-Part-Type` header and is validated as the payload type
+/// registered for it in [`BatchConfig`]:
+///
+/// ```
+/// use actix_json_validator::{AppBatch, BatchConfig};
+/// use actix_web::{web, App};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct CreateOrder { #[validate(minimum = 1)] quantity: u32 }
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct CreateRefund { #[validate(minimum = 1)] amount: u32 }
+///
+/// async fn batch(batch: AppBatch) -> String {
+///     let orders = batch.iter().filter_map(|part| part.downcast_ref::<CreateOrder>());
+///     format!("{} orders", orders.count())
+/// }
+///
+/// let app = App::new()
+///     .app_data(BatchConfig::default().part::<CreateOrder>("order").part::<CreateRefund>("refund"))
+///     .route("/batch", web::post().to(batch));
+/// ```
+///
+/// The whole batch is rejected if any part fails, with the errors of each failed part
+/// under its index:
+///
+/// ```json
+/// {"0": {"quantity": ["The number must be `>= 1`."]}, "2": {"error": ["Unknown part type `invoice`"]}}
+/// ```
+///
+/// The size limit of [`JsonConfig`] applies to the whole batch.
+#[derive(Debug)]
+pub struct AppBatch(pub Vec<BatchPart>);
+
+impl AppBatch {
+    /// Deconstruct to the parts
+    pub fn into_inner(self) -> Vec<BatchPart> {
+        self.0
+    }
+}
+
+impl std::ops::Deref for AppBatch {
+    type Target = [BatchPart];
+
+    fn deref(&self) -> &[BatchPart] {
+        &self.0
+    }
+}
+
+impl IntoIterator for AppBatch {
+    type Item = BatchPart;
+    type IntoIter = std::vec::IntoIter<BatchPart>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl FromRequest for AppBatch {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let batch_config = req.app_data::<BatchConfig>().cloned().unwrap_or_default();
+        let type_name = std::any::type_name::<Self>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                let boundary = match req.mime_type() {
+                    Ok(Some(mime))
+                        if mime.type_() == mime::MULTIPART && mime.subtype() == "mixed" =>
+                    {
+                        mime.get_param(mime::BOUNDARY).map(|name| name.to_string())
+                    }
+                    _ => {
+                        return Err(extract::decode_error(
+                            &config,
+                            type_name,
+                            "content_type",
+                            "Content type error".to_string(),
+                        ))
+                    }
+                };
+                let Some(boundary) = boundary else {
+                    let message = "Missing multipart boundary".to_string();
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "multipart",
+                        message,
+                    ));
+                };
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let body = extract::read_body(&req, payload, config.limit)
+                    .await
+                    .map_err(|err| extract::body_error(&config, &req, type_name, err))?;
+                extract::soft_limit(&config, &req, body.len());
+
+                let parts = split(&body, &boundary).ok_or_else(|| {
+                    let message = "Malformed multipart body".to_string();
+                    extract::decode_error(&config, type_name, "multipart", message)
+                })?;
+                if parts.len() > batch_config.max_parts {
+                    let message =
+                        format!("A batch may have at most {} parts", batch_config.max_parts);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "max_parts",
+                        message,
+                    ));
+                }
+
+                let mut batch = Vec::new();
+                let mut errors = ErrorMap::new();
+                for (index, part) in parts.into_iter().enumerate() {
+                    match parse_part(&part, &batch_config, &config, &req) {
+                        Ok(part) => batch.push(part),
+                        Err(AppError::ValidationError(part_errors)) => {
+                            errors.insert(index.to_string(), json!(part_errors));
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+                match errors.is_empty() {
+                    true => Ok(AppBatch(batch)),
+                    false => Err(AppError::ValidationError(errors)),
+                }
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+fn parse_part(
+    part: &Part,
+    batch_config: &BatchConfig,
+    config: &JsonConfig,
+    req: &HttpRequest,
+) -> Result<BatchPart, AppError> {
+    let error = |message: String| extract::payload_error(message, None);
+    let is_json = part.header("content-type").is_some_and(|value| {
+        value
+            .parse::<mime::Mime>()
+            .is_ok_and(|mime| extract::is_json_type(mime, config))
+    });
+    if !is_json {
+        return Err(error("Content type error".to_string()));
+    }
+    let Some(kind) = part.header(&batch_config.type_header) else {
+        return Err(error(format!(
+            "Missing `{}` header",
+            batch_config.type_header
+        )));
+    };
+    let Some(parse) = batch_config.types.get(kind) else {
+        return Err(error(format!("Unknown part type `{kind}`")));
+    };
+    Ok(BatchPart {
+        id: part.header("content-id").map(str::to_string),
+        kind: kind.to_string(),
+        value: parse(part.body, config, req)?,
+    })
+}
+
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl Part<'_> {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Split a multipart body (RFC 2046) into its parts, ignoring preamble and epilogue.
+/// Returns `None` if the closing delimiter or a part's header block is missing.
+fn split<'a>(body: &'a [u8], boundary: &str) -> Option<Vec<Part<'a>>> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    // The first delimiter may open the body; later ones follow a line break.
+    let mut rest = match body.strip_prefix(delimiter) {
+        Some(rest) => rest,
+        None => &body[find(body, &[b"\r\n", delimiter].concat())? + 2 + delimiter.len()..],
+    };
+
+    let mut parts = Vec::new();
+    let separator = [b"\r\n", delimiter].concat();
+    loop {
+        if rest.starts_with(b"--") {
+            return Some(parts);
+        }
+        // Skip transport padding up to the end of the delimiter line.
+        rest = &rest[find(rest, b"\r\n")? + 2..];
+        let end = find(rest, &separator)?;
+        let (headers, body) = match rest[..end].strip_prefix(b"\r\n") {
+            Some(body) => (&b""[..], body),
+            None => {
+                let split = find(&rest[..end], b"\r\n\r\n")?;
+                (&rest[..split], &rest[split + 4..end])
+            }
+        };
+        let headers = String::from_utf8_lossy(headers)
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        parts.push(Part { headers, body });
+        rest = &rest[end + separator.len()..];
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstraintKind, ValidationReport};
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct CreateOrder {
+        #[validate(minimum = 1)]
+        quantity: u32,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct CreateRefund {
+        #[validate(min_length = 1)]
+        reason: String,
+    }
+
+    fn part(kind: &str, id: &str, body: &str) -> String {
+        format!(
+            "--b0undary\r\nContent-Type: application/json\r\nX-Part-Type: {kind}\r\nContent-ID: {id}\r\n\r\n{body}\r\n"
+        )
+    }
+
+    async fn extract(body: String) -> Result<AppBatch, Value> {
+        let config = BatchConfig::default()
+            .part::<CreateOrder>("order")
+            .part::<CreateRefund>("refund");
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .insert_header(("content-type", "multipart/mixed; boundary=b0undary"))
+            .set_payload(body)
+            .to_http_parts();
+        AppBatch::from_request(&req, &mut payload)
+            .await
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_parts_are_validated_as_their_type() {
+        let body = format!(
+            "preamble\r\n{}{}--b0undary--\r\nepilogue",
+            part("order", "<1>", r#"{"quantity": 2}"#),
+            part("refund", "<2>", r#"{"reason": "damaged"}"#)
+        );
+        let mut parts = extract(body).await.unwrap().into_iter();
+
+        let order = parts.next().unwrap();
+        assert_eq!(order.id.as_deref(), Some("<1>"));
+        assert_eq!(order.downcast::<CreateOrder>().unwrap().quantity, 2);
+        let refund = parts.next().unwrap();
+        assert_eq!(refund.kind, "refund");
+        assert!(refund.downcast_ref::<CreateOrder>().is_none());
+        assert_eq!(
+            refund.downcast_ref::<CreateRefund>().unwrap().reason,
+            "damaged"
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_errors_are_reported_per_part() {
+        let body = format!(
+            "{}{}{}--b0undary--\r\n",
+            part("order", "<1>", r#"{"quantity": 0}"#),
+            part("refund", "<2>", r#"{"reason": "damaged"}"#),
+            part("invoice", "<3>", "{}")
+        );
+        assert_eq!(
+            extract(body).await.unwrap_err(),
+            json!({
+                "0": {"quantity": ["The number must be `>= 1`."]},
+                "2": {"error": ["Unknown part type `invoice`"]}
+            })
+        );
+
+        let body = part("order", "<1>", r#"{"quantity": 1}"#);
+        assert_eq!(
+            extract(body).await.unwrap_err(),
+            json!({"error": ["Malformed multipart body"]})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_undeserializable_part_is_reported() {
+        let config = BatchConfig::default().part::<CreateOrder>("order");
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .insert_header(("content-type", "multipart/mixed; boundary=b0undary"))
+            .set_payload(format!(
+                "{}--b0undary--\r\n",
+                part("order", "<1>", r#"{"quantity": "two"}"#)
+            ))
+            .to_http_parts();
+        AppBatch::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Instant;
+
+use actix_web::{dev::Payload, web::Bytes, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::Deserialize;
+use serde_valid::Validate;
+
+use crate::{extract, AppError, ConstraintKind, JsonConfig};
+
+/// A buffered JSON body that payload types borrowing from it are parsed from, so string
+/// fields that are only read need not be copied out of the request.
+///
+/// Content type and size limit are checked during extraction, as for
+/// [`LazyAppJson`](crate::LazyAppJson); [`parse`](BorrowedAppJson::parse) then
+/// deserializes and validates a type whose lifetime is tied to the body:
+///
+/// ```
+/// use std::borrow::Cow;
+/// use actix_json_validator::{AppError, BorrowedAppJson};
+/// use serde::Deserialize;
+/// use serde_valid::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Event<'a> {
+///     #[serde(borrow)]
+///     #[validate(min_length = 1)]
+///     kind: Cow<'a, str>,
+///     source: &'a str,
+/// }
+///
+/// async fn ingest(body: BorrowedAppJson) -> Result<String, AppError> {
+///     let event: Event<'_> = body.parse()?;
+///     Ok(format!("{} from {}", event.kind, event.source))
+/// }
+/// ```
+///
+/// `Cow<'a, str>` fields with `#[serde(borrow)]` borrow when they can and fall back to an
+/// owned copy for strings containing escapes; `&'a str` fields reject such strings with a
+/// deserialization error.
+pub struct BorrowedAppJson {
+    body: Bytes,
+    req: HttpRequest,
+    config: JsonConfig,
+}
+
+impl BorrowedAppJson {
+    /// Deserialize and validate the body as `T`, borrowing from it. Failures go through
+    /// the error handler of [`JsonConfig`] and end up in the
+    /// [`ValidationReport`](crate::ValidationReport) like those of
+    /// [`AppJson`](crate::AppJson).
+    pub fn parse<'a, T>(&'a self) -> Result<T, AppError>
+    where
+        T: Deserialize<'a> + Validate,
+    {
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let result = serde_json::from_slice::<T>(&self.body)
+            .map_err(|err| {
+                extract::fail(&self.req, ConstraintKind::Deserialization);
+                let message = format!("Json deserialize error: {err}");
+                extract::decode_error(&self.config, type_name, "deserialization", message)
+            })
+            .and_then(|data| extract::validate(data, &self.config, &self.req, type_name));
+        extract::finish(&self.config, &self.req, type_name, started, result)
+    }
+
+    /// The buffered body, as received.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+}
+
+impl FromRequest for BorrowedAppJson {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<Self>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            match extract::json_body(&config, &req, payload, type_name).await {
+                Ok(body) => Ok(BorrowedAppJson { body, req, config }),
+                Err(err) => extract::finish(&config, &req, type_name, started, Err(err)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use actix_web::{test, HttpMessage};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Event<'a> {
+        #[serde(borrow)]
+        #[validate(min_length = 3)]
+        kind: Cow<'a, str>,
+        source: &'a str,
+    }
+
+    async fn extract(body: serde_json::Value) -> BorrowedAppJson {
+        let (req, mut payload) = test::TestRequest::post().set_json(body).to_http_parts();
+        BorrowedAppJson::from_request(&req, &mut payload)
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_strings_borrow_from_the_body() {
+        let body = extract(json!({"kind": "click", "source": "web"})).await;
+        let event: Event = body.parse().unwrap();
+        assert!(matches!(event.kind, Cow::Borrowed("click")));
+        assert_eq!(event.source, "web");
+
+        let body = extract(json!({"kind": "say \"hi\"", "source": "web"})).await;
+        let event: Event = body.parse().unwrap();
+        assert!(matches!(event.kind, Cow::Owned(ref kind) if kind == "say \"hi\""));
+    }
+
+    #[actix_web::test]
+    async fn test_failures_have_the_usual_shape() {
+        let body = extract(json!({"kind": "ok", "source": "web"})).await;
+        match body.parse::<Event>().unwrap_err() {
+            AppError::ValidationError(errors) => assert_eq!(
+                json!(errors),
+                json!({"kind": ["The length of the value must be `>= 3`."]})
+            ),
+            AppError::Handled(err) => panic!("unexpected error: {err}"),
+        }
+
+        let body = extract(json!({"kind": "click", "source": "\u{e9}\n"})).await;
+        assert!(body.parse::<Event>().is_err());
+        let extensions = body.req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+}
@@ -0,0 +1,167 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::{error::InternalError, http::StatusCode, HttpRequest, HttpResponse};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{extract, AppError, JsonConfig};
+
+/// Bytes of request bodies that all extractions sharing the budget may buffer at once,
+/// registered with [`JsonConfig::memory_budget`].
+///
+/// Each extraction reserves its `Content-Length`, or the size limit when the length is
+/// unknown, while its body is read. Requests that do not fit are rejected with
+/// `503 Service Unavailable`, or first wait for room when [`wait`](Self::wait) is set:
+///
+/// ```
+/// use std::time::Duration;
+/// use actix_json_validator::{JsonConfig, MemoryBudget};
+///
+/// // At most 64 MiB of bodies in memory across all workers.
+/// let budget = MemoryBudget::new(64 * 1024 * 1024).wait(Duration::from_millis(200));
+/// let config = JsonConfig::default().memory_budget(budget);
+/// ```
+///
+/// The budget is shared by clones, so create it once, outside the `HttpServer` factory.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    semaphore: Arc<Semaphore>,
+    capacity: u32,
+    wait: Option<Duration>,
+}
+
+impl MemoryBudget {
+    /// A budget of `bytes`, capped at 4 GiB.
+    pub fn new(bytes: usize) -> Self {
+        let capacity = u32::try_from(bytes).unwrap_or(u32::MAX);
+        MemoryBudget {
+            semaphore: Arc::new(Semaphore::new(capacity as usize)),
+            capacity,
+            wait: None,
+        }
+    }
+
+    /// Wait up to `timeout` for room in the budget before rejecting a request.
+    pub fn wait(mut self, timeout: Duration) -> Self {
+        self.wait = Some(timeout);
+        self
+    }
+
+    /// Bytes currently available.
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    async fn reserve(&self, bytes: usize) -> Option<OwnedSemaphorePermit> {
+        // A body larger than the whole budget still gets through on its own.
+        let bytes = u32::try_from(bytes).unwrap_or(u32::MAX).min(self.capacity);
+        let semaphore = Arc::clone(&self.semaphore);
+        match self.wait {
+            Some(timeout) => tokio::time::timeout(timeout, semaphore.acquire_many_owned(bytes))
+                .await
+                .ok()?
+                .ok(),
+            None => semaphore.try_acquire_many_owned(bytes).ok(),
+        }
+    }
+}
+
+/// Reserve room for the body of `req` in the configured budget, if any. The reservation
+/// lasts until the returned permit is dropped.
+pub(crate) async fn reserve(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+) -> Result<Option<OwnedSemaphorePermit>, AppError> {
+    let Some(budget) = &config.memory_budget else {
+        return Ok(None);
+    };
+    let length = extract::content_length(req);
+    let bytes = length.unwrap_or(config.limit).min(config.limit);
+
+    match budget.reserve(bytes).await {
+        Some(permit) => Ok(Some(permit)),
+        None => {
+            let err = extract::decode_error(
+                config,
+                type_name,
+                "memory_budget",
+                "The server is busy, please retry later".to_string(),
+            );
+            let response = match &err {
+                AppError::ValidationError(errors) => {
+                    HttpResponse::build(StatusCode::SERVICE_UNAVAILABLE).json(errors)
+                }
+                AppError::Handled(err) => err.error_response(),
+            };
+            Err(AppError::Handled(
+                InternalError::from_response(err, response).into(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppJson;
+    use actix_web::{body::MessageBody, test, FromRequest, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Upload {
+        #[validate(min_length = 1)]
+        name: String,
+    }
+
+    #[actix_web::test]
+    async fn test_requests_over_budget_are_rejected() {
+        let budget = MemoryBudget::new(64);
+        let config = JsonConfig::default().memory_budget(budget.clone());
+        let held = budget.reserve(40).await.unwrap();
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config.clone())
+            .set_json(json!({"name": "a fairly long file name.txt"}))
+            .to_http_parts();
+        let err = AppJson::<Upload>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            body,
+            json!({"error": ["The server is busy, please retry later"]}).to_string()
+        );
+
+        drop(held);
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"name": "a fairly long file name.txt"}))
+            .to_http_parts();
+        assert!(AppJson::<Upload>::from_request(&req, &mut payload)
+            .await
+            .is_ok());
+        assert_eq!(budget.available(), 64);
+    }
+
+    #[actix_web::test]
+    async fn test_waiting_for_room() {
+        let budget = MemoryBudget::new(64).wait(Duration::from_secs(5));
+        let held = budget.reserve(64).await.unwrap();
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().memory_budget(budget.clone()))
+            .set_json(json!({"name": "notes.txt"}))
+            .to_http_parts();
+
+        let extraction = AppJson::<Upload>::from_request(&req, &mut payload);
+        actix_web::rt::spawn(async move {
+            actix_web::rt::time::sleep(Duration::from_millis(10)).await;
+            drop(held);
+        });
+
+        assert_eq!(extraction.await.unwrap().name, "notes.txt");
+    }
+}
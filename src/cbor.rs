@@ -0,0 +1,188 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{
+    dev::{Decompress, Payload},
+    FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ConstraintKind, JsonConfig};
+
+/// Extract a validated `application/cbor` body, as posted by constrained devices, with
+/// the same limits, validation and error body as [`AppJson`](crate::AppJson). Requires
+/// the `cbor` feature.
+///
+/// ```
+/// use actix_json_validator::AppCbor;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Reading { #[validate(maximum = 100)] value: i32 }
+///
+/// async fn ingest(reading: AppCbor<Reading>) -> String {
+///     format!("Recorded {}", reading.value)
+/// }
+/// ```
+///
+/// Errors are still reported as JSON. Content types with a `+cbor` suffix, or accepted
+/// by the [`content_type`](JsonConfig::content_type) predicate, are accepted too; a
+/// body without a content type is accepted unless
+/// [`content_type_required`](JsonConfig::content_type_required) is set.
+#[derive(Debug)]
+pub struct AppCbor<T>(pub T);
+
+impl<T> AppCbor<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppCbor<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppCbor<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppCbor<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                if !is_cbor(&req, &config) {
+                    extract::fail(&req, ConstraintKind::ContentType);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "content_type",
+                        "Content type error".to_string(),
+                    ));
+                }
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let payload = Decompress::from_headers(payload, req.headers());
+                let body =
+                    extract::read_body(&req, Payload::from(payload.boxed_local()), config.limit)
+                        .await
+                        .map_err(|err| extract::body_error(&config, &req, type_name, err))?;
+                extract::soft_limit(&config, &req, body.len());
+
+                let data = ciborium::from_reader::<T, _>(&body[..]).map_err(|err| {
+                    extract::fail(&req, ConstraintKind::Deserialization);
+                    let message = format!("CBOR deserialize error: {err}");
+                    extract::decode_error(&config, type_name, "deserialization", message)
+                })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppCbor)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+fn is_cbor(req: &HttpRequest, config: &JsonConfig) -> bool {
+    match req.mime_type() {
+        Ok(Some(mime)) => {
+            (mime.type_() == mime::APPLICATION && mime.subtype() == "cbor")
+                || mime.suffix().is_some_and(|suffix| suffix == "cbor")
+                || config
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|predicate| predicate(mime))
+        }
+        Ok(None) => !config.content_type_required,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Serialize, Deserialize, Validate)]
+    struct Reading {
+        #[validate(min_length = 3)]
+        sensor: String,
+        #[validate(maximum = 100)]
+        value: i32,
+    }
+
+    async fn extract(content_type: &str, body: Vec<u8>) -> Result<Reading, Value> {
+        let (req, mut payload) = test::TestRequest::post()
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_http_parts();
+        AppCbor::<Reading>::from_request(&req, &mut payload)
+            .await
+            .map(AppCbor::into_inner)
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    fn encode(sensor: &str, value: i32) -> Vec<u8> {
+        let mut body = Vec::new();
+        let reading = Reading {
+            sensor: sensor.to_string(),
+            value,
+        };
+        ciborium::into_writer(&reading, &mut body).unwrap();
+        body
+    }
+
+    #[actix_web::test]
+    async fn test_cbor_bodies() {
+        let reading = extract("application/cbor", encode("thermo-1", 21))
+            .await
+            .unwrap();
+        assert_eq!((reading.sensor.as_str(), reading.value), ("thermo-1", 21));
+
+        assert_eq!(
+            extract("application/cbor", encode("t1", 250))
+                .await
+                .unwrap_err(),
+            json!({
+                "sensor": ["The length of the value must be `>= 3`."],
+                "value": ["The number must be `<= 100`."]
+            })
+        );
+        assert_eq!(
+            extract("application/json", encode("thermo-1", 21))
+                .await
+                .unwrap_err(),
+            json!({"error": ["Content type error"]})
+        );
+        let Value::Object(err) = extract("application/cbor", vec![0xff]).await.unwrap_err() else {
+            panic!("malformed CBOR body was accepted");
+        };
+        assert!(err["error"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("CBOR deserialize error"));
+    }
+}
@@ -0,0 +1,178 @@
+//! Business error codes attached to `custom` validation errors, used to pick the
+//! response status configured with [`JsonConfig::code_status`](crate::JsonConfig::code_status).
+
+use actix_web::http::StatusCode;
+use serde_valid::validation::{Error, Errors as ValidationError};
+
+use crate::{format, template, JsonConfig};
+
+/// Separates the code from the message inside the `Custom` error.
+const MARK: char = '\u{1f}';
+
+/// A `custom` validation error tagged with a business error `code` (e.g.
+/// `"already_exists"`). The code never shows in error bodies; it selects the status
+/// mapped with [`JsonConfig::code_status`]:
+///
+/// ```
+/// use actix_json_validator::coded_error;
+///
+/// fn not_blocked(domain: &str) -> Result<(), serde_valid::validation::Error> {
+///     if domain.ends_with(".invalid") {
+///         return Err(coded_error("forbidden_value", "This domain is blocked."));
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn coded_error(code: &str, message: impl Into<String>) -> Error {
+    Error::Custom(format!("{MARK}{code}{MARK}{}", message.into()))
+}
+
+/// Split a coded message into its code and message.
+fn split(message: &str) -> Option<(&str, &str)> {
+    message.strip_prefix(MARK)?.split_once(MARK)
+}
+
+/// The code of a [`coded_error`].
+pub(crate) fn code(error: &Error) -> Option<&str> {
+    match error {
+        Error::Custom(message) => split(message).map(|(code, _)| code),
+        _ => None,
+    }
+}
+
+/// The message of `error`, without the code of a [`coded_error`]. Default messages of
+/// built-in constraints come from the crate's own templates.
+pub(crate) fn message(error: &Error) -> String {
+    match error {
+        Error::Custom(message) => split(message)
+            .map_or(message.as_str(), |(_, message)| message)
+            .to_string(),
+        error => template::render(error).unwrap_or_else(|| error.to_string()),
+    }
+}
+
+/// The status mapped to the first failure with a code that has one, looking at the
+/// mappings of `type_name` before the global ones.
+pub(crate) fn status(
+    errors: &ValidationError,
+    config: &JsonConfig,
+    type_name: &str,
+) -> Option<StatusCode> {
+    if config.code_statuses.is_empty() {
+        return None;
+    }
+    let mut codes = Vec::new();
+    collect(&mut codes, errors);
+    let lookup = |scope: Option<&str>| {
+        codes.iter().find_map(|code| {
+            config
+                .code_statuses
+                .iter()
+                .find(|rule| rule.code == *code && rule.type_name.as_deref() == scope)
+                .map(|rule| rule.status)
+        })
+    };
+    lookup(Some(type_name)).or_else(|| lookup(None))
+}
+
+fn collect<'a>(codes: &mut Vec<&'a str>, errors: &'a ValidationError) {
+    format::walk(errors, "", &mut |_, error| {
+        if let Error::Custom(message) = error {
+            codes.extend(split(message).map(|(code, _)| code));
+        }
+    });
+}
+
+/// A status for failures with a code, optionally only for one payload type.
+#[derive(Clone)]
+pub(crate) struct CodeStatus {
+    pub(crate) type_name: Option<String>,
+    pub(crate) code: String,
+    pub(crate) status: StatusCode,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, FailureClass};
+    use actix_web::{body::MessageBody, test, FromRequest, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    fn available(email: &str) -> Result<(), Error> {
+        match email {
+            "taken@example.com" => Err(coded_error("already_exists", "Already registered.")),
+            "root@example.com" => Err(coded_error("forbidden_value", "Reserved address.")),
+            _ => Ok(()),
+        }
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(custom = available)]
+        email: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Invite {
+        #[validate(custom = available)]
+        email: String,
+    }
+
+    async fn respond<T>(config: &JsonConfig, email: &str) -> (StatusCode, serde_json::Value)
+    where
+        T: serde::de::DeserializeOwned + Validate + std::fmt::Debug + 'static,
+    {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config.clone())
+            .set_json(json!({"email": email}))
+            .to_http_parts();
+        let err = AppJson::<T>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let response = err.error_response();
+        let status = response.status();
+        let body = response.into_body().try_into_bytes().unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_codes_select_status() {
+        let config = JsonConfig::default()
+            .status(FailureClass::Semantic, StatusCode::UNPROCESSABLE_ENTITY)
+            .code_status("already_exists", StatusCode::CONFLICT)
+            .code_status("forbidden_value", StatusCode::FORBIDDEN)
+            .code_status_for::<Invite>("already_exists", StatusCode::BAD_REQUEST);
+
+        assert_eq!(
+            respond::<Signup>(&config, "taken@example.com").await,
+            (
+                StatusCode::CONFLICT,
+                json!({"email": ["Already registered."]})
+            )
+        );
+        assert_eq!(
+            respond::<Signup>(&config, "root@example.com").await.0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            respond::<Invite>(&config, "taken@example.com").await.0,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            respond::<Invite>(&config, "root@example.com").await.0,
+            StatusCode::FORBIDDEN
+        );
+
+        let config =
+            JsonConfig::default().status(FailureClass::Semantic, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            respond::<Signup>(&config, "taken@example.com").await,
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                json!({"email": ["Already registered."]})
+            )
+        );
+    }
+}
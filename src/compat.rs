@@ -0,0 +1,30 @@
+//! The parts of actix-web whose API differs between major versions, behind the
+//! `actix-web-4` feature (the default), so supporting a new major means adding its
+//! versions of these items, and a feature selecting them, rather than touching every
+//! extractor.
+
+use actix_web::{dev::Payload, web::JsonBody, HttpRequest};
+use serde::de::DeserializeOwned;
+
+use crate::JsonConfig;
+
+#[cfg(not(feature = "actix-web-4"))]
+compile_error!(
+    "actix-json-validator needs the `actix-web-4` feature, its only actix-web integration"
+);
+
+/// actix-web's own JSON body reader, with the content type rules and size limit of
+/// `config`.
+pub(crate) fn json_body<T: DeserializeOwned>(
+    req: &HttpRequest,
+    payload: &mut Payload,
+    config: &JsonConfig,
+) -> JsonBody<T> {
+    JsonBody::new(
+        req,
+        payload,
+        config.content_type.as_deref(),
+        config.content_type_required,
+    )
+    .limit(config.limit)
+}
@@ -0,0 +1,1250 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use actix_web::{
+    error::InternalError,
+    http::StatusCode,
+    web::{Bytes, ServiceConfig},
+    HttpRequest, HttpResponse,
+};
+use serde_json::Value;
+use serde_valid::validation::Errors as ValidationErrors;
+
+use crate::{
+    audit::AuditSink, code::CodeStatus, error_format::Renderer, severity::SeverityRule, AppError,
+    AuditRecord, Describe, FailureClass, MemoryBudget, MessageEscape, Severity, TypeMetadata,
+    ValidationStats,
+};
+
+/// Transforms a body before it is parsed, see [`JsonConfig::transform_body`].
+pub(crate) type BodyTransform =
+    Arc<dyn Fn(Bytes, &HttpRequest) -> Result<Bytes, AppError> + Send + Sync>;
+
+/// Resolves the locale of a request's messages, see [`JsonConfig::locale`].
+#[cfg(feature = "i18n")]
+pub(crate) type LocaleResolver = Arc<dyn Fn(&HttpRequest) -> Option<String> + Send + Sync>;
+
+/// Decides whether a request may use [`JsonConfig::debug_query`].
+pub(crate) type RequestGuard = Arc<dyn Fn(&HttpRequest) -> bool + Send + Sync>;
+
+/// Renders the whole body of a validation error, see [`JsonConfig::error_body`].
+pub(crate) type ErrorBody = Arc<dyn Fn(ValidationErrors, &HttpRequest) -> Value + Send + Sync>;
+
+/// Custom error handler. Receives the structured error, the request and the
+/// [`std::any::type_name`] of the payload type that failed to extract.
+pub(crate) type ErrHandler =
+    Arc<dyn Fn(AppError, &HttpRequest, &'static str) -> actix_web::Error + Send + Sync>;
+
+/// Options shared by the crate's extractors, registered as app data.
+///
+/// A config can also be chosen per request: one inserted into the request extensions,
+/// typically by middleware that resolved the tenant or client, takes precedence over the
+/// app data. This lets each tenant have its own error envelope, statuses or debug
+/// settings:
+///
+/// ```
+/// use actix_json_validator::JsonConfig;
+/// use actix_web::{
+///     body::MessageBody,
+///     dev::{ServiceRequest, ServiceResponse},
+///     middleware::Next,
+///     HttpMessage,
+/// };
+///
+/// async fn tenant_config(
+///     req: ServiceRequest,
+///     next: Next<impl MessageBody>,
+/// ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+///     if req.headers().contains_key("x-legacy-tenant") {
+///         req.extensions_mut().insert(JsonConfig::default().debug_errors(true));
+///     }
+///     next.call(req).await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct JsonConfig {
+    pub(crate) limit: usize,
+    pub(crate) ehandler: Option<ErrHandler>,
+    pub(crate) content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
+    pub(crate) content_type_required: bool,
+    pub(crate) json_suffix: bool,
+    pub(crate) debug_errors: bool,
+    pub(crate) debug_query: Option<String>,
+    pub(crate) debug_guard: Option<RequestGuard>,
+    pub(crate) defer_rendering: bool,
+    pub(crate) severities: Vec<SeverityRule>,
+    pub(crate) statuses: HashMap<FailureClass, StatusCode>,
+    pub(crate) code_statuses: Vec<CodeStatus>,
+    pub(crate) labels: Vec<(String, String)>,
+    pub(crate) doc_base: Option<String>,
+    pub(crate) doc_urls: HashMap<String, String>,
+    pub(crate) memory_budget: Option<MemoryBudget>,
+    pub(crate) blocking_threshold: Option<usize>,
+    pub(crate) streaming_threshold: Option<usize>,
+    pub(crate) item_limit: Option<usize>,
+    pub(crate) message_limit: Option<usize>,
+    pub(crate) join_messages: bool,
+    pub(crate) first_error_only: bool,
+    pub(crate) message_length: Option<usize>,
+    pub(crate) message_escape: Option<MessageEscape>,
+    pub(crate) require_utf8: bool,
+    pub(crate) sniff_content_type: bool,
+    pub(crate) strict_json: bool,
+    pub(crate) transform: Option<BodyTransform>,
+    pub(crate) shapes: HashMap<&'static str, TypeMetadata>,
+    pub(crate) error_body: Option<ErrorBody>,
+    pub(crate) error_bodies: HashMap<&'static str, ErrorBody>,
+    pub(crate) error_cap: Option<usize>,
+    pub(crate) compress_errors: Option<usize>,
+    pub(crate) error_formats: Vec<(String, Renderer)>,
+    pub(crate) error_format_header: String,
+    pub(crate) reject_unsupported_formats: bool,
+    pub(crate) soft_limit: Option<usize>,
+    pub(crate) stats: Option<ValidationStats>,
+    pub(crate) audit: Option<AuditSink>,
+    pub(crate) redactions: Vec<String>,
+    #[cfg(feature = "unknown-keys")]
+    pub(crate) unknown_keys: Option<crate::UnknownKeys>,
+    #[cfg(feature = "i18n")]
+    pub(crate) messages: Option<crate::MessageCatalog>,
+    #[cfg(feature = "i18n")]
+    pub(crate) locale: Option<LocaleResolver>,
+    /// The settings with a default value that were set, for [`JsonConfig::merge`].
+    pub(crate) explicit: HashSet<&'static str>,
+}
+
+impl JsonConfig {
+    /// Register the config as app data of an app or scope, the way the extractors look
+    /// it up:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    /// use actix_web::{web, App};
+    ///
+    /// let config = JsonConfig::default().limit(4096);
+    /// let app = App::new().service(web::scope("/api").configure(|cfg| config.register(cfg)));
+    /// ```
+    ///
+    /// A config wrapped in `web::Data` by mistake is still used, with a warning logged
+    /// once in debug builds.
+    pub fn register(self, cfg: &mut ServiceConfig) {
+        cfg.app_data(self);
+    }
+
+    /// Change max size of payload. By default max size is 32Kb
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self.explicit.insert("limit");
+        self
+    }
+
+    /// Accept bodies over `bytes`, up to [`limit`](JsonConfig::limit), but record their
+    /// size in the [`ValidationReport`](crate::ValidationReport) so limits can be
+    /// tightened gradually, knowing which clients would break:
+    ///
+    /// ```
+    /// use actix_json_validator::{soft_limit_warning, JsonConfig};
+    /// use actix_web::{middleware::from_fn, App};
+    ///
+    /// let config = JsonConfig::default().soft_limit(16 * 1024).limit(64 * 1024);
+    /// let app = App::new().app_data(config).wrap(from_fn(soft_limit_warning));
+    /// ```
+    ///
+    /// The [`soft_limit_warning`](crate::soft_limit_warning) middleware adds a `Warning`
+    /// header to the responses to such requests. Bodies over the hard limit are then
+    /// rejected with `413 Payload Too Large`, unless a status is set for
+    /// [`FailureClass::Security`](crate::FailureClass::Security).
+    pub fn soft_limit(mut self, bytes: usize) -> Self {
+        self.soft_limit = Some(bytes);
+        self
+    }
+
+    /// Count extractions into `stats`, for apps that do not register [`ValidationStats`]
+    /// as app data themselves; counters registered as app data take precedence. Set
+    /// once, outside the `HttpServer` factory, so workers share the counters:
+    ///
+    /// ```
+    /// use actix_json_validator::{validation_stats_service, JsonConfig, ValidationStats};
+    /// use actix_web::App;
+    ///
+    /// let config = JsonConfig::default().stats(ValidationStats::new());
+    /// let app = App::new()
+    ///     .app_data(config)
+    ///     .service(validation_stats_service("/admin/validation"));
+    /// ```
+    pub fn stats(mut self, stats: ValidationStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Set custom error handler.
+    ///
+    /// The handler receives the [`AppError`] produced by the extractor, the request and
+    /// the type name of the payload (e.g. `my_app::CreateUser`), so responses can be
+    /// customized per payload type without downcasting.
+    pub fn error_handler<F>(mut self, f: F) -> Self
+    where
+        F: Fn(AppError, &HttpRequest, &'static str) -> actix_web::Error + Send + Sync + 'static,
+    {
+        self.ehandler = Some(Arc::new(f));
+        self
+    }
+
+    /// Set custom response handler.
+    ///
+    /// Like [`JsonConfig::error_handler`], but the handler builds the final
+    /// [`HttpResponse`] directly, giving full control over status, headers and body.
+    /// Replaces any previously registered error handler.
+    pub fn response_handler<F>(self, f: F) -> Self
+    where
+        F: Fn(&AppError, &HttpRequest, &'static str) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.error_handler(move |err, req, type_name| {
+            let response = f(&err, req, type_name);
+            InternalError::from_response(err, response).into()
+        })
+    }
+
+    /// Let clients pick the error format `name` with the `X-Error-Format` header, e.g. to
+    /// trial a new format before it becomes the default:
+    ///
+    /// ```
+    /// use actix_json_validator::{problem_details, JsonConfig};
+    ///
+    /// // `X-Error-Format: problem+json` switches to RFC 7807 problem details.
+    /// let config = JsonConfig::default().error_format("problem+json", problem_details);
+    /// ```
+    ///
+    /// The renderer receives the error, the status it would otherwise have had, the
+    /// request and the type name of the payload. Only registered names are honored, case
+    /// insensitively; other requests keep the default rendering, unless
+    /// [`reject_unsupported_formats`](JsonConfig::reject_unsupported_formats) is set. A
+    /// requested format takes precedence over the
+    /// [`error_handler`](JsonConfig::error_handler).
+    pub fn error_format<F>(mut self, name: impl Into<String>, renderer: F) -> Self
+    where
+        F: Fn(&AppError, StatusCode, &HttpRequest, &'static str) -> HttpResponse
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.error_formats.push((name.into(), Arc::new(renderer)));
+        self
+    }
+
+    /// Read the format of [`error_format`](JsonConfig::error_format) from `header`
+    /// instead of `X-Error-Format`.
+    pub fn error_format_header(mut self, header: impl Into<String>) -> Self {
+        self.error_format_header = header.into();
+        self.explicit.insert("error_format_header");
+        self
+    }
+
+    /// Answer `406 Not Acceptable` when a request only asks for error formats that are not
+    /// registered, instead of falling back to the default rendering. The body lists the
+    /// supported formats:
+    ///
+    /// ```json
+    /// {"error": ["The requested error format is not supported"], "supported": ["json", "problem+json"]}
+    /// ```
+    ///
+    /// With [`error_format_header`](JsonConfig::error_format_header) set to `Accept`,
+    /// `application/json` and wildcards select the default rendering.
+    pub fn reject_unsupported_formats(mut self, enabled: bool) -> Self {
+        self.reject_unsupported_formats = enabled;
+        self.explicit.insert("reject_unsupported_formats");
+        self
+    }
+
+    /// Set predicate for allowed content types
+    pub fn content_type<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
+    {
+        self.content_type = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Sets whether or not the request must have a `Content-Type` header to be parsed.
+    /// When enabled, only JSON media types (and those accepted by
+    /// [`JsonConfig::content_type`]) are parsed. Disabled by default.
+    pub fn content_type_required(mut self, content_type_required: bool) -> Self {
+        self.content_type_required = content_type_required;
+        self.explicit.insert("content_type_required");
+        self
+    }
+
+    /// Sets whether structured syntax suffix types such as `application/vnd.myapp+json`,
+    /// `application/problem+json` or `application/hal+json` are parsed as JSON. Enabled
+    /// by default; when disabled, only `*/json` and the types accepted by
+    /// [`JsonConfig::content_type`] are.
+    pub fn json_suffix(mut self, enabled: bool) -> Self {
+        self.json_suffix = enabled;
+        self.explicit.insert("json_suffix");
+        self
+    }
+
+    /// For clients that omit the `Content-Type` header, look at the start of the body to
+    /// decide whether it is JSON, even with
+    /// [`content_type_required`](JsonConfig::content_type_required) set. Bodies without a
+    /// content type that do not start like a JSON document are rejected with
+    /// `415 Unsupported Media Type` before they are parsed:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // `{"name": "Pizza"}` without a content type is parsed, `name=Pizza` is a 415.
+    /// let config = JsonConfig::default()
+    ///     .content_type_required(true)
+    ///     .sniff_content_type(true);
+    /// ```
+    ///
+    /// Bodies without a content type are buffered to be sniffed. Disabled by default.
+    pub fn sniff_content_type(mut self, enabled: bool) -> Self {
+        self.sniff_content_type = enabled;
+        self.explicit.insert("sniff_content_type");
+        self
+    }
+
+    /// Reject bodies whose content type has a `charset` parameter other than UTF-8, the
+    /// only encoding of JSON (RFC 8259), with `415 Unsupported Media Type`, instead of
+    /// failing later with a confusing parse error. Disabled by default; content types
+    /// without a `charset` are always accepted.
+    pub fn require_utf8(mut self, enabled: bool) -> Self {
+        self.require_utf8 = enabled;
+        self.explicit.insert("require_utf8");
+        self
+    }
+
+    /// Hold every body to the same strict reading of RFC 8259, whatever the payload
+    /// type: strings anywhere in the body, including fields the type ignores and
+    /// `RawValue`s, must not contain lone surrogate escapes, and no object may repeat a
+    /// key, where maps would otherwise keep the last value. Bodies are buffered and
+    /// checked before they are deserialized. Disabled by default.
+    pub fn strict_json(mut self, enabled: bool) -> Self {
+        self.strict_json = enabled;
+        self.explicit.insert("strict_json");
+        self
+    }
+
+    /// Transform each body before it is parsed, e.g. to decrypt an envelope, so that
+    /// endpoints with encrypted payloads can still use [`AppJson`](crate::AppJson):
+    ///
+    /// ```
+    /// use actix_json_validator::{AppError, JsonConfig};
+    /// use actix_web::{error::ErrorBadRequest, web::Bytes, HttpRequest};
+    /// # fn decrypt(_: &str, body: &[u8]) -> Option<Vec<u8>> { Some(body.to_vec()) }
+    ///
+    /// let config = JsonConfig::default().transform_body(|body: Bytes, req: &HttpRequest| {
+    ///     let tenant = req.headers().get("x-tenant").and_then(|v| v.to_str().ok());
+    ///     decrypt(tenant.unwrap_or_default(), &body)
+    ///         .map(Bytes::from)
+    ///         .ok_or_else(|| AppError::Handled(ErrorBadRequest("Cannot decrypt the body")))
+    /// });
+    /// ```
+    ///
+    /// Bodies are buffered, and the content type, size limit and memory budget apply to
+    /// them as received; the audit sink and [`strict_json`](JsonConfig::strict_json) see
+    /// the transformed body. Errors of the hook are passed through the error handler.
+    pub fn transform_body<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(Bytes, &HttpRequest) -> Result<Bytes, AppError> + Send + Sync + 'static,
+    {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// Enrich error bodies with a `"_debug"` entry holding the payload's Rust type name
+    /// and, for every failure, the constraint that fired and the path of the offending
+    /// value (or line/column for deserialization errors). Disabled by default.
+    ///
+    /// This exposes internals, so only enable it during development, e.g.
+    /// `.debug_errors(cfg!(debug_assertions))`.
+    pub fn debug_errors(mut self, enabled: bool) -> Self {
+        self.debug_errors = enabled;
+        self.explicit.insert("debug_errors");
+        self
+    }
+
+    /// Turn on [`debug_errors`](JsonConfig::debug_errors) for requests with the query
+    /// parameter `name` set to `1` or `true`, e.g. to reproduce a client's issue in
+    /// staging. Disabled by default; restrict who may use it with
+    /// [`debug_query_guard`](JsonConfig::debug_query_guard):
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // POST /orders?__debug_errors=1, from the internal network only.
+    /// let config = JsonConfig::default()
+    ///     .debug_query("__debug_errors")
+    ///     .debug_query_guard(|req| {
+    ///         req.peer_addr()
+    ///             .is_some_and(|addr| addr.ip().is_loopback())
+    ///     });
+    /// ```
+    pub fn debug_query(mut self, name: impl Into<String>) -> Self {
+        self.debug_query = Some(name.into());
+        self
+    }
+
+    /// Only honor [`debug_query`](JsonConfig::debug_query) for requests accepted by
+    /// `guard`.
+    pub fn debug_query_guard<F>(mut self, guard: F) -> Self
+    where
+        F: Fn(&HttpRequest) -> bool + Send + Sync + 'static,
+    {
+        self.debug_guard = Some(Arc::new(guard));
+        self
+    }
+
+    /// Leave rendering of validation errors to app-level middleware: instead of the error
+    /// body, the extractors respond with the empty [`ValidationDeferred`] sentinel, and the
+    /// structured errors are available from the [`ValidationReport`] in the request
+    /// extensions. Takes precedence over custom error handlers. Disabled by default.
+    ///
+    /// [`ValidationDeferred`]: crate::ValidationDeferred
+    /// [`ValidationReport`]: crate::ValidationReport
+    pub fn defer_rendering(mut self, enabled: bool) -> Self {
+        self.defer_rendering = enabled;
+        self.explicit.insert("defer_rendering");
+        self
+    }
+
+    /// Set the [`Severity`] of every constraint failing at `path`.
+    ///
+    /// Paths are dotted like in the debug output (`profile.nickname`, `tags.0`), with `*`
+    /// matching any single segment (`tags.*`). When several rules match, the one
+    /// registered last wins.
+    ///
+    /// ```
+    /// use actix_json_validator::{JsonConfig, Severity};
+    ///
+    /// let config = JsonConfig::default()
+    ///     .severity("bio", Severity::Info)
+    ///     .constraint_severity("nickname", "max_length", Severity::Warning);
+    /// ```
+    pub fn severity(mut self, path: impl Into<String>, severity: Severity) -> Self {
+        self.severities.push(SeverityRule {
+            path: path.into(),
+            constraint: None,
+            severity,
+        });
+        self
+    }
+
+    /// Like [`JsonConfig::severity`], but only for one constraint at `path`, named as in
+    /// the `serde_valid` attribute (e.g. `"max_length"`, `"pattern"`, `"custom"`).
+    pub fn constraint_severity(
+        mut self,
+        path: impl Into<String>,
+        constraint: impl Into<String>,
+        severity: Severity,
+    ) -> Self {
+        self.severities.push(SeverityRule {
+            path: path.into(),
+            constraint: Some(constraint.into()),
+            severity,
+        });
+        self
+    }
+
+    /// Respond with `status` instead of `400 Bad Request` when a payload is rejected for
+    /// the given [`FailureClass`]:
+    ///
+    /// ```
+    /// use actix_json_validator::{FailureClass, JsonConfig};
+    /// use actix_web::http::StatusCode;
+    ///
+    /// let config = JsonConfig::default()
+    ///     .status(FailureClass::Semantic, StatusCode::UNPROCESSABLE_ENTITY)
+    ///     .status(FailureClass::Security, StatusCode::FORBIDDEN);
+    /// ```
+    ///
+    /// Custom error handlers choose their own status; they can find the class of the
+    /// failure in the [`ValidationReport`](crate::ValidationReport).
+    pub fn status(mut self, class: FailureClass, status: StatusCode) -> Self {
+        self.statuses.insert(class, status);
+        self
+    }
+
+    /// Respond with `status` when a failure carries the business error `code` of a
+    /// [`coded_error`](crate::coded_error), keeping the usual error body:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    /// use actix_web::http::StatusCode;
+    ///
+    /// let config = JsonConfig::default()
+    ///     .code_status("already_exists", StatusCode::CONFLICT)
+    ///     .code_status("forbidden_value", StatusCode::FORBIDDEN);
+    /// ```
+    ///
+    /// Codes take precedence over the statuses of [`status`](JsonConfig::status); when
+    /// several failures have mapped codes, the first one wins.
+    pub fn code_status(mut self, code: impl Into<String>, status: StatusCode) -> Self {
+        self.code_statuses.push(CodeStatus {
+            type_name: None,
+            code: code.into(),
+            status,
+        });
+        self
+    }
+
+    /// Like [`code_status`](JsonConfig::code_status), only for payloads of type `T`. Takes
+    /// precedence over the mappings for all types.
+    pub fn code_status_for<T: ?Sized>(
+        mut self,
+        code: impl Into<String>,
+        status: StatusCode,
+    ) -> Self {
+        self.code_statuses.push(CodeStatus {
+            type_name: Some(std::any::type_name::<T>().to_string()),
+            code: code.into(),
+            status,
+        });
+        self
+    }
+
+    /// Name the value at `path` with a human-friendly label in error messages, instead of
+    /// the generic subject of the default messages:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // "The length of Given name must be `>= 3`." rather than
+    /// // "The length of the value must be `>= 3`."
+    /// let config = JsonConfig::default()
+    ///     .label("given_name", "Given name")
+    ///     .label("addresses.*.zip", "ZIP code");
+    /// ```
+    ///
+    /// Paths are dotted, with `*` matching any single segment. Translated messages can use
+    /// the label as `$label`.
+    pub fn label(mut self, path: impl Into<String>, label: impl Into<String>) -> Self {
+        self.labels.push((path.into(), label.into()));
+        self
+    }
+
+    /// Share a [`MemoryBudget`] for buffered bodies between the extractors using this
+    /// configuration.
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Keep error bodies bounded for large arrays: when more than `limit` items of an
+    /// array fail, each distinct failure is reported once under `*`, prefixed with the
+    /// number of items, instead of per index:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // {"tags": {"*": ["1,204 items: The length of the value must be `>= 3`."]}}
+    /// let config = JsonConfig::default().aggregate_items(100);
+    /// ```
+    pub fn aggregate_items(mut self, limit: usize) -> Self {
+        self.item_limit = Some(limit);
+        self
+    }
+
+    /// Report at most `limit` messages per field, for `custom` validators that emit
+    /// dozens of them for one value. The rest are collapsed into a last message:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // {"password": ["Too short.", "No digit.", "and 7 more issues"]}
+    /// let config = JsonConfig::default().messages_per_field(2);
+    /// ```
+    ///
+    /// All messages are reported by default. The cap applies before
+    /// [`truncate_errors`](JsonConfig::truncate_errors).
+    pub fn messages_per_field(mut self, limit: usize) -> Self {
+        self.message_limit = Some(limit);
+        self
+    }
+
+    /// Cut messages longer than `chars` characters, ending them with `…`, since `custom`
+    /// validators may interpolate user input into them:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// let config = JsonConfig::default().max_message_length(200);
+    /// ```
+    ///
+    /// Control characters are always stripped from messages, with line breaks and tabs
+    /// turned into spaces.
+    pub fn max_message_length(mut self, chars: usize) -> Self {
+        self.message_length = Some(chars);
+        self
+    }
+
+    /// Escape or strip HTML in messages, which may echo user-submitted values, for
+    /// frontends that render error bodies verbatim:
+    ///
+    /// ```
+    /// use actix_json_validator::{JsonConfig, MessageEscape};
+    ///
+    /// // "Unknown city `&lt;script&gt;`."
+    /// let config = JsonConfig::default().escape_messages(MessageEscape::Html);
+    /// ```
+    ///
+    /// Messages are escaped after being cut to the
+    /// [`max_message_length`](JsonConfig::max_message_length), so entities stay whole.
+    pub fn escape_messages(mut self, escape: MessageEscape) -> Self {
+        self.message_escape = Some(escape);
+        self
+    }
+
+    /// Report only the first failing constraint of each field, in declaration order, like
+    /// most form frameworks:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // {"name": ["The length of the value must be `>= 3`."]}, without the pattern.
+    /// let config = JsonConfig::default().first_error_only(true);
+    /// ```
+    ///
+    /// Takes precedence over [`messages_per_field`](JsonConfig::messages_per_field).
+    pub fn first_error_only(mut self, enabled: bool) -> Self {
+        self.first_error_only = enabled;
+        self.explicit.insert("first_error_only");
+        self
+    }
+
+    /// Join the messages of each field into a single sentence, for clients that show
+    /// exactly one message per input:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // {"name": ["Must be at least 3 characters and must only contain letters."]}
+    /// let config = JsonConfig::default().join_messages(true);
+    /// ```
+    ///
+    /// Bodies keep their shape, with a list of one message. Messages with
+    /// [documentation links](JsonConfig::error_docs) are not joined.
+    pub fn join_messages(mut self, enabled: bool) -> Self {
+        self.join_messages = enabled;
+        self.explicit.insert("join_messages");
+        self
+    }
+
+    /// Include every field of `T` in its validation error bodies, with an empty list of
+    /// messages for the valid ones, so statically typed clients can deserialize errors
+    /// into a fixed structure:
+    ///
+    /// ```
+    /// use actix_json_validator::{Describe, JsonConfig};
+    /// # #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+    /// # struct Signup { #[validate(min_length = 3)] name: String, email: String }
+    ///
+    /// // {"name": ["The length of the value must be `>= 3`."], "email": []}
+    /// let config = JsonConfig::default().constant_shape::<Signup>();
+    /// ```
+    ///
+    /// Nested payloads are filled in the same way, except in collections, whose items
+    /// only appear when they fail.
+    pub fn constant_shape<T: Describe>(mut self) -> Self {
+        self.shapes
+            .insert(std::any::type_name::<T>(), T::metadata());
+        self
+    }
+
+    /// Render the whole body of validation errors with `render`, for legacy endpoints
+    /// whose error contract no built-in format can express. Register it on the config of
+    /// the route, or use [`error_body_for`](JsonConfig::error_body_for) to target a
+    /// payload type:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    /// use actix_web::{web, App};
+    /// use serde_json::json;
+    ///
+    /// // {"ok": false, "reason": "invalid_payload", "fields": ["name", "email"]}
+    /// let legacy = JsonConfig::default().error_body(|errors, _req| {
+    ///     let fields = match errors {
+    ///         serde_valid::validation::Errors::Object(errors) => {
+    ///             errors.properties.keys().cloned().collect()
+    ///         }
+    ///         _ => Vec::new(),
+    ///     };
+    ///     json!({"ok": false, "reason": "invalid_payload", "fields": fields})
+    /// });
+    /// let app = App::new().service(web::resource("/v1/signup").app_data(legacy));
+    /// ```
+    ///
+    /// `render` gets the raw `serde_valid` errors, so labels, message limits and the other
+    /// formatting settings do not apply, and its result replaces the negotiated
+    /// [error formats](JsonConfig::error_format) and the
+    /// [`error_handler`](JsonConfig::error_handler). The status is chosen as usual.
+    /// Payloads that cannot be read or parsed keep the regular error body.
+    pub fn error_body<F>(mut self, render: F) -> Self
+    where
+        F: Fn(ValidationErrors, &HttpRequest) -> Value + Send + Sync + 'static,
+    {
+        self.error_body = Some(Arc::new(render));
+        self
+    }
+
+    /// Like [`error_body`](JsonConfig::error_body), only for payloads of type `T`. Takes
+    /// precedence over the renderer for all types.
+    pub fn error_body_for<T: ?Sized, F>(mut self, render: F) -> Self
+    where
+        F: Fn(ValidationErrors, &HttpRequest) -> Value + Send + Sync + 'static,
+    {
+        self.error_bodies
+            .insert(std::any::type_name::<T>(), Arc::new(render));
+        self
+    }
+
+    /// Keep error bodies small when a payload has more than `cap` failures: only the
+    /// first `cap` messages are kept, along with a summary of all of them:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // {..., "_truncated": true, "_total_errors": 4312,
+    /// //  "_by_code": {"min_length": 4000, "pattern": 312}}
+    /// let config = JsonConfig::default().truncate_errors(100);
+    /// ```
+    ///
+    /// Failures are counted under the code of a [`coded_error`](crate::coded_error), or
+    /// the name of the constraint when they have none.
+    pub fn truncate_errors(mut self, cap: usize) -> Self {
+        self.error_cap = Some(cap);
+        self
+    }
+
+    /// Only compress error responses of at least `min_size` bytes, such as the errors of
+    /// a bulk import, when the app is wrapped in actix's
+    /// [`Compress`](actix_web::middleware::Compress) middleware:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    /// use actix_web::{middleware::Compress, App};
+    ///
+    /// let app = App::new()
+    ///     .app_data(JsonConfig::default().compress_errors(8 * 1024))
+    ///     .wrap(Compress::default());
+    /// ```
+    ///
+    /// Smaller error responses get `Content-Encoding: identity`, which `Compress` leaves
+    /// alone; larger ones are compressed if the client accepts it.
+    pub fn compress_errors(mut self, min_size: usize) -> Self {
+        self.compress_errors = Some(min_size);
+        self
+    }
+
+    /// Deserialize and validate [`OffloadedAppJson`](crate::OffloadedAppJson) bodies of at
+    /// least `bytes` bytes on the blocking thread pool, so multi-megabyte imports do not
+    /// stall other requests served by the same worker. Smaller bodies are still handled
+    /// inline, as are all bodies of [`AppJson`](crate::AppJson), whose payload types need
+    /// not be `Send`.
+    pub fn blocking_threshold(mut self, bytes: usize) -> Self {
+        self.blocking_threshold = Some(bytes);
+        self
+    }
+
+    /// Parse [`OffloadedAppJson`](crate::OffloadedAppJson) bodies with a `Content-Length`
+    /// of at least `bytes`, or none, as they arrive instead of buffering them first, so large uploads
+    /// do not hold the whole body and the payload in memory at once:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// let config = JsonConfig::default()
+    ///     .limit(64 * 1024 * 1024)
+    ///     .streaming_threshold(1024 * 1024);
+    /// ```
+    ///
+    /// Streamed bodies are parsed on the blocking thread pool, smaller ones as usual.
+    /// Settings that need the whole body, such as [`audit`](JsonConfig::audit),
+    /// [`strict_json`](JsonConfig::strict_json) or
+    /// [`transform_body`](JsonConfig::transform_body), keep every body buffered.
+    pub fn streaming_threshold(mut self, bytes: usize) -> Self {
+        self.streaming_threshold = Some(bytes);
+        self
+    }
+
+    /// Hand a copy of every payload accepted by [`AppJson`](crate::AppJson) to `sink`,
+    /// e.g. to feed an audit log or replay traffic in a test environment. Values at the
+    /// paths given to [`redact`](JsonConfig::redact) are masked first:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// let config = JsonConfig::default()
+    ///     .redact("password")
+    ///     .redact("cards.*.number")
+    ///     .audit(|record| eprintln!("{} {} {}", record.method, record.path, record.payload));
+    /// ```
+    ///
+    /// The sink runs on the worker before the handler, so slow sinks should hand records
+    /// off to a channel. Rejected payloads are never audited.
+    pub fn audit<F>(mut self, sink: F) -> Self
+    where
+        F: Fn(AuditRecord) + Send + Sync + 'static,
+    {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Replace the value at `path` with `"[REDACTED]"` in audited payloads. Paths are
+    /// dotted, with `*` matching any single field or index.
+    pub fn redact(mut self, path: impl Into<String>) -> Self {
+        self.redactions.push(path.into());
+        self
+    }
+
+    /// Accept keys of [`AppJson`](crate::AppJson) bodies that the payload type does not
+    /// know, as usual, but collect their paths for the handler, e.g. while clients roll
+    /// out fields the server does not support yet. Requires the `unknown-keys` feature:
+    ///
+    /// ```
+    /// use actix_json_validator::{JsonConfig, UnknownKeys};
+    ///
+    /// // Handlers get the keys with an `IgnoredKeys` argument, and with `Warn` the
+    /// // `ValidationReport` lists them under `warnings`.
+    /// let config = JsonConfig::default().unknown_keys(UnknownKeys::Warn);
+    /// ```
+    ///
+    /// Bodies are buffered to collect their keys. Payload types with
+    /// `#[serde(deny_unknown_fields)]` still reject them.
+    #[cfg(feature = "unknown-keys")]
+    pub fn unknown_keys(mut self, mode: crate::UnknownKeys) -> Self {
+        self.unknown_keys = Some(mode);
+        self
+    }
+
+    /// Link each validation message to its documentation, as in the `type` member of
+    /// RFC 7807 problem details. Messages become objects with the URL under `type`:
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    ///
+    /// // {"name": [{
+    /// //     "message": "The length of the value must be `>= 3`.",
+    /// //     "type": "https://docs.example.com/errors/min_length"
+    /// // }]}
+    /// let config = JsonConfig::default()
+    ///     .error_docs("https://docs.example.com/errors/")
+    ///     .error_doc("custom", "https://docs.example.com/errors/business-rules");
+    /// ```
+    ///
+    /// The URL is `base_url` followed by the constraint name (`min_length`, `pattern`,
+    /// `custom`, ...), unless [`error_doc`](Self::error_doc) set one for it.
+    pub fn error_docs(mut self, base_url: impl Into<String>) -> Self {
+        self.doc_base = Some(base_url.into());
+        self
+    }
+
+    /// Set the documentation URL of one constraint, see [`error_docs`](Self::error_docs).
+    /// Constraints with no URL, when there is no base URL either, get `"about:blank"`.
+    pub fn error_doc(mut self, constraint: impl Into<String>, url: impl Into<String>) -> Self {
+        self.doc_urls.insert(constraint.into(), url.into());
+        self
+    }
+
+    /// Translate validation messages into the locale requested by the client
+    /// (`Accept-Language`), falling back to the default English messages. Requires the
+    /// `i18n` feature.
+    #[cfg(feature = "i18n")]
+    pub fn messages(self, messages: crate::Messages) -> Self {
+        self.message_catalog(crate::MessageCatalog::new(messages))
+    }
+
+    /// Like [`JsonConfig::messages`], with translations that can be reloaded while the
+    /// app runs through the [`MessageCatalog`](crate::MessageCatalog) handle. Requires
+    /// the `i18n` feature.
+    #[cfg(feature = "i18n")]
+    pub fn message_catalog(mut self, catalog: crate::MessageCatalog) -> Self {
+        self.messages = Some(catalog);
+        self
+    }
+
+    /// Resolve the locale of the messages from the request, e.g. from the language saved
+    /// in the authenticated user's profile. A locale returned here takes precedence over
+    /// the `Accept-Language` header. Requires the `i18n` feature.
+    ///
+    /// ```
+    /// use actix_json_validator::JsonConfig;
+    /// use actix_web::HttpMessage;
+    ///
+    /// # struct User { language: String }
+    /// let config = JsonConfig::default().locale(|req| {
+    ///     req.extensions().get::<User>().map(|user| user.language.clone())
+    /// });
+    /// ```
+    #[cfg(feature = "i18n")]
+    pub fn locale<F>(mut self, resolve: F) -> Self
+    where
+        F: Fn(&HttpRequest) -> Option<String> + Send + Sync + 'static,
+    {
+        self.locale = Some(Arc::new(resolve));
+        self
+    }
+}
+
+impl JsonConfig {
+    /// Whether `req` asks for debug errors through [`JsonConfig::debug_query`], and may.
+    pub(crate) fn debug_requested(&self, req: &HttpRequest) -> bool {
+        let Some(name) = &self.debug_query else {
+            return false;
+        };
+        let requested = form_urlencoded::parse(req.query_string().as_bytes())
+            .any(|(key, value)| key == name.as_str() && matches!(&*value, "1" | "true"));
+        requested && self.debug_guard.as_ref().is_none_or(|guard| guard(req))
+    }
+
+    /// Whether messages are rendered with documentation links.
+    pub(crate) fn documents_errors(&self) -> bool {
+        self.doc_base.is_some() || !self.doc_urls.is_empty()
+    }
+
+    /// The documentation URL of `constraint`, see [`JsonConfig::error_docs`].
+    pub(crate) fn doc_url(&self, constraint: &str) -> Option<String> {
+        self.doc_urls
+            .get(constraint)
+            .cloned()
+            .or_else(|| Some(format!("{}{constraint}", self.doc_base.as_ref()?)))
+    }
+
+    /// Layer `other` over this configuration, e.g. a service's settings over the
+    /// organization's, then a route's over the service's:
+    ///
+    /// ```
+    /// use actix_json_validator::{FailureClass, JsonConfig, Profile};
+    /// use actix_web::http::StatusCode;
+    ///
+    /// let org = JsonConfig::from_profile(Profile::Production)
+    ///     .status(FailureClass::Semantic, StatusCode::UNPROCESSABLE_ENTITY)
+    ///     .redact("password");
+    /// let service = org.clone().merge(JsonConfig::default().limit(256 * 1024));
+    /// let upload_route = service.clone().merge(JsonConfig::default().limit(8 * 1024 * 1024));
+    /// ```
+    ///
+    /// `other` takes precedence:
+    ///
+    /// - settings with a single value (limits, flags, handlers, the error format header,
+    ///   ...) are taken from `other` when it set them, with a builder method or a
+    ///   [`Profile`], and kept otherwise, even when `other` has a different default;
+    /// - statuses by class, documentation URLs, constant shapes and error bodies by type
+    ///   are combined, with `other`'s entries replacing those for the same class,
+    ///   constraint or type;
+    /// - severities, labels, business code statuses and error formats are combined, with
+    ///   `other`'s rules winning over matching ones;
+    /// - redactions are combined.
+    pub fn merge(mut self, other: JsonConfig) -> Self {
+        let explicit = |name| other.explicit.contains(name);
+        if explicit("limit") {
+            self.limit = other.limit;
+        }
+        if explicit("content_type_required") {
+            self.content_type_required = other.content_type_required;
+        }
+        if explicit("json_suffix") {
+            self.json_suffix = other.json_suffix;
+        }
+        if explicit("require_utf8") {
+            self.require_utf8 = other.require_utf8;
+        }
+        if explicit("sniff_content_type") {
+            self.sniff_content_type = other.sniff_content_type;
+        }
+        if explicit("strict_json") {
+            self.strict_json = other.strict_json;
+        }
+        if explicit("debug_errors") {
+            self.debug_errors = other.debug_errors;
+        }
+        if explicit("defer_rendering") {
+            self.defer_rendering = other.defer_rendering;
+        }
+        if explicit("join_messages") {
+            self.join_messages = other.join_messages;
+        }
+        if explicit("first_error_only") {
+            self.first_error_only = other.first_error_only;
+        }
+        if explicit("error_format_header") {
+            self.error_format_header = other.error_format_header;
+        }
+        if explicit("reject_unsupported_formats") {
+            self.reject_unsupported_formats = other.reject_unsupported_formats;
+        }
+        self.explicit.extend(other.explicit);
+
+        self.ehandler = other.ehandler.or(self.ehandler);
+        self.content_type = other.content_type.or(self.content_type);
+        self.debug_query = other.debug_query.or(self.debug_query);
+        self.debug_guard = other.debug_guard.or(self.debug_guard);
+        self.doc_base = other.doc_base.or(self.doc_base);
+        self.memory_budget = other.memory_budget.or(self.memory_budget);
+        self.blocking_threshold = other.blocking_threshold.or(self.blocking_threshold);
+        self.streaming_threshold = other.streaming_threshold.or(self.streaming_threshold);
+        self.item_limit = other.item_limit.or(self.item_limit);
+        self.message_limit = other.message_limit.or(self.message_limit);
+        self.message_length = other.message_length.or(self.message_length);
+        self.message_escape = other.message_escape.or(self.message_escape);
+        self.error_cap = other.error_cap.or(self.error_cap);
+        self.compress_errors = other.compress_errors.or(self.compress_errors);
+        self.soft_limit = other.soft_limit.or(self.soft_limit);
+        self.stats = other.stats.or(self.stats);
+        self.audit = other.audit.or(self.audit);
+        self.transform = other.transform.or(self.transform);
+        self.error_body = other.error_body.or(self.error_body);
+        #[cfg(feature = "unknown-keys")]
+        {
+            self.unknown_keys = other.unknown_keys.or(self.unknown_keys);
+        }
+        #[cfg(feature = "i18n")]
+        {
+            self.messages = other.messages.or(self.messages);
+            self.locale = other.locale.or(self.locale);
+        }
+
+        self.statuses.extend(other.statuses);
+        self.doc_urls.extend(other.doc_urls);
+        self.shapes.extend(other.shapes);
+        self.error_bodies.extend(other.error_bodies);
+        // The last matching severity and label rules win, the first code statuses and
+        // error formats.
+        self.severities.extend(other.severities);
+        self.labels.extend(other.labels);
+        self.code_statuses.splice(0..0, other.code_statuses);
+        self.error_formats.splice(0..0, other.error_formats);
+        for path in other.redactions {
+            if !self.redactions.contains(&path) {
+                self.redactions.push(path);
+            }
+        }
+        self
+    }
+
+    /// Build a configuration from a named [`Profile`], typically selected once at startup:
+    ///
+    /// ```
+    /// use actix_json_validator::{JsonConfig, Profile};
+    ///
+    /// let profile = std::env::var("APP_PROFILE")
+    ///     .ok()
+    ///     .and_then(|name| name.parse().ok())
+    ///     .unwrap_or(Profile::Production);
+    /// let config = JsonConfig::from_profile(profile);
+    /// ```
+    ///
+    /// The result can be further customized with the usual builder methods. Build it
+    /// outside the `HttpServer` factory, so the workers share its counters.
+    pub fn from_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Development => JsonConfig::default().limit(16_384).debug_errors(true),
+            Profile::Staging => JsonConfig::from_profile(Profile::Production).debug_errors(true),
+            Profile::Production => JsonConfig::default()
+                .content_type_required(true)
+                .stats(ValidationStats::new()),
+        }
+    }
+}
+
+/// Named bundles of [`JsonConfig`] defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Verbose debug errors and a small (16Kb) payload limit, so oversized
+    /// fixtures are caught early.
+    Development,
+    /// Production settings with verbose debug errors kept on.
+    Staging,
+    /// Terse errors, the default 32Kb limit, a required JSON `Content-Type`, and
+    /// extractions counted into fresh [`ValidationStats`].
+    Production,
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    /// Parses `dev`/`development`, `staging` and `prod`/`production`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dev" | "development" => Ok(Profile::Development),
+            "staging" => Ok(Profile::Staging),
+            "prod" | "production" => Ok(Profile::Production),
+            _ => Err(format!("unknown configuration profile `{s}`")),
+        }
+    }
+}
+
+impl Default for JsonConfig {
+    fn default() -> Self {
+        JsonConfig {
+            limit: 32768,
+            ehandler: None,
+            content_type: None,
+            content_type_required: false,
+            json_suffix: true,
+            debug_errors: false,
+            debug_query: None,
+            debug_guard: None,
+            defer_rendering: false,
+            severities: Vec::new(),
+            statuses: HashMap::new(),
+            code_statuses: Vec::new(),
+            labels: Vec::new(),
+            doc_base: None,
+            doc_urls: HashMap::new(),
+            memory_budget: None,
+            blocking_threshold: None,
+            streaming_threshold: None,
+            item_limit: None,
+            message_limit: None,
+            join_messages: false,
+            first_error_only: false,
+            message_length: None,
+            message_escape: None,
+            require_utf8: false,
+            sniff_content_type: false,
+            strict_json: false,
+            transform: None,
+            shapes: HashMap::new(),
+            error_body: None,
+            error_bodies: HashMap::new(),
+            error_cap: None,
+            compress_errors: None,
+            error_formats: Vec::new(),
+            error_format_header: "X-Error-Format".to_string(),
+            reject_unsupported_formats: false,
+            soft_limit: None,
+            stats: None,
+            audit: None,
+            redactions: Vec::new(),
+            #[cfg(feature = "unknown-keys")]
+            unknown_keys: None,
+            #[cfg(feature = "i18n")]
+            messages: None,
+            #[cfg(feature = "i18n")]
+            locale: None,
+            explicit: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_from_str() {
+        assert_eq!("dev".parse(), Ok(Profile::Development));
+        assert_eq!("Staging".parse(), Ok(Profile::Staging));
+        assert_eq!("PRODUCTION".parse(), Ok(Profile::Production));
+        assert!("qa".parse::<Profile>().is_err());
+    }
+
+    #[test]
+    fn test_production_counts_extractions() {
+        assert!(JsonConfig::from_profile(Profile::Production)
+            .stats
+            .is_some());
+        let staging = JsonConfig::from_profile(Profile::Staging);
+        assert!(staging.stats.is_some());
+        assert!(staging.content_type_required && staging.debug_errors);
+        assert!(JsonConfig::from_profile(Profile::Development)
+            .stats
+            .is_none());
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let base = JsonConfig::from_profile(Profile::Production)
+            .limit(1024)
+            .status(FailureClass::Semantic, StatusCode::UNPROCESSABLE_ENTITY)
+            .status(FailureClass::Security, StatusCode::FORBIDDEN)
+            .code_status("taken", StatusCode::CONFLICT)
+            .label("email", "Email")
+            .redact("password");
+        let merged = base.merge(
+            JsonConfig::default()
+                .debug_errors(true)
+                .status(FailureClass::Security, StatusCode::PAYLOAD_TOO_LARGE)
+                .code_status("taken", StatusCode::BAD_REQUEST)
+                .label("email", "E-mail")
+                .redact("password")
+                .redact("token"),
+        );
+
+        // Defaults of the overlay do not reset what the base set.
+        assert_eq!(merged.limit, 1024);
+        assert!(merged.content_type_required);
+        assert!(merged.debug_errors);
+        assert_eq!(
+            merged.statuses.get(&FailureClass::Semantic),
+            Some(&StatusCode::UNPROCESSABLE_ENTITY)
+        );
+        assert_eq!(
+            merged.statuses.get(&FailureClass::Security),
+            Some(&StatusCode::PAYLOAD_TOO_LARGE)
+        );
+        assert_eq!(merged.code_statuses[0].status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            crate::label::lookup(&merged.labels, "email"),
+            Some("E-mail")
+        );
+        assert_eq!(merged.redactions, ["password", "token"]);
+
+        let relaxed = merged.merge(JsonConfig::default().content_type_required(false));
+        assert!(!relaxed.content_type_required);
+    }
+
+    #[test]
+    fn test_profile_defaults() {
+        let dev = JsonConfig::from_profile(Profile::Development);
+        assert!(dev.debug_errors);
+        assert!(!dev.content_type_required);
+        assert_eq!(dev.limit, 16_384);
+
+        let prod = JsonConfig::from_profile(Profile::Production);
+        assert!(!prod.debug_errors);
+        assert!(prod.content_type_required);
+        assert_eq!(prod.limit, 32768);
+    }
+
+    #[actix_web::test]
+    async fn test_registered_and_misregistered_configs() {
+        use crate::AppJson;
+        use actix_web::{test, web, App};
+
+        #[derive(serde::Deserialize, serde_valid::Validate)]
+        struct Note {
+            #[allow(dead_code)]
+            text: String,
+        }
+
+        let handler = || web::post().to(|_: AppJson<Note>| async { "ok" });
+        let app = test::init_service(
+            App::new()
+                .service(
+                    web::scope("/registered")
+                        .configure(|cfg| JsonConfig::default().limit(8).register(cfg))
+                        .route("", handler()),
+                )
+                .service(
+                    web::scope("/data")
+                        .app_data(web::Data::new(JsonConfig::default().limit(8)))
+                        .route("", handler()),
+                ),
+        )
+        .await;
+        for uri in ["/registered", "/data"] {
+            let req = test::TestRequest::post()
+                .uri(uri)
+                .set_json(serde_json::json!({"text": "longer than eight bytes"}))
+                .to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Configuration documents read by [`JsonConfig::from_file`], enabled by the
+//! `config-file` feature.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use actix_web::http::StatusCode;
+use serde::Deserialize;
+
+use crate::{problem_details, FailureClass, JsonConfig, MessageEscape, Profile, Severity};
+
+/// The settings of a configuration document, all optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    profile: Option<String>,
+    limit: Option<usize>,
+    soft_limit: Option<usize>,
+    content_type_required: Option<bool>,
+    content_types: Vec<String>,
+    json_suffix: Option<bool>,
+    require_utf8: Option<bool>,
+    strict_json: Option<bool>,
+    debug_errors: Option<bool>,
+    debug_query: Option<String>,
+    error_format_header: Option<String>,
+    error_formats: Vec<String>,
+    reject_unsupported_formats: Option<bool>,
+    aggregate_items: Option<usize>,
+    messages_per_field: Option<usize>,
+    join_messages: Option<bool>,
+    first_error_only: Option<bool>,
+    max_message_length: Option<usize>,
+    escape_messages: Option<String>,
+    truncate_errors: Option<usize>,
+    compress_errors: Option<usize>,
+    redact: Vec<String>,
+    error_docs: Option<String>,
+    statuses: BTreeMap<String, u16>,
+    code_statuses: BTreeMap<String, u16>,
+    labels: BTreeMap<String, String>,
+    severities: BTreeMap<String, String>,
+    constraint_severities: Vec<ConstraintSeverity>,
+    locale_dir: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConstraintSeverity {
+    path: String,
+    constraint: String,
+    severity: String,
+}
+
+impl JsonConfig {
+    /// Build a configuration from a TOML (`.toml`) or YAML (`.yaml`, `.yml`) document, so
+    /// services can share their validation settings declaratively. Requires the
+    /// `config-file` feature.
+    ///
+    /// ```toml
+    /// profile = "production"          # see `Profile`, applied first
+    /// limit = 65536
+    /// soft_limit = 16384
+    /// content_type_required = true
+    /// content_types = ["application/x-ndjson"]
+    /// json_suffix = true
+    /// require_utf8 = true
+    /// strict_json = true
+    /// debug_query = "debug"
+    /// error_format_header = "X-Error-Format"
+    /// error_formats = ["problem+json"]
+    /// reject_unsupported_formats = true
+    /// aggregate_items = 100
+    /// messages_per_field = 5
+    /// join_messages = true
+    /// first_error_only = false
+    /// max_message_length = 200
+    /// escape_messages = "html"        # or "strip"
+    /// truncate_errors = 200
+    /// compress_errors = 8192
+    /// redact = ["password", "card.number"]
+    /// error_docs = "https://docs.example.com/errors/"
+    /// locale_dir = "locales"          # `<locale>.ftl` files, with the `i18n` feature
+    ///
+    /// [statuses]                      # by `FailureClass`
+    /// semantic = 422
+    /// security = 413
+    ///
+    /// [code_statuses]
+    /// already_exists = 409
+    ///
+    /// [labels]
+    /// "user.email" = "Email address"
+    ///
+    /// [severities]
+    /// bio = "info"
+    ///
+    /// [[constraint_severities]]
+    /// path = "nickname"
+    /// constraint = "max_length"
+    /// severity = "warning"
+    /// ```
+    ///
+    /// Every setting is optional and maps to the builder method of the same name. Fails
+    /// on unknown settings and invalid values, naming the file. `locale_dir` is relative
+    /// to the file. The result can be further customized with the usual builder methods.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .map_err(|err| format!("cannot read `{}`: {err}", path.display()))?;
+        let file: Result<ConfigFile, String> = match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("toml") => toml::from_str(&source).map_err(|err| err.to_string()),
+            Some("yaml" | "yml") => serde_yaml::from_str(&source).map_err(|err| err.to_string()),
+            _ => Err("expected a `.toml`, `.yaml` or `.yml` file".to_string()),
+        };
+        file.and_then(|file| file.build(path.parent().unwrap_or(Path::new(""))))
+            .map_err(|err| format!("invalid configuration `{}`: {err}", path.display()))
+    }
+}
+
+impl ConfigFile {
+    #[cfg_attr(not(feature = "i18n"), allow(unused_variables))]
+    fn build(self, dir: &Path) -> Result<JsonConfig, String> {
+        let mut config = match self.profile {
+            Some(profile) => JsonConfig::from_profile(profile.parse::<Profile>()?),
+            None => JsonConfig::default(),
+        };
+        if let Some(limit) = self.limit {
+            config = config.limit(limit);
+        }
+        if let Some(bytes) = self.soft_limit {
+            config = config.soft_limit(bytes);
+        }
+        if let Some(required) = self.content_type_required {
+            config = config.content_type_required(required);
+        }
+        if !self.content_types.is_empty() {
+            let types = self.content_types;
+            config = config.content_type(move |mime| {
+                types.iter().any(|accepted| accepted == mime.essence_str())
+            });
+        }
+        if let Some(enabled) = self.json_suffix {
+            config = config.json_suffix(enabled);
+        }
+        if let Some(enabled) = self.require_utf8 {
+            config = config.require_utf8(enabled);
+        }
+        if let Some(enabled) = self.strict_json {
+            config = config.strict_json(enabled);
+        }
+        if let Some(enabled) = self.debug_errors {
+            config = config.debug_errors(enabled);
+        }
+        if let Some(name) = self.debug_query {
+            config = config.debug_query(name);
+        }
+        if let Some(header) = self.error_format_header {
+            config = config.error_format_header(header);
+        }
+        for name in self.error_formats {
+            match name.as_str() {
+                "problem+json" => config = config.error_format(name, problem_details),
+                _ => return Err(format!("unknown error format `{name}`")),
+            }
+        }
+        if let Some(enabled) = self.reject_unsupported_formats {
+            config = config.reject_unsupported_formats(enabled);
+        }
+        if let Some(limit) = self.aggregate_items {
+            config = config.aggregate_items(limit);
+        }
+        if let Some(limit) = self.messages_per_field {
+            config = config.messages_per_field(limit);
+        }
+        if let Some(enabled) = self.join_messages {
+            config = config.join_messages(enabled);
+        }
+        if let Some(enabled) = self.first_error_only {
+            config = config.first_error_only(enabled);
+        }
+        if let Some(chars) = self.max_message_length {
+            config = config.max_message_length(chars);
+        }
+        if let Some(escape) = self.escape_messages {
+            config = config.escape_messages(message_escape(&escape)?);
+        }
+        if let Some(cap) = self.truncate_errors {
+            config = config.truncate_errors(cap);
+        }
+        if let Some(min_size) = self.compress_errors {
+            config = config.compress_errors(min_size);
+        }
+        for path in self.redact {
+            config = config.redact(path);
+        }
+        if let Some(base_url) = self.error_docs {
+            config = config.error_docs(base_url);
+        }
+        for (class, status) in self.statuses {
+            config = config.status(failure_class(&class)?, status_code(status)?);
+        }
+        for (code, status) in self.code_statuses {
+            config = config.code_status(code, status_code(status)?);
+        }
+        for (path, label) in self.labels {
+            config = config.label(path, label);
+        }
+        for (path, level) in self.severities {
+            config = config.severity(path, severity(&level)?);
+        }
+        for rule in self.constraint_severities {
+            config =
+                config.constraint_severity(rule.path, rule.constraint, severity(&rule.severity)?);
+        }
+        if let Some(locale_dir) = self.locale_dir {
+            #[cfg(feature = "i18n")]
+            {
+                let catalog = crate::MessageCatalog::from_dir(dir.join(locale_dir))?;
+                config = config.message_catalog(catalog);
+            }
+            #[cfg(not(feature = "i18n"))]
+            return Err(format!(
+                "`locale_dir = \"{locale_dir}\"` requires the `i18n` feature"
+            ));
+        }
+        Ok(config)
+    }
+}
+
+fn failure_class(name: &str) -> Result<FailureClass, String> {
+    match name {
+        "payload" => Ok(FailureClass::Payload),
+        "schema" => Ok(FailureClass::Schema),
+        "semantic" => Ok(FailureClass::Semantic),
+        "security" => Ok(FailureClass::Security),
+        _ => Err(format!("unknown failure class `{name}`")),
+    }
+}
+
+fn severity(name: &str) -> Result<Severity, String> {
+    match name {
+        "error" => Ok(Severity::Error),
+        "warning" => Ok(Severity::Warning),
+        "info" => Ok(Severity::Info),
+        _ => Err(format!("unknown severity `{name}`")),
+    }
+}
+
+fn message_escape(name: &str) -> Result<MessageEscape, String> {
+    match name {
+        "html" => Ok(MessageEscape::Html),
+        "strip" => Ok(MessageEscape::Strip),
+        _ => Err(format!("unknown message escape `{name}`")),
+    }
+}
+
+fn status_code(status: u16) -> Result<StatusCode, String> {
+    StatusCode::from_u16(status).map_err(|_| format!("invalid status code {status}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_source(name: &str, source: &str) -> Result<JsonConfig, String> {
+        let dir = std::env::temp_dir().join(format!("config-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, source).unwrap();
+        let config = JsonConfig::from_file(&path);
+        fs::remove_file(path).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_toml_and_yaml_documents() {
+        let toml = from_source(
+            "validation.toml",
+            r#"
+profile = "dev"
+limit = 1024
+content_type_required = true
+error_formats = ["problem+json"]
+redact = ["password"]
+
+[statuses]
+semantic = 422
+
+[code_statuses]
+already_exists = 409
+
+[severities]
+bio = "info"
+"#,
+        )
+        .unwrap();
+        assert!(toml.debug_errors);
+        assert_eq!(toml.limit, 1024);
+        assert!(toml.content_type_required);
+        assert_eq!(toml.error_formats[0].0, "problem+json");
+        assert_eq!(toml.redactions, ["password"]);
+        assert_eq!(
+            toml.statuses.get(&FailureClass::Semantic),
+            Some(&StatusCode::UNPROCESSABLE_ENTITY)
+        );
+        assert_eq!(toml.code_statuses[0].status, StatusCode::CONFLICT);
+        assert_eq!(toml.severities[0].severity, Severity::Info);
+
+        let yaml = from_source(
+            "validation.yml",
+            "limit: 2048\nlabels:\n  user.email: Email address\n",
+        )
+        .unwrap();
+        assert_eq!(yaml.limit, 2048);
+        assert!(!yaml.debug_errors);
+        assert_eq!(yaml.labels.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        let err = from_source("typo.toml", "limt = 1").err().unwrap();
+        assert!(err.contains("typo.toml") && err.contains("limt"), "{err}");
+
+        let err = from_source("class.yaml", "statuses:\n  business: 422\n")
+            .err()
+            .unwrap();
+        assert!(err.ends_with("unknown failure class `business`"), "{err}");
+
+        assert!(from_source("validation.json", "{}").is_err());
+    }
+}
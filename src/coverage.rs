@@ -0,0 +1,216 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_valid::{validation::Errors as ValidationError, Validate};
+
+use crate::{
+    constraints_of, debug::constraint_name, format, severity::path_matches, DeclaredConstraint,
+    Describe,
+};
+
+/// Run sample invalid payloads through the validation of `T` and report which of its
+/// declared constraints none of them triggered, to keep validation tests in step with
+/// the payload types:
+///
+/// ```
+/// use actix_json_validator::{constraint_coverage, Describe};
+/// use serde_json::json;
+///
+/// #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// struct Signup {
+///     #[validate(min_length = 3)]
+///     name: String,
+///     #[validate(minimum = 18)]
+///     age: u8,
+/// }
+///
+/// let coverage = constraint_coverage::<Signup>([
+///     json!({"name": "Al", "age": 30}),
+///     json!({"name": "Alice", "age": 16}),
+/// ]);
+/// coverage.assert_complete();
+/// ```
+///
+/// Constraints on the elements of collections, and on the values of maps, are covered by a
+/// failure of any of them.
+pub fn constraint_coverage<T>(samples: impl IntoIterator<Item = Value>) -> Coverage
+where
+    T: DeserializeOwned + Validate + Describe,
+{
+    let mut triggered = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, sample) in samples.into_iter().enumerate() {
+        match serde_json::from_value::<T>(sample) {
+            Ok(data) => {
+                if let Err(errors) = data.validate() {
+                    collect(&mut triggered, "", &errors);
+                }
+            }
+            Err(err) => rejected.push((index, err.to_string())),
+        }
+    }
+
+    let (covered, uncovered) = constraints_of::<T>().iter().cloned().partition(|declared| {
+        triggered
+            .iter()
+            .any(|(path, kind)| declared.kind == *kind && covers(&declared.path, path))
+    });
+    Coverage {
+        covered,
+        uncovered,
+        rejected,
+    }
+}
+
+/// The outcome of [`constraint_coverage`].
+#[derive(Debug, Clone)]
+pub struct Coverage {
+    /// Declared constraints that at least one sample triggered.
+    pub covered: Vec<DeclaredConstraint>,
+    /// Declared constraints that no sample triggered.
+    pub uncovered: Vec<DeclaredConstraint>,
+    /// Samples that could not be deserialized, by position, with the reason. They do not
+    /// reach validation, so are usually a mistake in the test.
+    pub rejected: Vec<(usize, String)>,
+}
+
+impl Coverage {
+    /// Whether every constraint was triggered and every sample deserialized.
+    pub fn is_complete(&self) -> bool {
+        self.uncovered.is_empty() && self.rejected.is_empty()
+    }
+
+    /// Panic, listing what is missing, unless the coverage is complete.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        if !self.is_complete() {
+            panic!("incomplete constraint coverage:\n{self}");
+        }
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for declared in &self.uncovered {
+            let path = if declared.path.is_empty() {
+                "<root>"
+            } else {
+                &declared.path
+            };
+            writeln!(f, "  never triggered: `{}` at {path}", declared.kind)?;
+        }
+        for (index, reason) in &self.rejected {
+            writeln!(f, "  sample {index} could not be deserialized: {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a failure at `path` triggered a constraint declared at `declared`, which
+/// applies to each element when declared on a collection (`tags` for `tags.2`).
+fn covers(declared: &str, mut path: &str) -> bool {
+    loop {
+        if path_matches(declared, path) {
+            return true;
+        }
+        match path.rsplit_once('.') {
+            Some((parent, index)) if index.parse::<usize>().is_ok() => path = parent,
+            _ => return false,
+        }
+    }
+}
+
+/// Collect the failed constraints of an error tree by path.
+fn collect(triggered: &mut Vec<(String, &'static str)>, path: &str, errors: &ValidationError) {
+    format::walk(errors, path, &mut |path, error| {
+        triggered.push((path.to_string(), constraint_name(error)))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, Validate, Describe)]
+    struct Order {
+        #[validate(max_items = 2)]
+        #[validate(min_length = 2)]
+        tags: Vec<String>,
+        #[validate]
+        lines: Vec<Line>,
+    }
+
+    #[derive(Deserialize, Validate, Describe)]
+    struct Line {
+        #[validate(minimum = 1)]
+        quantity: u32,
+        #[validate(maximum = 100)]
+        price: u32,
+    }
+
+    #[test]
+    fn test_reports_untriggered_constraints() {
+        let coverage = constraint_coverage::<Order>([
+            json!({"tags": ["a", "ok"], "lines": [{"quantity": 1, "price": 1}]}),
+            json!({"tags": [], "lines": [{"quantity": 1, "price": 1}, {"quantity": 0, "price": 1}]}),
+            json!({"tags": []}),
+        ]);
+
+        assert!(!coverage.is_complete());
+        assert_eq!(coverage.covered.len(), 2);
+        assert_eq!(
+            coverage.to_string(),
+            "  never triggered: `max_items` at tags\n  \
+             never triggered: `maximum` at lines.*.price\n  \
+             sample 2 could not be deserialized: missing field `lines`\n"
+        );
+    }
+
+    #[derive(Deserialize, Validate, Describe)]
+    struct Menu {
+        #[validate]
+        dishes: HashMap<String, Dish>,
+        #[validate(min_length = 2)]
+        aliases: HashMap<String, String>,
+    }
+
+    #[derive(Deserialize, Validate, Describe)]
+    struct Dish {
+        #[validate(min_length = 3)]
+        name: String,
+        #[validate(maximum = 2000)]
+        calories: u32,
+    }
+
+    #[test]
+    fn test_map_values_are_covered() {
+        let coverage = constraint_coverage::<Menu>([
+            json!({"dishes": {"pizza": {"name": "Pi", "calories": 800}}, "aliases": {}}),
+            json!({"dishes": {}, "aliases": {"pie": "p"}}),
+        ]);
+        assert_eq!(
+            coverage.to_string(),
+            "  never triggered: `maximum` at dishes.*.calories\n"
+        );
+        let covered: Vec<_> = coverage
+            .covered
+            .iter()
+            .map(|declared| (declared.path.as_str(), declared.kind))
+            .collect();
+        assert_eq!(
+            covered,
+            [("dishes.*.name", "min_length"), ("aliases", "min_length")]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "never triggered: `maximum` at lines.*.price")]
+    fn test_assert_complete_panics() {
+        constraint_coverage::<Order>([json!({"tags": ["a", "b", "c"], "lines": []})])
+            .assert_complete();
+    }
+}
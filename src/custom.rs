@@ -0,0 +1,166 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde_valid::Validate;
+
+use crate::{extract, AppError, ConstraintKind};
+
+/// A payload type that decodes its own body, for [`AppCustomJson`].
+///
+/// Implement it to parse with something other than plain `serde_json`, such as
+/// `serde_ignored` to reject or record unknown keys, or a hand-written decoder for a
+/// hot endpoint. The error is reported like any other deserialization error.
+pub trait DeserializeBody: Sized {
+    /// Decode the body of `req`, already checked against the size limit and content type
+    /// of the [`JsonConfig`](crate::JsonConfig).
+    fn deserialize_body(req: &HttpRequest, body: &[u8]) -> Result<Self, String>;
+}
+
+/// Extractor for payload types that implement [`DeserializeBody`]: the body is read
+/// and checked like that of [`AppJson`](crate::AppJson), decoded by the payload type
+/// itself, then validated and reported in the usual error format:
+///
+/// ```
+/// use actix_json_validator::{AppCustomJson, DeserializeBody};
+/// use actix_web::HttpRequest;
+/// use serde::Deserialize;
+/// use serde_valid::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Signup {
+///     #[validate(min_length = 3)]
+///     username: String,
+/// }
+///
+/// impl DeserializeBody for Signup {
+///     /// Reject keys the type does not know, naming them all.
+///     fn deserialize_body(_req: &HttpRequest, body: &[u8]) -> Result<Self, String> {
+///         let fields: serde_json::Map<String, serde_json::Value> =
+///             serde_json::from_slice(body).map_err(|err| err.to_string())?;
+///         let unknown: Vec<&str> = fields
+///             .keys()
+///             .map(String::as_str)
+///             .filter(|key| *key != "username")
+///             .collect();
+///         if !unknown.is_empty() {
+///             return Err(format!("unknown keys: {}", unknown.join(", ")));
+///         }
+///         serde_json::from_value(fields.into()).map_err(|err| err.to_string())
+///     }
+/// }
+///
+/// async fn signup(signup: AppCustomJson<Signup>) -> String {
+///     format!("Welcome {}", signup.username)
+/// }
+/// ```
+///
+/// Limits, error handler and debug settings are read from [`JsonConfig`](crate::JsonConfig).
+#[derive(Debug)]
+pub struct AppCustomJson<T>(pub T);
+
+impl<T> AppCustomJson<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppCustomJson<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppCustomJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppCustomJson<T>
+where
+    T: DeserializeBody + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                let body = extract::json_body(&config, &req, payload, type_name).await?;
+
+                let data = T::deserialize_body(&req, &body).map_err(|err| {
+                    extract::fail(&req, ConstraintKind::Deserialization);
+                    let message = format!("Json deserialize error: {err}");
+                    extract::decode_error(&config, type_name, "deserialization", message)
+                })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppCustomJson)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonConfig;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde_json::{json, Value};
+
+    /// Decoded from comma-separated tags rather than JSON.
+    #[derive(Debug, Validate)]
+    struct Tags(#[validate(max_items = 2)] Vec<String>);
+
+    impl DeserializeBody for Tags {
+        fn deserialize_body(_req: &HttpRequest, body: &[u8]) -> Result<Self, String> {
+            let body = std::str::from_utf8(body).map_err(|err| err.to_string())?;
+            Ok(Tags(body.split(',').map(str::to_string).collect()))
+        }
+    }
+
+    async fn extract(config: JsonConfig, body: &'static str) -> Result<Tags, Value> {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_payload(body)
+            .to_http_parts();
+        AppCustomJson::<Tags>::from_request(&req, &mut payload)
+            .await
+            .map(AppCustomJson::into_inner)
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_payload_type_decodes_its_body() {
+        let tags = extract(JsonConfig::default(), "rust,web").await.unwrap();
+        assert_eq!(tags.0, ["rust", "web"]);
+
+        assert_eq!(
+            extract(JsonConfig::default(), "rust,web,json")
+                .await
+                .unwrap_err(),
+            json!({"non_field_errors": ["The length of the items must be `<= 2`."]})
+        );
+        assert_eq!(
+            extract(JsonConfig::default().limit(4), "rust,web")
+                .await
+                .unwrap_err(),
+            json!({"error": ["payload reached size limit"]})
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Verbose error context for development, enabled with [`JsonConfig::debug_errors`].
+//!
+//! [`JsonConfig::debug_errors`]: crate::JsonConfig::debug_errors
+
+use actix_web::error::{JsonPayloadError, PayloadError};
+use serde_json::{json, Value};
+use serde_valid::validation::{Error as ConstraintError, Errors as ValidationError};
+
+use crate::{code, format, ErrorCode};
+
+/// Key under which the debug context is added to the error body.
+pub(crate) const DEBUG_KEY: &str = "_debug";
+
+/// Build the debug context for a payload that failed validation.
+pub(crate) fn validation_context(type_name: &str, errors: &ValidationError) -> Value {
+    let mut details = Vec::new();
+    collect(&mut details, "", errors);
+    json!({ "type": type_name, "errors": details })
+}
+
+/// Build the debug context for a payload that could not be read or deserialized.
+pub(crate) fn payload_context(type_name: &str, error: &JsonPayloadError) -> Value {
+    let detail = match error {
+        JsonPayloadError::Deserialize(err) => json!({
+            "constraint": "deserialization",
+            "category": format!("{:?}", err.classify()).to_lowercase(),
+            "line": err.line(),
+            "column": err.column(),
+            "message": err.to_string(),
+        }),
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            json!({ "constraint": "limit", "message": error.to_string() })
+        }
+        JsonPayloadError::ContentType => {
+            json!({ "constraint": "content_type", "message": error.to_string() })
+        }
+        JsonPayloadError::Payload(PayloadError::Incomplete(_)) => {
+            json!({ "constraint": "truncated", "message": error.to_string() })
+        }
+        _ => json!({ "constraint": "payload", "message": error.to_string() }),
+    };
+    json!({ "type": type_name, "errors": [with_code(detail)] })
+}
+
+/// Build the debug context for a non-JSON payload that could not be read or decoded.
+pub(crate) fn decode_context(type_name: &str, constraint: &str, message: &str) -> Value {
+    json!({
+        "type": type_name,
+        "errors": [with_code(json!({ "constraint": constraint, "message": message }))],
+    })
+}
+
+/// Add the [`ErrorCode`] of the `constraint` of a detail.
+fn with_code(mut detail: Value) -> Value {
+    let constraint = detail["constraint"].as_str().unwrap_or_default();
+    detail["code"] = json!(ErrorCode::of_constraint(constraint));
+    detail
+}
+
+fn collect(details: &mut Vec<Value>, path: &str, errors: &ValidationError) {
+    format::walk(errors, path, &mut |path, error| {
+        details.push(with_code(json!({
+            "path": path,
+            "constraint": constraint_name(error),
+            "message": code::message(error),
+        })))
+    });
+}
+
+pub(crate) fn join(parent: &str, child: &str) -> String {
+    if parent.is_empty() {
+        child.to_string()
+    } else {
+        format!("{parent}.{child}")
+    }
+}
+
+pub(crate) fn constraint_name(error: &ConstraintError) -> &'static str {
+    match error {
+        ConstraintError::Minimum(_) => "minimum",
+        ConstraintError::Maximum(_) => "maximum",
+        ConstraintError::ExclusiveMinimum(_) => "exclusive_minimum",
+        ConstraintError::ExclusiveMaximum(_) => "exclusive_maximum",
+        ConstraintError::MultipleOf(_) => "multiple_of",
+        ConstraintError::MinLength(_) => "min_length",
+        ConstraintError::MaxLength(_) => "max_length",
+        ConstraintError::Pattern(_) => "pattern",
+        ConstraintError::MinItems(_) => "min_items",
+        ConstraintError::MaxItems(_) => "max_items",
+        ConstraintError::UniqueItems(_) => "unique_items",
+        ConstraintError::MinProperties(_) => "min_properties",
+        ConstraintError::MaxProperties(_) => "max_properties",
+        ConstraintError::Enumerate(_) => "enumerate",
+        ConstraintError::Custom(_) => "custom",
+        ConstraintError::Items(_) => "items",
+        ConstraintError::Properties(_) => "properties",
+        // Variants behind optional serde_valid features (e.g. `fluent`).
+        #[allow(unreachable_patterns)]
+        _ => "custom",
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+
+use crate::AppError;
+
+/// Error returned by the extractors when [`JsonConfig::defer_rendering`] is enabled.
+///
+/// Its response is empty, with the status the error would otherwise have had, and the
+/// structured errors are left in the [`ValidationReport`](crate::ValidationReport) in the
+/// request extensions. App-level middleware then owns the final response:
+///
+/// ```
+/// use actix_json_validator::{ValidationDeferred, ValidationReport};
+/// use actix_web::{
+///     body::{BoxBody, MessageBody},
+///     dev::{ServiceRequest, ServiceResponse},
+///     middleware::Next,
+///     HttpMessage, HttpResponse,
+/// };
+///
+/// async fn render_errors(
+///     req: ServiceRequest,
+///     next: Next<impl MessageBody + 'static>,
+/// ) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+///     let res = next.call(req).await?.map_into_boxed_body();
+///     if !res.response().error().is_some_and(ValidationDeferred::is) {
+///         return Ok(res);
+///     }
+///     let report = res.request().extensions().get::<ValidationReport>().cloned();
+///     let count = report.map_or(0, |report| report.error_count());
+///     let status = res.status();
+///     Ok(res.into_response(
+///         HttpResponse::build(status).body(format!("{count} problems found")),
+///     ))
+/// }
+/// ```
+///
+/// [`JsonConfig::defer_rendering`]: crate::JsonConfig::defer_rendering
+#[derive(Debug, Clone, Copy)]
+pub struct ValidationDeferred {
+    pub(crate) status: StatusCode,
+}
+
+impl ValidationDeferred {
+    /// Whether `err` (typically `ServiceResponse::response().error()`) is a deferred
+    /// validation error.
+    pub fn is(err: &actix_web::Error) -> bool {
+        err.as_error::<ValidationDeferred>().is_some()
+            || matches!(
+                err.as_error::<AppError>(),
+                Some(AppError::Handled(inner)) if inner.as_error::<ValidationDeferred>().is_some()
+            )
+    }
+}
+
+impl fmt::Display for ValidationDeferred {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Validation failed; errors are in the request extensions")
+    }
+}
+
+impl ResponseError for ValidationDeferred {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::new(self.status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, JsonConfig, ValidationReport};
+    use actix_web::{
+        body::{BoxBody, MessageBody},
+        dev::{ServiceRequest, ServiceResponse},
+        middleware::{from_fn, Next},
+        test, web, App, HttpMessage,
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Food {
+        #[validate(min_length = 3)]
+        name: String,
+    }
+
+    async fn render(
+        req: ServiceRequest,
+        next: Next<impl MessageBody + 'static>,
+    ) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+        let res = next.call(req).await?.map_into_boxed_body();
+        if !res.response().error().is_some_and(ValidationDeferred::is) {
+            return Ok(res);
+        }
+        let errors = res
+            .request()
+            .extensions()
+            .get::<ValidationReport>()
+            .map(|report| json!({"tenant": "acme", "problems": report.errors}))
+            .unwrap();
+        let status = res.status();
+        Ok(res.into_response(HttpResponse::build(status).json(errors)))
+    }
+
+    #[actix_web::test]
+    async fn test_middleware_renders_deferred_errors() {
+        let app = test::init_service(
+            App::new()
+                .app_data(JsonConfig::default().defer_rendering(true))
+                .wrap(from_fn(render))
+                .route("/", web::post().to(|_: AppJson<Food>| async { "ok" })),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"name": "Pi"}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            json!({
+                "tenant": "acme",
+                "problems": {"name": ["The length of the value must be `>= 3`."]}
+            })
+        );
+    }
+}
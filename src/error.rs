@@ -0,0 +1,153 @@
+use actix_web::{http::StatusCode, HttpResponse, HttpResponseBuilder, ResponseError};
+use serde_json::Value;
+
+use crate::format::{format_errors, non_field_errors};
+
+/// Messages by field, as rendered in error bodies.
+///
+/// A `HashMap` by default. With the `indexmap` feature, an `IndexMap` instead, so fields
+/// appear in declaration order and array items by index, nested objects included.
+#[cfg(not(feature = "indexmap"))]
+pub type ErrorMap = std::collections::HashMap<String, Value>;
+
+/// Messages by field, as rendered in error bodies.
+///
+/// An `IndexMap`, so fields appear in declaration order and array items by index, nested
+/// objects included.
+#[cfg(feature = "indexmap")]
+pub type ErrorMap = indexmap::IndexMap<String, Value>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("{{\"non_field_errors\": [\"Validation failed\"]}}")]
+    ValidationError(ErrorMap),
+
+    /// An error returned by a custom handler registered on the extractor config.
+    /// Its status code and response are used as-is.
+    #[error("{0}")]
+    Handled(actix_web::Error),
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        match self {
+            AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            AppError::Handled(err) => err.as_response_error().status_code(),
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let response_body = match self {
+            AppError::ValidationError(errors) => {
+                serde_json::json!(errors)
+            }
+            AppError::Handled(err) => return err.error_response(),
+        };
+
+        HttpResponseBuilder::new(self.status_code()).json(response_body)
+    }
+}
+
+/// Conversion into the standard `400 Bad Request` body, for handlers and services that
+/// validate by hand. `?` converts through the matching `From` impls:
+///
+/// ```
+/// use actix_json_validator::AppError;
+/// use serde_valid::Validate;
+/// # #[derive(serde::Deserialize, Validate)]
+/// # struct Address { #[validate(min_length = 2)] country: String }
+///
+/// fn ship(address: Address, stock: u32) -> Result<(), AppError> {
+///     address.validate()?;
+///     if stock == 0 {
+///         Err(vec![("address".to_string(), "Nothing ships there yet.".to_string())])?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub trait IntoAppError {
+    fn into_app_error(self) -> AppError;
+}
+
+impl IntoAppError for serde_valid::validation::Errors {
+    fn into_app_error(self) -> AppError {
+        AppError::ValidationError(format_errors(self))
+    }
+}
+
+/// Messages by field; an empty field name stands for `non_field_errors`.
+impl IntoAppError for Vec<(String, String)> {
+    fn into_app_error(self) -> AppError {
+        let mut errors = ErrorMap::new();
+        for (field, message) in self {
+            let field = if field.is_empty() {
+                non_field_errors()
+            } else {
+                field
+            };
+            let messages = errors
+                .entry(field)
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(messages) = messages {
+                messages.push(Value::String(message));
+            }
+        }
+        AppError::ValidationError(errors)
+    }
+}
+
+impl From<serde_valid::validation::Errors> for AppError {
+    fn from(errors: serde_valid::validation::Errors) -> Self {
+        errors.into_app_error()
+    }
+}
+
+impl From<Vec<(String, String)>> for AppError {
+    fn from(errors: Vec<(String, String)>) -> Self {
+        errors.into_app_error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::MessageBody;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Validate)]
+    struct Address {
+        #[validate(min_length = 2)]
+        country: String,
+    }
+
+    fn body(err: AppError) -> Value {
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn test_manual_validation_errors() {
+        let address = Address {
+            country: "N".to_string(),
+        };
+        let manual = || -> Result<(), AppError> { Ok(address.validate()?) };
+        assert_eq!(
+            body(manual().unwrap_err()),
+            json!({"country": ["The length of the value must be `>= 2`."]})
+        );
+
+        let errors = vec![
+            ("country".to_string(), "Not shipped to.".to_string()),
+            ("country".to_string(), "Embargoed.".to_string()),
+            (String::new(), "Try again later.".to_string()),
+        ];
+        assert_eq!(
+            body(errors.into()),
+            json!({
+                "country": ["Not shipped to.", "Embargoed."],
+                "non_field_errors": ["Try again later."]
+            })
+        );
+    }
+}
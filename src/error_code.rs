@@ -0,0 +1,155 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::ConstraintKind;
+
+/// A stable, namespaced identifier of a kind of failure, for clients and dashboards that
+/// branch on failures without parsing messages. Codes are `namespace.name`:
+///
+/// - `validation.*`: a `serde_valid` constraint failed, named as in its attribute, e.g.
+///   `validation.min_length`, or `validation.custom` for `custom` validators.
+/// - `payload.*`: the body could not be turned into the payload type:
+///   `payload.malformed`, `payload.too_large`, `payload.truncated` or
+///   `payload.unreadable`.
+/// - `media_type.*`: the body has a content type or charset that is not accepted,
+///   `media_type.unsupported`.
+///
+/// Codes are never renamed or removed; new kinds of failure get new codes. They appear
+/// as `code` in [debug details](crate::JsonConfig::debug_errors), and
+/// [`ConstraintKind::code`] gives the code of each of a report's
+/// [failures](crate::ValidationReport::failures). [`ErrorCode::all`] lists them for
+/// documentation pipelines:
+///
+/// ```
+/// use actix_json_validator::ErrorCode;
+///
+/// for code in ErrorCode::all() {
+///     println!("| `{code}` | {} |", code.description());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct ErrorCode(&'static str);
+
+macro_rules! codes {
+    ($($name:ident = $code:literal, $description:literal;)*) => {
+        impl ErrorCode {
+            $(
+                #[doc = $description]
+                pub const $name: ErrorCode = ErrorCode($code);
+            )*
+
+            /// Every code, in namespace order.
+            pub fn all() -> &'static [ErrorCode] {
+                &[$(ErrorCode::$name),*]
+            }
+
+            /// What the code stands for, in one sentence.
+            pub fn description(&self) -> &'static str {
+                match self.0 {
+                    $($code => $description,)*
+                    _ => "",
+                }
+            }
+        }
+    };
+}
+
+codes! {
+    MINIMUM = "validation.minimum", "A number is below its `minimum`.";
+    MAXIMUM = "validation.maximum", "A number is above its `maximum`.";
+    EXCLUSIVE_MINIMUM = "validation.exclusive_minimum",
+        "A number is not above its `exclusive_minimum`.";
+    EXCLUSIVE_MAXIMUM = "validation.exclusive_maximum",
+        "A number is not below its `exclusive_maximum`.";
+    MULTIPLE_OF = "validation.multiple_of", "A number is not a `multiple_of` its step.";
+    MIN_LENGTH = "validation.min_length", "A string is shorter than its `min_length`.";
+    MAX_LENGTH = "validation.max_length", "A string is longer than its `max_length`.";
+    PATTERN = "validation.pattern", "A string does not match its `pattern`.";
+    MIN_ITEMS = "validation.min_items", "An array has fewer items than its `min_items`.";
+    MAX_ITEMS = "validation.max_items", "An array has more items than its `max_items`.";
+    UNIQUE_ITEMS = "validation.unique_items", "An array with `unique_items` has duplicates.";
+    MIN_PROPERTIES = "validation.min_properties",
+        "A map has fewer entries than its `min_properties`.";
+    MAX_PROPERTIES = "validation.max_properties",
+        "A map has more entries than its `max_properties`.";
+    ENUMERATE = "validation.enumerate", "A value is not one of its `enumerate` values.";
+    CUSTOM = "validation.custom", "A `custom` validator failed.";
+    MALFORMED = "payload.malformed", "The body is not valid JSON for the payload type.";
+    TOO_LARGE = "payload.too_large", "The body exceeds the size limit.";
+    TRUNCATED = "payload.truncated", "The body ended before it was complete.";
+    UNREADABLE = "payload.unreadable", "The body could not be read or decoded.";
+    UNSUPPORTED_MEDIA_TYPE = "media_type.unsupported",
+        "The body's content type or charset is not accepted.";
+}
+
+impl ErrorCode {
+    /// The code, e.g. `validation.min_length`.
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+
+    /// The code of a constraint or payload failure named as in the debug details.
+    /// Failures of custom extraction stages, such as a decryption step, are
+    /// `payload.unreadable`.
+    pub(crate) fn of_constraint(name: &str) -> ErrorCode {
+        let validation = ErrorCode::all()
+            .iter()
+            .find(|code| code.0.strip_prefix("validation.") == Some(name));
+        match (validation, name) {
+            (Some(code), _) => *code,
+            // Failures nested in the items or properties of a value, reported whole.
+            (None, "items" | "properties") => ErrorCode::CUSTOM,
+            (None, "deserialization") => ErrorCode::MALFORMED,
+            (None, "limit") => ErrorCode::TOO_LARGE,
+            (None, "truncated") => ErrorCode::TRUNCATED,
+            (None, "content_type") => ErrorCode::UNSUPPORTED_MEDIA_TYPE,
+            _ => ErrorCode::UNREADABLE,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl ConstraintKind {
+    /// The stable [`ErrorCode`] of this kind of failure.
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::of_constraint(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_codes_are_unique_and_namespaced() {
+        let codes: HashSet<&str> = ErrorCode::all().iter().map(ErrorCode::as_str).collect();
+        assert_eq!(codes.len(), ErrorCode::all().len());
+        assert!(ErrorCode::all().iter().all(|code| {
+            let (namespace, _) = code.as_str().split_once('.').unwrap();
+            ["validation", "payload", "media_type"].contains(&namespace)
+                && !code.description().is_empty()
+        }));
+
+        assert_eq!(ConstraintKind::MinLength.code(), ErrorCode::MIN_LENGTH);
+        assert_eq!(
+            ConstraintKind::Custom("taken".to_string()).code().as_str(),
+            "validation.custom"
+        );
+        assert_eq!(
+            ConstraintKind::Limit.code().to_string(),
+            "payload.too_large"
+        );
+        assert_eq!(
+            ConstraintKind::ContentType.code(),
+            ErrorCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use actix_web::{
+    http::{header::ContentType, StatusCode},
+    HttpRequest, HttpResponse, ResponseError,
+};
+use serde_json::json;
+
+use crate::{AppError, JsonConfig};
+
+/// Builds the error response of a format registered with [`JsonConfig::error_format`].
+pub(crate) type Renderer =
+    Arc<dyn Fn(&AppError, StatusCode, &HttpRequest, &'static str) -> HttpResponse + Send + Sync>;
+
+/// Error format requested by a request.
+pub(crate) enum Requested<'a> {
+    /// No format, or one the default rendering serves.
+    Default,
+    Format(&'a Renderer),
+    /// Only formats that are not registered.
+    Unsupported,
+}
+
+/// Names the default rendering answers to, also as `Accept` values.
+const DEFAULT_NAMES: [&str; 5] = ["json", "application/json", "application/*", "*/*", "*"];
+
+/// The format requested by `req`. The header holds a name or, like `Accept`, a
+/// comma-separated list of them, optionally as `application/<name>` media types with
+/// parameters; the first registered one wins, and those with `q=0` are skipped.
+pub(crate) fn requested<'a>(config: &'a JsonConfig, req: &HttpRequest) -> Requested<'a> {
+    let Some(value) = req.headers().get(&config.error_format_header) else {
+        return Requested::Default;
+    };
+    let Ok(value) = value.to_str() else {
+        return Requested::Unsupported;
+    };
+    let mut names = value
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';').map(str::trim);
+            let name = parts.next().filter(|name| !name.is_empty())?;
+            let refused = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (!refused).then_some(name)
+        })
+        .peekable();
+    if names.peek().is_none() {
+        return Requested::Default;
+    }
+    for name in names {
+        if DEFAULT_NAMES
+            .iter()
+            .any(|default| default.eq_ignore_ascii_case(name))
+        {
+            return Requested::Default;
+        }
+        let bare = name
+            .get(..12)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("application/"))
+            .map_or(name, |_| &name[12..]);
+        let renderer = config.error_formats.iter().find(|(format, _)| {
+            format.eq_ignore_ascii_case(name) || format.eq_ignore_ascii_case(bare)
+        });
+        if let Some((_, renderer)) = renderer {
+            return Requested::Format(renderer);
+        }
+    }
+    Requested::Unsupported
+}
+
+/// `406 Not Acceptable` for a request whose error formats are all unsupported, listing
+/// the supported ones.
+pub(crate) fn not_acceptable(config: &JsonConfig) -> HttpResponse {
+    let supported: Vec<&str> = std::iter::once("json")
+        .chain(config.error_formats.iter().map(|(name, _)| name.as_str()))
+        .collect();
+    HttpResponse::NotAcceptable().json(json!({
+        "error": ["The requested error format is not supported"],
+        "supported": supported,
+    }))
+}
+
+/// Render errors as RFC 7807 problem details, with the usual messages by field under
+/// `errors`, for use with [`JsonConfig::error_format`]:
+///
+/// ```json
+/// {
+///     "type": "about:blank",
+///     "title": "Validation failed",
+///     "status": 400,
+///     "errors": {"name": ["The length of the value must be `>= 3`."]}
+/// }
+/// ```
+///
+/// Errors already turned into responses by an error handler are left untouched.
+pub fn problem_details(
+    err: &AppError,
+    status: StatusCode,
+    _: &HttpRequest,
+    _: &'static str,
+) -> HttpResponse {
+    let AppError::ValidationError(errors) = err else {
+        return err.error_response();
+    };
+    HttpResponse::build(status)
+        .content_type(ContentType(
+            "application/problem+json"
+                .parse()
+                .expect("valid media type"),
+        ))
+        .json(json!({
+            "type": "about:blank",
+            "title": "Validation failed",
+            "status": status.as_u16(),
+            "errors": errors,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, FailureClass};
+    use actix_web::{body::MessageBody, test, FromRequest};
+    use serde::Deserialize;
+    use serde_json::Value;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Food {
+        #[validate(min_length = 3)]
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    async fn respond(format: Option<&str>) -> (StatusCode, String, Value) {
+        let config = JsonConfig::default()
+            .status(FailureClass::Schema, StatusCode::UNPROCESSABLE_ENTITY)
+            .error_format("problem+json", problem_details);
+        let mut req = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"name": "Pi"}));
+        if let Some(format) = format {
+            req = req.insert_header(("x-error-format", format));
+        }
+        let (req, mut payload) = req.to_http_parts();
+        let err = AppJson::<Food>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let response = err.error_response();
+        let content_type = response
+            .headers()
+            .get(actix_web::http::header::CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let status = response.status();
+        let body = response.into_body().try_into_bytes().unwrap();
+        (status, content_type, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_format_selected_by_header() {
+        let messages = json!({"name": ["The length of the value must be `>= 3`."]});
+
+        assert_eq!(
+            respond(Some("Problem+JSON")).await,
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "application/problem+json".to_string(),
+                json!({
+                    "type": "about:blank",
+                    "title": "Validation failed",
+                    "status": 422,
+                    "errors": messages,
+                })
+            )
+        );
+
+        for format in [None, Some("xml")] {
+            assert_eq!(
+                respond(format).await,
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "application/json".to_string(),
+                    messages.clone()
+                )
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unsupported_formats_are_not_acceptable() {
+        let config = JsonConfig::default()
+            .error_format("problem+json", problem_details)
+            .error_format_header("Accept")
+            .reject_unsupported_formats(true);
+        let respond = |accept: &'static str| {
+            let config = config.clone();
+            async move {
+                let (req, mut payload) = test::TestRequest::post()
+                    .app_data(config)
+                    .insert_header(("accept", accept))
+                    .set_json(json!({"name": "Pi"}))
+                    .to_http_parts();
+                let response = AppJson::<Food>::from_request(&req, &mut payload)
+                    .await
+                    .unwrap_err()
+                    .error_response();
+                let status = response.status();
+                let body = response.into_body().try_into_bytes().unwrap();
+                (status, serde_json::from_slice::<Value>(&body).unwrap())
+            }
+        };
+
+        let (status, body) = respond("text/xml, application/problem+json;q=0.5").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["title"], "Validation failed");
+        let (status, _) = respond("application/json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert_eq!(
+            respond("text/xml, application/problem+json;q=0").await,
+            (
+                StatusCode::NOT_ACCEPTABLE,
+                json!({
+                    "error": ["The requested error format is not supported"],
+                    "supported": ["json", "problem+json"],
+                })
+            )
+        );
+    }
+}
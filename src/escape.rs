@@ -0,0 +1,60 @@
+//! Neutralizing markup in error messages, configured with
+//! [`JsonConfig::escape_messages`](crate::JsonConfig::escape_messages).
+
+/// What to do with HTML in error messages, which may echo user-submitted values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageEscape {
+    /// Replace `&`, `<`, `>`, `"` and `'` with their HTML entities.
+    Html,
+    /// Remove `<` and `>`, so no tag survives.
+    Strip,
+}
+
+impl MessageEscape {
+    pub(crate) fn apply(self, text: &mut String) {
+        let special: &[char] = match self {
+            MessageEscape::Html => &['&', '<', '>', '"', '\''],
+            MessageEscape::Strip => &['<', '>'],
+        };
+        if !text.contains(special) {
+            return;
+        }
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match (self, c) {
+                (MessageEscape::Html, '&') => escaped.push_str("&amp;"),
+                (MessageEscape::Html, '<') => escaped.push_str("&lt;"),
+                (MessageEscape::Html, '>') => escaped.push_str("&gt;"),
+                (MessageEscape::Html, '"') => escaped.push_str("&quot;"),
+                (MessageEscape::Html, '\'') => escaped.push_str("&#39;"),
+                (MessageEscape::Strip, '<' | '>') => {}
+                (_, c) => escaped.push(c),
+            }
+        }
+        *text = escaped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_and_strip() {
+        let message = r#"Unknown tag `<img src=x onerror="alert('hi')">` & more."#;
+
+        let mut escaped = message.to_string();
+        MessageEscape::Html.apply(&mut escaped);
+        assert_eq!(
+            escaped,
+            "Unknown tag `&lt;img src=x onerror=&quot;alert(&#39;hi&#39;)&quot;&gt;` &amp; more."
+        );
+
+        let mut stripped = message.to_string();
+        MessageEscape::Strip.apply(&mut stripped);
+        assert_eq!(
+            stripped,
+            r#"Unknown tag `img src=x onerror="alert('hi')"` & more."#
+        );
+    }
+}
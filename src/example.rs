@@ -0,0 +1,410 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    debug::join,
+    metadata::{Constraint, Describe, TypeMetadata},
+};
+
+/// Example payloads of `T` built from its declared constraints, so doc examples, fuzz
+/// seeds and contract tests follow the validation rules as they change.
+///
+/// ```
+/// use actix_json_validator::{examples_of, Describe};
+/// use serde::Deserialize;
+/// use serde_valid::Validate;
+///
+/// #[derive(Deserialize, Validate, Describe)]
+/// struct Signup {
+///     #[validate(min_length = 3)]
+///     name: String,
+///     #[validate(minimum = 18)]
+///     age: u8,
+/// }
+///
+/// let examples = examples_of::<Signup>();
+/// let signup: Signup = serde_json::from_value(examples.valid).unwrap();
+/// assert!(signup.validate().is_ok());
+///
+/// for example in examples.invalid {
+///     let signup: Signup = serde_json::from_value(example.payload).unwrap();
+///     assert!(signup.validate().is_err(), "{} {}", example.path, example.kind);
+/// }
+/// ```
+///
+/// Optional fields are filled in, and collections hold as few elements as their bounds
+/// allow, at least one. `pattern` and `custom` constraints cannot be solved from their
+/// parameters: the values they constrain may need adjusting, and they have no invalid
+/// example.
+pub fn examples_of<T: Describe>() -> Examples {
+    let metadata = T::metadata();
+    Examples {
+        valid: object(&metadata, 0),
+        invalid: object_violations(&metadata)
+            .into_iter()
+            .map(|(path, kind, payload)| InvalidExample {
+                path,
+                kind,
+                payload,
+            })
+            .collect(),
+    }
+}
+
+/// Example payloads of a type, see [`examples_of`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Examples {
+    /// A payload satisfying every declared constraint.
+    pub valid: Value,
+    /// For each declared constraint that can be violated, a payload violating only it,
+    /// as narrowly as possible.
+    pub invalid: Vec<InvalidExample>,
+}
+
+/// A payload violating one declared constraint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvalidExample {
+    /// The path of the constraint, as in [`DeclaredConstraint::path`].
+    ///
+    /// [`DeclaredConstraint::path`]: crate::DeclaredConstraint::path
+    pub path: String,
+    /// The violated constraint, named as in the `serde_valid` attribute.
+    pub kind: &'static str,
+    /// The payload.
+    pub payload: Value,
+}
+
+/// The JSON shape of a field type, as written.
+enum Shape<'a> {
+    Array(Box<Shape<'a>>),
+    Map,
+    Object(&'a TypeMetadata),
+    String,
+    Integer { signed: bool },
+    Float,
+    Bool,
+    Unknown,
+}
+
+impl<'a> Shape<'a> {
+    fn of(ty: &str, nested: Option<&'a TypeMetadata>) -> Self {
+        let ty = ty.trim();
+        if let Some((name, rest)) = ty.split_once('<') {
+            let inner = rest.strip_suffix('>').unwrap_or(rest);
+            return match name.rsplit("::").next().unwrap_or(name) {
+                "Vec" | "ParVec" | "VecDeque" | "HashSet" | "BTreeSet" | "IndexSet" => {
+                    Shape::Array(Box::new(Shape::of(inner, nested)))
+                }
+                "HashMap" | "BTreeMap" | "IndexMap" => Shape::Map,
+                "Cow" => Shape::String,
+                _ => Shape::of(inner, nested),
+            };
+        }
+        match ty.trim_start_matches('&').rsplit("::").next().unwrap_or(ty) {
+            "String" | "str" | "char" => Shape::String,
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => Shape::Integer { signed: true },
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => Shape::Integer { signed: false },
+            "f32" | "f64" => Shape::Float,
+            "bool" => Shape::Bool,
+            _ => nested.map_or(Shape::Unknown, Shape::Object),
+        }
+    }
+}
+
+/// Constraints `serde_valid` checks on a collection itself rather than on its elements.
+const COLLECTION_CONSTRAINTS: [&str; 3] = ["min_items", "max_items", "unique_items"];
+
+fn parameter(constraints: &[&Constraint], code: &str) -> Option<Value> {
+    constraints
+        .iter()
+        .find(|constraint| constraint.code == code)
+        .map(|constraint| constraint.value.clone())
+}
+
+fn bound(constraints: &[&Constraint], code: &str) -> Option<f64> {
+    parameter(constraints, code)?.as_f64()
+}
+
+fn count(constraints: &[&Constraint], code: &str) -> Option<usize> {
+    parameter(constraints, code)?
+        .as_u64()
+        .and_then(|count| usize::try_from(count).ok())
+}
+
+fn number(value: f64, shape: &Shape) -> Value {
+    match shape {
+        // Bounds of integer fields are integers.
+        Shape::Integer { .. } => Value::from(value as i64),
+        _ => Value::from(value),
+    }
+}
+
+/// A string of `length` characters, distinct for each `index`.
+fn text(length: usize, index: usize) -> String {
+    let mut text = "a".repeat(length.saturating_sub(1));
+    if length > 0 {
+        text.push(char::from(b'a' + (index % 26) as u8));
+    }
+    text
+}
+
+fn object(metadata: &TypeMetadata, index: usize) -> Value {
+    let fields = metadata.fields.iter().map(|field| {
+        let constraints: Vec<_> = field.constraints.iter().collect();
+        let shape = Shape::of(field.ty, field.nested.as_ref());
+        (field.name.to_string(), value(&shape, &constraints, index))
+    });
+    Value::Object(fields.collect())
+}
+
+/// A valid value of `shape`, the `index`th of a collection.
+fn value(shape: &Shape, constraints: &[&Constraint], index: usize) -> Value {
+    if let (false, Some(Value::Array(values))) = (
+        matches!(shape, Shape::Array(_)),
+        parameter(constraints, "enumerate"),
+    ) {
+        if !values.is_empty() {
+            return values[index % values.len()].clone();
+        }
+    }
+    match shape {
+        Shape::Array(item) => {
+            let items: Vec<_> = constraints
+                .iter()
+                .copied()
+                .filter(|constraint| !COLLECTION_CONSTRAINTS.contains(&constraint.code))
+                .collect();
+            let length = items_length(constraints);
+            Value::Array((0..length).map(|i| value(item, &items, i)).collect())
+        }
+        Shape::Map => Value::Object(Map::new()),
+        Shape::Object(metadata) => object(metadata, index),
+        Shape::String => {
+            let length = count(constraints, "min_length").unwrap_or(1).max(1);
+            let length = count(constraints, "max_length").map_or(length, |max| length.min(max));
+            Value::String(text(length, index))
+        }
+        Shape::Integer { .. } | Shape::Float => {
+            let step = if matches!(shape, Shape::Float) {
+                0.5
+            } else {
+                1.0
+            };
+            let lower = bound(constraints, "minimum")
+                .or_else(|| bound(constraints, "exclusive_minimum").map(|bound| bound + step));
+            let upper = bound(constraints, "maximum")
+                .or_else(|| bound(constraints, "exclusive_maximum").map(|bound| bound - step));
+            let mut start = lower.unwrap_or_else(|| upper.map_or(1.0, |upper| upper.min(1.0)));
+            let step = bound(constraints, "multiple_of").unwrap_or(step);
+            if let Some(multiple) = bound(constraints, "multiple_of") {
+                start = (start / multiple).ceil() * multiple;
+            }
+            number(start + index as f64 * step, shape)
+        }
+        Shape::Bool => Value::Bool(index.is_multiple_of(2)),
+        Shape::Unknown => Value::Null,
+    }
+}
+
+fn items_length(constraints: &[&Constraint]) -> usize {
+    let length = count(constraints, "min_items").unwrap_or(1).max(1);
+    count(constraints, "max_items").map_or(length, |max| length.min(max))
+}
+
+/// Every declared constraint of `metadata` that can be violated, with its path relative
+/// to it and the violating object.
+fn object_violations(metadata: &TypeMetadata) -> Vec<(String, &'static str, Value)> {
+    let valid = object(metadata, 0);
+    let mut violations = Vec::new();
+    for field in &metadata.fields {
+        let constraints: Vec<_> = field.constraints.iter().collect();
+        let shape = Shape::of(field.ty, field.nested.as_ref());
+        for (path, kind, value) in field_violations(&shape, &constraints) {
+            let mut payload = valid.clone();
+            payload[field.name] = value;
+            let path = if path.is_empty() {
+                field.name.to_string()
+            } else {
+                join(field.name, &path)
+            };
+            violations.push((path, kind, payload));
+        }
+    }
+    violations
+}
+
+/// The violations of a field value of `shape`, with paths relative to the field.
+fn field_violations(
+    shape: &Shape,
+    constraints: &[&Constraint],
+) -> Vec<(String, &'static str, Value)> {
+    let mut violations: Vec<_> = constraints
+        .iter()
+        .filter_map(|constraint| {
+            violate(shape, constraints, constraint)
+                .map(|value| (String::new(), constraint.code, value))
+        })
+        .collect();
+    match shape {
+        Shape::Array(item) => {
+            let valid = value(shape, constraints, 0);
+            let Shape::Object(metadata) = item.as_ref() else {
+                return violations;
+            };
+            for (path, kind, item) in object_violations(metadata) {
+                let mut array = valid.clone();
+                array[0] = item;
+                violations.push((join("*", &path), kind, array));
+            }
+        }
+        Shape::Object(metadata) => violations.extend(object_violations(metadata)),
+        _ => {}
+    }
+    violations
+}
+
+/// A value of `shape` violating `constraint` and, as far as possible, only it.
+fn violate(shape: &Shape, constraints: &[&Constraint], constraint: &Constraint) -> Option<Value> {
+    if let Shape::Array(item) = shape {
+        let items: Vec<_> = constraints
+            .iter()
+            .copied()
+            .filter(|constraint| !COLLECTION_CONSTRAINTS.contains(&constraint.code))
+            .collect();
+        let Value::Array(valid) = value(shape, constraints, 0) else {
+            return None;
+        };
+        let bound = constraint
+            .value
+            .as_u64()
+            .and_then(|bound| usize::try_from(bound).ok());
+        let length = match constraint.code {
+            "min_items" => bound?.checked_sub(1)?,
+            "max_items" => bound? + 1,
+            "unique_items" => {
+                let length = valid.len().max(2);
+                return Some(Value::Array(vec![valid[0].clone(); length]));
+            }
+            _ => {
+                // Constraints on the elements, violated by the first one.
+                let mut valid = valid;
+                valid[0] = violate(item, &items, constraint)?;
+                return Some(Value::Array(valid));
+            }
+        };
+        return Some(Value::Array(
+            (0..length).map(|i| value(item, &items, i)).collect(),
+        ));
+    }
+
+    let parameter = &constraint.value;
+    match (shape, constraint.code) {
+        (_, "enumerate") => {
+            let values = parameter.as_array()?;
+            match values.first()? {
+                Value::String(first) => {
+                    let mut other = format!("{first}x");
+                    while values.contains(&Value::String(other.clone())) {
+                        other.push('x');
+                    }
+                    Some(Value::String(other))
+                }
+                _ => {
+                    let largest = values.iter().filter_map(Value::as_f64).reduce(f64::max)?;
+                    Some(number(largest + 1.0, shape))
+                }
+            }
+        }
+        (Shape::String, "min_length") => {
+            let length = usize::try_from(parameter.as_u64()?).ok()?.checked_sub(1)?;
+            Some(Value::String(text(length, 0)))
+        }
+        (Shape::String, "max_length") => {
+            let length = usize::try_from(parameter.as_u64()?).ok()? + 1;
+            Some(Value::String(text(length, 0)))
+        }
+        (Shape::Integer { .. } | Shape::Float, kind) => {
+            let bound = parameter.as_f64()?;
+            let step = if matches!(shape, Shape::Float) {
+                0.5
+            } else {
+                1.0
+            };
+            let invalid = match kind {
+                "minimum" => bound - step,
+                "exclusive_minimum" | "exclusive_maximum" => bound,
+                "maximum" => bound + step,
+                "multiple_of" => {
+                    let valid = value(shape, constraints, 0).as_f64()?;
+                    let offset = if bound > 1.0 { 1.0 } else { bound / 2.0 };
+                    if matches!(shape, Shape::Integer { .. }) && offset < 1.0 {
+                        return None;
+                    }
+                    valid + offset
+                }
+                _ => return None,
+            };
+            if matches!(shape, Shape::Integer { signed: false }) && invalid < 0.0 {
+                return None;
+            }
+            Some(number(invalid, shape))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::truncate::by_code;
+    use serde::Deserialize;
+    use serde_valid::Validate;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, Validate, Describe)]
+    struct Order {
+        #[validate(min_length = 3)]
+        #[validate(max_length = 8)]
+        reference: String,
+        #[validate(min_items = 2)]
+        #[validate(max_items = 4)]
+        #[validate(unique_items)]
+        #[validate]
+        lines: Vec<Line>,
+        #[validate(max_length = 5)]
+        tags: Option<Vec<String>>,
+        #[validate(multiple_of = 5)]
+        #[validate(exclusive_minimum = 0)]
+        discount: Option<u8>,
+    }
+
+    #[derive(Debug, Deserialize, Validate, Describe, PartialEq, Eq, Hash)]
+    struct Line {
+        #[validate(enumerate = ["kg", "pcs"])]
+        unit: String,
+        #[validate(minimum = 1)]
+        #[validate(maximum = 100)]
+        quantity: u32,
+    }
+
+    #[test]
+    fn test_examples_follow_constraints() {
+        let examples = examples_of::<Order>();
+        let order: Order = serde_json::from_value(examples.valid).unwrap();
+        assert!(order.validate().is_ok(), "{order:?}");
+
+        let constraints = crate::constraints_of::<Order>();
+        assert_eq!(examples.invalid.len(), constraints.len());
+        for example in examples.invalid {
+            assert!(constraints.contains(&example.path, example.kind));
+            let order: Order = serde_json::from_value(example.payload).unwrap();
+            let errors = order.validate().unwrap_err();
+            assert_eq!(
+                by_code(&errors),
+                BTreeMap::from([(example.kind.to_string(), 1)]),
+                "{}",
+                example.path
+            );
+        }
+    }
+}
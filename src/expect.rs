@@ -0,0 +1,166 @@
+use actix_http::{body::BoxBody, Request, Response};
+use actix_web::{
+    dev::{always_ready, Service, ServiceFactory},
+    error::PayloadError,
+    http::StatusCode,
+    HttpResponse,
+};
+use futures_util::future::{ready, Ready};
+
+use crate::{extract, AppError, FailureClass, JsonConfig};
+
+/// Rejects uploads sent with `Expect: 100-continue` before the interim response, from
+/// their headers alone, so clients never transmit bodies that would fail the content
+/// type or size limit of a [`JsonConfig`].
+///
+/// `HttpServer` always accepts the expectation, so the handler goes on an
+/// `actix_http::HttpService` built by hand:
+///
+/// ```
+/// use actix_json_validator::{ExpectContinue, JsonConfig};
+/// use actix_service::map_config;
+/// use actix_web::{
+///     dev::{AppConfig, Server},
+///     App,
+/// };
+///
+/// # fn run() -> std::io::Result<Server> {
+/// let config = JsonConfig::default()
+///     .limit(1024 * 1024)
+///     .content_type_required(true);
+/// let server = Server::build()
+///     .bind("uploads", ("127.0.0.1", 8080), move || {
+///         let app = App::new().app_data(config.clone());
+///         actix_http::HttpService::build()
+///             .expect(ExpectContinue::new(config.clone()))
+///             .finish(map_config(app, |_| AppConfig::default()))
+///             .tcp()
+///     })?
+///     .run();
+/// # Ok(server)
+/// # }
+/// ```
+///
+/// Rejections have the body of the extractors' own: `{"error": ["Content type error"]}`
+/// with `400 Bad Request`, or the size limit message with the status of
+/// [`FailureClass::Security`] if one is configured. The handler runs before routing,
+/// so it only sees the config it was given, not those of scopes or
+/// [`ConfigByPrefix`](crate::ConfigByPrefix).
+#[derive(Clone)]
+pub struct ExpectContinue {
+    config: JsonConfig,
+}
+
+impl ExpectContinue {
+    /// Check expectations against the content type and size limit of `config`.
+    pub fn new(config: JsonConfig) -> Self {
+        ExpectContinue { config }
+    }
+
+    /// The status and message rejecting `req`, if any.
+    fn check(&self, req: &Request) -> Result<(), (StatusCode, String)> {
+        if !extract::is_json(req, &self.config) {
+            return Err((StatusCode::BAD_REQUEST, "Content type error".to_string()));
+        }
+        if let Some(charset) = extract::foreign_charset(req, &self.config) {
+            let message = extract::charset_message(&charset);
+            return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, message));
+        }
+        let length = extract::content_length(req);
+        if length.is_some_and(|length| length > self.config.limit) {
+            let status = self
+                .config
+                .statuses
+                .get(&FailureClass::Security)
+                .copied()
+                .unwrap_or(StatusCode::BAD_REQUEST);
+            return Err((status, PayloadError::Overflow.to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn reject(status: StatusCode, message: String) -> HttpResponse {
+    match extract::payload_error(message, None) {
+        AppError::ValidationError(errors) => HttpResponse::build(status).json(errors),
+        AppError::Handled(err) => err.error_response(),
+    }
+}
+
+impl ServiceFactory<Request> for ExpectContinue {
+    type Response = Request;
+    type Error = Response<BoxBody>;
+    type Config = ();
+    type Service = ExpectContinue;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        ready(Ok(self.clone()))
+    }
+}
+
+impl Service<Request> for ExpectContinue {
+    type Response = Request;
+    type Error = Response<BoxBody>;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    always_ready!();
+
+    fn call(&self, req: Request) -> Self::Future {
+        ready(match self.check(&req) {
+            Ok(()) => Ok(req),
+            Err((status, message)) => Err(reject(status, message).into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_http::test::TestRequest;
+    use actix_web::body::MessageBody;
+    use serde_json::{json, Value};
+
+    async fn expect(config: JsonConfig, headers: &[(&str, &str)]) -> Result<(), (u16, Value)> {
+        let mut req = TestRequest::default();
+        req.insert_header(("expect", "100-continue"));
+        for &header in headers {
+            req.insert_header(header);
+        }
+        let service = ExpectContinue::new(config).new_service(()).await.unwrap();
+        match service.call(req.finish()).await {
+            Ok(_) => Ok(()),
+            Err(response) => {
+                let status = response.status().as_u16();
+                let body = response.into_body().try_into_bytes().unwrap();
+                Err((status, serde_json::from_slice(&body).unwrap()))
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_rejects_from_headers() {
+        let config = JsonConfig::default()
+            .limit(1024)
+            .status(FailureClass::Security, StatusCode::PAYLOAD_TOO_LARGE);
+        let json = ("content-type", "application/json");
+
+        assert_eq!(
+            expect(config.clone(), &[json, ("content-length", "512")]).await,
+            Ok(())
+        );
+        assert_eq!(
+            expect(config.clone(), &[json, ("content-length", "4096")]).await,
+            Err((413, json!({"error": ["payload reached size limit"]})))
+        );
+        assert_eq!(
+            expect(
+                config,
+                &[("content-type", "text/csv"), ("content-length", "512")]
+            )
+            .await,
+            Err((400, json!({"error": ["Content type error"]})))
+        );
+    }
+}
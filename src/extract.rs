@@ -0,0 +1,696 @@
+//! Pipeline steps shared by the crate's extractors.
+
+use std::time::Instant;
+
+use actix_web::{
+    body::{BodySize, MessageBody},
+    dev::Payload,
+    error::{InternalError, JsonPayloadError, PayloadError},
+    http::{header, StatusCode},
+    web::{self, Bytes, BytesMut},
+    HttpMessage, HttpRequest, HttpResponse, ResponseError,
+};
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use serde_valid::{validation::Errors as ValidationError, Validate};
+
+use crate::{
+    budget, code, debug,
+    error_format::{self, Requested},
+    format::{format_errors_with, join_messages, limit_messages, sanitize_messages},
+    kind, label, prefix, severity, shape, stats, status, strict, truncate, AppError,
+    ConstraintKind, ErrorMap, ExtractionOutcome, Failure, FailureClass, JsonConfig,
+    ValidationDeferred, ValidationReport,
+};
+
+/// The [`JsonConfig`] for this request. A config inserted into the request extensions,
+/// e.g. by middleware that loaded the tenant's settings, takes precedence over the one
+/// registered as app data, which the [`ConfigByPrefix`](crate::ConfigByPrefix) settings
+/// for the request's path are layered over.
+pub(crate) fn config(req: &HttpRequest) -> JsonConfig {
+    let extension = req.extensions().get::<JsonConfig>().cloned();
+    let mut config = match extension {
+        Some(config) => config,
+        None => prefix::apply(registered(req).unwrap_or_default(), req),
+    };
+    if config.debug_requested(req) {
+        config.debug_errors = true;
+    }
+    config
+}
+
+/// The [`JsonConfig`] registered as app data, or one mistakenly wrapped in `web::Data`,
+/// which debug builds warn about once.
+fn registered(req: &HttpRequest) -> Option<JsonConfig> {
+    if let Some(config) = req.app_data::<JsonConfig>() {
+        return Some(config.clone());
+    }
+    let config = req.app_data::<web::Data<JsonConfig>>()?;
+    #[cfg(debug_assertions)]
+    {
+        static WARNED: std::sync::Once = std::sync::Once::new();
+        WARNED.call_once(|| {
+            log::warn!(
+                "JsonConfig is registered as `web::Data<JsonConfig>`; register it with \
+                 `.app_data(config)` or `config.register(cfg)` instead. Using it anyway."
+            );
+        });
+    }
+    Some(JsonConfig::clone(config))
+}
+
+/// Failures below [`Severity::Error`](crate::Severity::Error) and the class of the
+/// rejection, kept in the request extensions until [`finish`] moves them into the
+/// [`ValidationReport`].
+#[derive(Default)]
+struct Findings {
+    warnings: ErrorMap,
+    info: ErrorMap,
+    class: Option<FailureClass>,
+    status: Option<StatusCode>,
+    oversized: Option<usize>,
+    size: Option<usize>,
+    failures: Vec<Failure>,
+    /// The body rendered by [`JsonConfig::error_body`], replacing the error map.
+    body: Option<Value>,
+}
+
+/// Record why the payload is being rejected. Rejections that are not classified are
+/// reported as [`FailureClass::Payload`].
+pub(crate) fn classify(req: &HttpRequest, class: FailureClass) {
+    req.extensions_mut()
+        .get_or_insert_with(Findings::default)
+        .class = Some(class);
+}
+
+/// Report `warnings` in the [`ValidationReport`] without rejecting the payload.
+#[cfg_attr(not(feature = "unknown-keys"), allow(dead_code))]
+pub(crate) fn warn(req: &HttpRequest, warnings: ErrorMap) {
+    req.extensions_mut()
+        .get_or_insert_with(Findings::default)
+        .warnings
+        .extend(warnings);
+}
+
+/// Respond to the rejection with `status`, taking precedence over the statuses of
+/// [`JsonConfig::status`].
+pub(crate) fn escalate(req: &HttpRequest, status: StatusCode) {
+    req.extensions_mut()
+        .get_or_insert_with(Findings::default)
+        .status = Some(status);
+}
+
+/// Record a failure of the payload as a whole.
+pub(crate) fn fail(req: &HttpRequest, kind: ConstraintKind) {
+    req.extensions_mut()
+        .get_or_insert_with(Findings::default)
+        .failures
+        .push(Failure {
+            path: String::new(),
+            kind,
+        });
+}
+
+/// Record the size of a body of `length` bytes, flagging it when it exceeds
+/// [`JsonConfig::soft_limit`].
+pub(crate) fn soft_limit(config: &JsonConfig, req: &HttpRequest, length: usize) {
+    let mut extensions = req.extensions_mut();
+    let findings = extensions.get_or_insert_with(Findings::default);
+    findings.size = Some(length);
+    if config.soft_limit.is_some_and(|limit| length > limit) {
+        findings.oversized = Some(length);
+    }
+}
+
+/// Deserialize a buffered JSON body.
+pub(crate) fn deserialize<T: DeserializeOwned>(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+    body: &[u8],
+) -> Result<T, AppError> {
+    serde_json::from_slice(body).map_err(|err| {
+        fail(req, ConstraintKind::Deserialization);
+        let message = format!("Json deserialize error: {err}");
+        decode_error(config, type_name, "deserialization", message)
+    })
+}
+
+/// Run `serde_valid` validation on a deserialized payload, formatting failures into the
+/// crate's error shape.
+pub(crate) fn validate<T: Validate>(
+    data: T,
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+) -> Result<T, AppError> {
+    check(&data, config, req, type_name).map(|()| data)
+}
+
+/// Like [`validate`], for callers that keep the payload whatever the outcome.
+pub(crate) fn check<T: Validate>(
+    data: &T,
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+) -> Result<(), AppError> {
+    outcome(validator(req, type_name)(data), config, req, type_name)
+}
+
+/// `serde_valid` validation, timed into the app's
+/// [`ValidationProfile`](crate::ValidationProfile) if it has one.
+pub(crate) fn validator<T: Validate>(
+    req: &HttpRequest,
+    type_name: &str,
+) -> impl Fn(&T) -> Result<(), ValidationError> + Send + 'static {
+    #[cfg(feature = "profiling")]
+    {
+        let profile = req.app_data::<crate::ValidationProfile>().cloned();
+        let type_name = type_name.to_string();
+        move |data: &T| match &profile {
+            Some(profile) => profile.validate(data, &type_name),
+            None => data.validate(),
+        }
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        let _ = (req, type_name);
+        |data: &T| data.validate()
+    }
+}
+
+/// Like [`check`], for validation that already ran, e.g. on the blocking thread pool.
+pub(crate) fn outcome(
+    result: Result<(), ValidationError>,
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+) -> Result<(), AppError> {
+    let Err(err) = result else {
+        return Ok(());
+    };
+    if config.severities.is_empty() {
+        return Err(validation_error(err, config, req, type_name));
+    }
+
+    let [errors, warnings, info] = severity::split(err, &config.severities);
+    if warnings.is_some() || info.is_some() {
+        let mut extensions = req.extensions_mut();
+        let findings = extensions.get_or_insert_with(Findings::default);
+        let format = |errors| format(errors, config, req);
+        findings
+            .warnings
+            .extend(warnings.map(format).unwrap_or_default());
+        findings.info.extend(info.map(format).unwrap_or_default());
+    }
+    match errors {
+        Some(err) => Err(validation_error(err, config, req, type_name)),
+        None => Ok(()),
+    }
+}
+
+fn validation_error(
+    err: ValidationError,
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+) -> AppError {
+    classify(req, status::classify(&err));
+    req.extensions_mut()
+        .get_or_insert_with(Findings::default)
+        .failures
+        .extend(kind::failures(&err));
+    if let Some(status) = code::status(&err, config, type_name) {
+        escalate(req, status);
+    }
+    let render = config
+        .error_bodies
+        .get(type_name)
+        .or(config.error_body.as_ref());
+    if let Some(render) = render {
+        let body = render(err.clone(), req);
+        req.extensions_mut()
+            .get_or_insert_with(Findings::default)
+            .body = Some(body);
+    }
+    let debug_context = config
+        .debug_errors
+        .then(|| debug::validation_context(type_name, &err));
+    let by_code = config.error_cap.map(|cap| (truncate::by_code(&err), cap));
+    let mut formatted_errors = format(err, config, req);
+    if config.first_error_only {
+        limit_messages(formatted_errors.values_mut(), 1, false);
+    } else if let Some(limit) = config.message_limit {
+        limit_messages(formatted_errors.values_mut(), limit, true);
+    }
+    if config.join_messages {
+        join_messages(formatted_errors.values_mut());
+    }
+    if let Some((by_code, cap)) = by_code {
+        formatted_errors = truncate::truncate(formatted_errors, by_code, cap);
+    }
+    if let Some(metadata) = config.shapes.get(type_name) {
+        shape::fill(&mut formatted_errors, metadata);
+    }
+    if let Some(context) = debug_context {
+        formatted_errors.insert(debug::DEBUG_KEY.to_string(), context);
+    }
+    AppError::ValidationError(formatted_errors)
+}
+
+/// Format validation errors, applying field labels, documentation links and, with the
+/// `i18n` feature, translating the messages.
+#[cfg_attr(not(feature = "i18n"), allow(unused_variables))]
+fn format(err: ValidationError, config: &JsonConfig, req: &HttpRequest) -> ErrorMap {
+    let default = |path: &str, error: &serde_valid::validation::Error| match label::lookup(
+        &config.labels,
+        path,
+    ) {
+        Some(label) => label::relabel(code::message(error), label),
+        None => code::message(error),
+    };
+    let documented = |message: String, error: &serde_valid::validation::Error| {
+        if !config.documents_errors() {
+            return Value::String(message);
+        }
+        let url = config.doc_url(debug::constraint_name(error));
+        json!({"message": message, "type": url.as_deref().unwrap_or("about:blank")})
+    };
+
+    #[cfg(feature = "i18n")]
+    if let Some(translator) = crate::i18n::translator(config, req) {
+        return format_errors_with(
+            err,
+            &|path, error| {
+                let label = label::lookup(&config.labels, path);
+                let message = translator
+                    .render(path, label, error)
+                    .unwrap_or_else(|| default(path, error));
+                documented(message, error)
+            },
+            config.item_limit,
+        );
+    }
+    format_errors_with(
+        err,
+        &|path, error| documented(default(path, error), error),
+        config.item_limit,
+    )
+}
+
+/// Error for a payload that could not be read or decoded, reported under `"error"`.
+pub(crate) fn payload_error(message: String, debug_context: Option<Value>) -> AppError {
+    let mut formatted_errors = ErrorMap::new();
+    formatted_errors.insert("error".to_string(), json!(vec![message]));
+    if let Some(context) = debug_context {
+        formatted_errors.insert(debug::DEBUG_KEY.to_string(), context);
+    }
+    AppError::ValidationError(formatted_errors)
+}
+
+/// Error for a body or parameters that could not be decoded, with `constraint` naming
+/// the failed step in the debug details.
+pub(crate) fn decode_error(
+    config: &JsonConfig,
+    type_name: &str,
+    constraint: &str,
+    message: String,
+) -> AppError {
+    let debug_context = config
+        .debug_errors
+        .then(|| debug::decode_context(type_name, constraint, &message));
+    payload_error(message, debug_context)
+}
+
+/// Error for a JSON body that actix-web's reader, or the crate's own, could not read or
+/// deserialize, recording the failed [`ConstraintKind`]. Oversized bodies are classified
+/// as [`FailureClass::Security`].
+pub(crate) fn json_error(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+    err: JsonPayloadError,
+) -> AppError {
+    let kind = match err {
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            overflow(config, req);
+            ConstraintKind::Limit
+        }
+        JsonPayloadError::ContentType => ConstraintKind::ContentType,
+        JsonPayloadError::Deserialize(_) => ConstraintKind::Deserialization,
+        JsonPayloadError::Payload(PayloadError::Incomplete(_)) => ConstraintKind::Truncated,
+        _ => ConstraintKind::Payload,
+    };
+    let message = match kind {
+        ConstraintKind::Truncated => TRUNCATED.to_string(),
+        _ => err.to_string(),
+    };
+    fail(req, kind);
+    let debug_context = config
+        .debug_errors
+        .then(|| debug::payload_context(type_name, &err));
+    payload_error(message, debug_context)
+}
+
+/// Error for a body that could not be read by [`read_body`]. Oversized bodies are
+/// classified as [`FailureClass::Security`].
+pub(crate) fn body_error(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+    err: PayloadError,
+) -> AppError {
+    let (constraint, message) = match err {
+        PayloadError::Overflow => {
+            overflow(config, req);
+            fail(req, ConstraintKind::Limit);
+            ("limit", err.to_string())
+        }
+        PayloadError::Incomplete(_) => {
+            fail(req, ConstraintKind::Truncated);
+            ("truncated", TRUNCATED.to_string())
+        }
+        _ => ("payload", err.to_string()),
+    };
+    decode_error(config, type_name, constraint, message)
+}
+
+/// Classify a body over [`JsonConfig::limit`] as [`FailureClass::Security`], rejected with
+/// `413 Payload Too Large` when a [`JsonConfig::soft_limit`] is set and no status is
+/// configured for the class.
+fn overflow(config: &JsonConfig, req: &HttpRequest) {
+    classify(req, FailureClass::Security);
+    if config.soft_limit.is_some() && !config.statuses.contains_key(&FailureClass::Security) {
+        escalate(req, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
+
+/// Message of a body that ended early, which says nothing about its JSON.
+pub(crate) const TRUNCATED: &str = "The request body was cut short before it was complete";
+
+/// Whether the request's content type is JSON (`application/json`, `*/*+json`) or
+/// accepted by [`JsonConfig::content_type`]. A missing content type is accepted unless
+/// [`JsonConfig::content_type_required`] is set.
+pub(crate) fn is_json(req: &impl HttpMessage, config: &JsonConfig) -> bool {
+    match req.mime_type() {
+        Ok(Some(mime)) => is_json_type(mime, config),
+        Ok(None) => !config.content_type_required || config.sniff_content_type,
+        Err(_) => false,
+    }
+}
+
+/// Whether the body of `req` has no content type and is sniffed, see
+/// [`JsonConfig::sniff_content_type`].
+pub(crate) fn sniffs(req: &HttpRequest, config: &JsonConfig) -> bool {
+    config.sniff_content_type && !req.headers().contains_key(header::CONTENT_TYPE)
+}
+
+/// Reject a sniffed body that does not start like a JSON document with
+/// `415 Unsupported Media Type`.
+pub(crate) fn check_sniffed(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+    body: &[u8],
+) -> Result<(), AppError> {
+    let start = body.trim_ascii_start();
+    let json = matches!(start.first(), Some(b'{' | b'[' | b'"' | b'-' | b'0'..=b'9'))
+        || [&b"true"[..], b"false", b"null"]
+            .iter()
+            .any(|literal| start.starts_with(literal));
+    if json {
+        return Ok(());
+    }
+    fail(req, ConstraintKind::ContentType);
+    escalate(req, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let message = "Missing content type, and the body is not JSON".to_string();
+    Err(decode_error(config, type_name, "content_type", message))
+}
+
+/// Whether `mime` is JSON: `*/json`, a `*/*+json` type unless
+/// [`JsonConfig::json_suffix`] is off, or one accepted by [`JsonConfig::content_type`].
+pub(crate) fn is_json_type(mime: mime::Mime, config: &JsonConfig) -> bool {
+    mime.subtype() == mime::JSON
+        || (config.json_suffix && mime.suffix() == Some(mime::JSON))
+        || config
+            .content_type
+            .as_ref()
+            .is_some_and(|predicate| predicate(mime))
+}
+
+/// The `charset` of the request's content type when [`JsonConfig::require_utf8`] is set
+/// and it is not UTF-8.
+pub(crate) fn foreign_charset(req: &impl HttpMessage, config: &JsonConfig) -> Option<String> {
+    if !config.require_utf8 {
+        return None;
+    }
+    let charset = req.mime_type().ok()??.get_param(mime::CHARSET)?.to_string();
+    let utf8 = ["utf-8", "utf8"]
+        .iter()
+        .any(|name| charset.eq_ignore_ascii_case(name));
+    (!utf8).then_some(charset)
+}
+
+/// Message of a content type with a `charset` other than UTF-8.
+pub(crate) fn charset_message(charset: &str) -> String {
+    format!("Unsupported charset `{charset}`, JSON must be UTF-8")
+}
+
+/// Reject a body whose content type has a `charset` other than UTF-8 with
+/// `415 Unsupported Media Type`, when [`JsonConfig::require_utf8`] is set.
+pub(crate) fn check_charset(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &str,
+) -> Result<(), AppError> {
+    let Some(charset) = foreign_charset(req, config) else {
+        return Ok(());
+    };
+    fail(req, ConstraintKind::ContentType);
+    escalate(req, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let message = charset_message(&charset);
+    Err(decode_error(config, type_name, "content_type", message))
+}
+
+/// Buffer a JSON body for extractors that parse it themselves: check the content type,
+/// reserve room in the memory budget while reading, enforce the size limit, and apply
+/// the [`JsonConfig::transform_body`] hook.
+pub(crate) async fn json_body(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    payload: Payload,
+    type_name: &str,
+) -> Result<Bytes, AppError> {
+    if !is_json(req, config) {
+        fail(req, ConstraintKind::ContentType);
+        return Err(decode_error(
+            config,
+            type_name,
+            "content_type",
+            "Content type error".to_string(),
+        ));
+    }
+    check_charset(config, req, type_name)?;
+    let _permit = budget::reserve(config, req, type_name).await?;
+    let body = read_body(req, payload, config.limit)
+        .await
+        .map_err(|err| body_error(config, req, type_name, err))?;
+    soft_limit(config, req, body.len());
+    if sniffs(req, config) {
+        check_sniffed(config, req, type_name, &body)?;
+    }
+    let body = match &config.transform {
+        Some(transform) => transform(body, req)?,
+        None => body,
+    };
+    if config.strict_json {
+        strict::check(&body).map_err(|err| {
+            fail(req, ConstraintKind::Deserialization);
+            let message = format!("Json deserialize error: {err}");
+            decode_error(config, type_name, "deserialization", message)
+        })?;
+    }
+    Ok(body)
+}
+
+/// Record the [`ValidationReport`] of an extraction in the request extensions, then pass
+/// any error through the configured error handler.
+pub(crate) fn finish<T>(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &'static str,
+    started: Instant,
+    mut result: Result<T, AppError>,
+) -> Result<T, AppError> {
+    if let Err(AppError::ValidationError(errors)) = &mut result {
+        sanitize_messages(
+            errors.values_mut(),
+            config.message_length,
+            config.message_escape,
+        );
+    }
+    let (escalated, body) = req
+        .extensions_mut()
+        .get_mut::<Findings>()
+        .map(|findings| (findings.status, findings.body.take()))
+        .unwrap_or_default();
+    let class = report(config, req, type_name, started, &result);
+    let status = escalated.or_else(|| class.and_then(|class| config.statuses.get(&class).copied()));
+    result.map_err(|err| {
+        if config.defer_rendering {
+            let status = status.unwrap_or(StatusCode::BAD_REQUEST);
+            return AppError::Handled(ValidationDeferred { status }.into());
+        }
+        if let Some(body) = body {
+            let status = status.unwrap_or(StatusCode::BAD_REQUEST);
+            let response = HttpResponse::build(status).json(body);
+            return AppError::Handled(InternalError::from_response(err, response).into());
+        }
+        let err = match error_format::requested(config, req) {
+            Requested::Format(render) => {
+                let status = status.unwrap_or_else(|| err.status_code());
+                let response = render(&err, status, req, type_name);
+                AppError::Handled(InternalError::from_response(err, response).into())
+            }
+            Requested::Unsupported if config.reject_unsupported_formats => {
+                let response = error_format::not_acceptable(config);
+                AppError::Handled(InternalError::from_response(err, response).into())
+            }
+            _ => match (&config.ehandler, status) {
+                (None, Some(status)) => with_status(err, status),
+                _ => handle_error(config, req, type_name, err),
+            },
+        };
+        match config.compress_errors {
+            Some(min_size) => compression_hint(err, min_size),
+            None => err,
+        }
+    })
+}
+
+/// Mark error responses smaller than `min_size` bytes with `Content-Encoding: identity`,
+/// which the `Compress` middleware leaves uncompressed.
+fn compression_hint(err: AppError, min_size: usize) -> AppError {
+    let mut response = err.error_response();
+    if let BodySize::Sized(size) = response.body().size() {
+        if size < min_size as u64 {
+            response.headers_mut().insert(
+                header::CONTENT_ENCODING,
+                header::HeaderValue::from_static("identity"),
+            );
+        }
+    }
+    AppError::Handled(InternalError::from_response(err, response).into())
+}
+
+/// Record the [`ValidationReport`] and [`ExtractionOutcome`] of an extraction in the
+/// request extensions, returning the class of the failure, if any.
+pub(crate) fn report<T>(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &'static str,
+    started: Instant,
+    result: &Result<T, AppError>,
+) -> Option<FailureClass> {
+    let errors = match result {
+        Err(AppError::ValidationError(errors)) => errors.clone(),
+        _ => ErrorMap::new(),
+    };
+    let findings = req
+        .extensions_mut()
+        .remove::<Findings>()
+        .unwrap_or_default();
+    let class = result
+        .is_err()
+        .then(|| findings.class.unwrap_or(FailureClass::Payload));
+    let mut failures = findings.failures;
+    if result.is_err() && failures.is_empty() {
+        failures.push(Failure {
+            path: String::new(),
+            kind: ConstraintKind::Payload,
+        });
+    }
+    // Bodies parsed by `JsonBody` are not measured.
+    let size = findings.size.or_else(|| content_length(req));
+    let report = ValidationReport {
+        type_name,
+        errors,
+        warnings: findings.warnings,
+        info: findings.info,
+        class,
+        oversized: findings.oversized,
+        failures,
+        elapsed: started.elapsed(),
+    };
+    stats::record(config, req, &report);
+    let outcome = ExtractionOutcome::of(&report, size);
+    let mut extensions = req.extensions_mut();
+    extensions.insert(report);
+    extensions.insert(outcome);
+    class
+}
+
+/// Respond to a validation error with `status` instead of `400 Bad Request`.
+pub(crate) fn with_status(err: AppError, status: StatusCode) -> AppError {
+    match &err {
+        AppError::ValidationError(errors) => {
+            let response = HttpResponse::build(status).json(errors);
+            AppError::Handled(InternalError::from_response(err, response).into())
+        }
+        AppError::Handled(_) => err,
+    }
+}
+
+/// Pass an extraction error through the configured error handler, if any.
+fn handle_error(
+    config: &JsonConfig,
+    req: &HttpRequest,
+    type_name: &'static str,
+    err: AppError,
+) -> AppError {
+    match &config.ehandler {
+        Some(handler) => AppError::Handled(handler(err, req, type_name)),
+        None => err,
+    }
+}
+
+/// The length announced by the `Content-Length` header of `req`, if it is valid. Takes
+/// requests before they reach extractors too, as seen by middleware.
+pub(crate) fn content_length(req: &impl HttpMessage) -> Option<usize> {
+    req.headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.parse().ok())
+}
+
+/// Buffer the request body, failing with [`PayloadError::Overflow`] once `limit` bytes
+/// are exceeded (or up front when `Content-Length` announces a larger body).
+pub(crate) async fn read_body(
+    req: &HttpRequest,
+    mut payload: Payload,
+    limit: usize,
+) -> Result<Bytes, PayloadError> {
+    let length = content_length(req);
+    if length.is_some_and(|length| length > limit) {
+        return Err(PayloadError::Overflow);
+    }
+
+    let mut body = BytesMut::with_capacity(length.unwrap_or(8192).min(limit));
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > limit {
+            return Err(PayloadError::Overflow);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    // The length of an encoded body says nothing about the decoded one.
+    let encoded = req
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding != "identity");
+    if !encoded && length.is_some_and(|length| body.len() < length) {
+        return Err(PayloadError::Incomplete(None));
+    }
+    Ok(body.freeze())
+}
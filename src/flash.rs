@@ -0,0 +1,132 @@
+use actix_session::{Session, SessionExt};
+use actix_web::{http::header, HttpRequest, HttpResponse, ResponseError};
+
+use crate::{AppError, ErrorMap};
+
+/// The session key the error map is flashed under.
+const FLASH_KEY: &str = "_validation_errors";
+
+/// Error response for server-rendered forms: store the error map in the session and
+/// redirect to `location` with `303 See Other`, where the form is rendered again with
+/// the errors of [`take_flash_errors`]. Requires the `session` feature and a
+/// `SessionMiddleware` from `actix-session`.
+///
+/// ```
+/// use actix_json_validator::{flash_errors, take_flash_errors, AppJson, JsonConfig};
+/// use actix_session::Session;
+/// use actix_web::{web, App, HttpResponse};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Signup { #[validate(min_length = 3)] name: String }
+///
+/// async fn form(session: Session) -> HttpResponse {
+///     // Render the form with the errors of the last submission, if any.
+///     let errors = take_flash_errors(&session).unwrap_or_default();
+///     HttpResponse::Ok().body(format!("{errors:?}"))
+/// }
+///
+/// async fn submit(signup: AppJson<Signup>) -> HttpResponse {
+///     HttpResponse::SeeOther()
+///         .insert_header(("Location", "/welcome"))
+///         .finish()
+/// }
+///
+/// let app = App::new().service(
+///     web::resource("/signup")
+///         .app_data(JsonConfig::default().response_handler(flash_errors("/signup")))
+///         .get(form)
+///         .post(submit),
+/// );
+/// ```
+///
+/// Errors that are not validation errors, and error maps the session fails to store,
+/// get their usual response.
+pub fn flash_errors(
+    location: impl Into<String>,
+) -> impl Fn(&AppError, &HttpRequest, &'static str) -> HttpResponse + Send + Sync + 'static {
+    let location = location.into();
+    move |err, req, _| {
+        let AppError::ValidationError(errors) = err else {
+            return err.error_response();
+        };
+        match req.get_session().insert(FLASH_KEY, errors) {
+            Ok(()) => HttpResponse::SeeOther()
+                .insert_header((header::LOCATION, location.as_str()))
+                .finish(),
+            Err(_) => err.error_response(),
+        }
+    }
+}
+
+/// The error map flashed by [`flash_errors`], removed from the session so it is only
+/// shown once.
+pub fn take_flash_errors(session: &Session) -> Option<ErrorMap> {
+    session.remove_as(FLASH_KEY)?.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, JsonConfig};
+    use actix_session::{storage::CookieSessionStore, SessionMiddleware};
+    use actix_web::{cookie::Key, http::StatusCode, test, web, App};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        name: String,
+    }
+
+    #[actix_web::test]
+    async fn test_errors_are_flashed_once() {
+        let app =
+            test::init_service(
+                App::new()
+                    .wrap(SessionMiddleware::new(
+                        CookieSessionStore::default(),
+                        Key::generate(),
+                    ))
+                    .service(
+                        web::resource("/signup")
+                            .app_data(
+                                JsonConfig::default().response_handler(flash_errors("/signup")),
+                            )
+                            .get(|session: Session| async move {
+                                web::Json(take_flash_errors(&session))
+                            })
+                            .post(|signup: AppJson<Signup>| async move { signup.name.clone() }),
+                    ),
+            )
+            .await;
+
+        let req = test::TestRequest::post()
+            .uri("/signup")
+            .set_json(json!({"name": "Al"}))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get(header::LOCATION).unwrap(), "/signup");
+        let cookie = res.response().cookies().next().unwrap().into_owned();
+
+        let req = test::TestRequest::get()
+            .uri("/signup")
+            .cookie(cookie)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let cookie = res.response().cookies().next().unwrap().into_owned();
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(
+            body,
+            json!({"name": ["The length of the value must be `>= 3`."]})
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/signup")
+            .cookie(cookie)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body, json!(null));
+    }
+}
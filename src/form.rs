@@ -0,0 +1,166 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{
+    dev::{Decompress, Payload},
+    FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ConstraintKind, JsonConfig, QueryConfig};
+
+/// Extract a validated `application/x-www-form-urlencoded` body, as posted by classic
+/// HTML forms, with errors in the same shape as [`AppJson`](crate::AppJson):
+///
+/// ```
+/// use actix_json_validator::AppForm;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Login { #[validate(min_length = 3)] username: String }
+///
+/// async fn login(form: AppForm<Login>) -> String {
+///     format!("Welcome {}", form.username)
+/// }
+/// ```
+///
+/// The limit, error handler and other settings come from [`JsonConfig`]; list fields
+/// are parsed as configured on [`QueryConfig`], which encodes lists alike. A body
+/// without a content type is accepted unless
+/// [`content_type_required`](JsonConfig::content_type_required) is set.
+#[derive(Debug)]
+pub struct AppForm<T>(pub T);
+
+impl<T> AppForm<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppForm<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppForm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppForm<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let query_config = req.app_data::<QueryConfig>().cloned().unwrap_or_default();
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                if !is_form(&req, &config) {
+                    extract::fail(&req, ConstraintKind::ContentType);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "content_type",
+                        "Content type error".to_string(),
+                    ));
+                }
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let payload = Decompress::from_headers(payload, req.headers());
+                let body =
+                    extract::read_body(&req, Payload::from(payload.boxed_local()), config.limit)
+                        .await
+                        .map_err(|err| extract::body_error(&config, &req, type_name, err))?;
+                extract::soft_limit(&config, &req, body.len());
+
+                let data = std::str::from_utf8(&body)
+                    .map_err(|err| err.to_string())
+                    .and_then(|body| query_config.deserialize::<T>(body))
+                    .map_err(|err| {
+                        extract::fail(&req, ConstraintKind::Deserialization);
+                        let message = format!("Form deserialize error: {err}");
+                        extract::decode_error(&config, type_name, "deserialization", message)
+                    })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppForm)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+fn is_form(req: &HttpRequest, config: &JsonConfig) -> bool {
+    match req.mime_type() {
+        Ok(Some(mime)) => mime == mime::APPLICATION_WWW_FORM_URLENCODED,
+        Ok(None) => !config.content_type_required,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ListFormat;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        username: String,
+        #[validate(min_items = 1)]
+        topics: Vec<String>,
+    }
+
+    async fn extract(content_type: &str, body: &str) -> Result<Signup, Value> {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(QueryConfig::default().list_field("topics", ListFormat::Repeated))
+            .insert_header(("content-type", content_type))
+            .set_payload(body.to_string())
+            .to_http_parts();
+        AppForm::<Signup>::from_request(&req, &mut payload)
+            .await
+            .map(AppForm::into_inner)
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_form_bodies() {
+        let form = "application/x-www-form-urlencoded";
+        let signup = extract(form, "username=ada+l&topics=rust&topics=math")
+            .await
+            .unwrap();
+        assert_eq!(signup.username, "ada l");
+        assert_eq!(signup.topics, ["rust", "math"]);
+
+        assert_eq!(
+            extract(form, "username=al&topics=rust").await.unwrap_err(),
+            json!({"username": ["The length of the value must be `>= 3`."]})
+        );
+        assert_eq!(
+            extract("application/json", r#"{"username": "ada"}"#)
+                .await
+                .unwrap_err(),
+            json!({"error": ["Content type error"]})
+        );
+    }
+}
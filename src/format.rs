@@ -0,0 +1,517 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use serde_json::{json, Map, Value};
+use serde_valid::validation::{
+    ArrayErrors, Error, Errors as ValidationError, ItemErrorsMap, ObjectErrors,
+};
+
+use crate::{code, debug::join, ErrorMap, MessageEscape};
+
+/// Renders a single constraint failure, usually as a message string, given the dotted
+/// path of the failing value (e.g. `profile.tags.1`).
+pub(crate) type Render<'a> = &'a dyn Fn(&str, &Error) -> Value;
+
+pub(crate) fn format_errors(errors: ValidationError) -> ErrorMap {
+    format_errors_with(
+        errors,
+        &|_, error| Value::String(code::message(error)),
+        None,
+    )
+}
+
+/// Like [`format_errors`], rendering each message with `render` (e.g. to translate it).
+/// Arrays with more than `item_limit` failing items report each distinct failure once,
+/// under `*`, with the number of items.
+pub(crate) fn format_errors_with(
+    errors: ValidationError,
+    render: Render,
+    item_limit: Option<usize>,
+) -> ErrorMap {
+    let mut result = Map::new();
+    let mut formatter = Formatter {
+        render,
+        item_limit,
+        path: String::new(),
+    };
+    formatter.errors(&mut result, None, errors);
+    result.into_iter().collect()
+}
+
+const NON_FIELD_ERRORS: &str = "non_field_errors";
+
+/// Writes an error tree straight into the maps of the response body, each one created
+/// once, in place, with a single buffer for the dotted path of the value at hand.
+struct Formatter<'a> {
+    render: Render<'a>,
+    item_limit: Option<usize>,
+    path: String,
+}
+
+impl Formatter<'_> {
+    /// Write `errors` into `out`: their messages under `key`, or `non_field_errors`
+    /// without one, and the errors of their items and properties next to them.
+    fn errors(
+        &mut self,
+        out: &mut Map<String, Value>,
+        key: Option<String>,
+        errors: ValidationError,
+    ) {
+        match errors {
+            ValidationError::Array(array) => self.array(out, key, array),
+            ValidationError::Object(object) => {
+                if !object.errors.is_empty() {
+                    let messages = self.messages(&object.errors);
+                    out.insert(key.clone().unwrap_or_else(non_field_errors), messages);
+                }
+                if object.properties.is_empty() {
+                    return;
+                }
+                // Properties go in the object under the parent key, replacing its
+                // messages if any, or next to the messages at the top.
+                let target = match key {
+                    Some(parent) => {
+                        let slot = out.entry(parent).or_insert_with(|| json!({}));
+                        if !slot.is_object() {
+                            *slot = json!({});
+                        }
+                        slot.as_object_mut().expect("just made an object")
+                    }
+                    None => out,
+                };
+                for (property, error) in object.properties {
+                    let parent = self.enter(&property);
+                    let mut child = Map::new();
+                    self.errors(&mut child, None, error);
+                    self.path.truncate(parent);
+                    // Plain messages of a property are its value, without a
+                    // `non_field_errors` wrapper.
+                    let value = if child.len() == 1 && child.contains_key(NON_FIELD_ERRORS) {
+                        child.remove(NON_FIELD_ERRORS).unwrap_or_default()
+                    } else {
+                        Value::Object(child)
+                    };
+                    target.insert(property, value);
+                }
+            }
+            ValidationError::NewType(errors) => {
+                let (messages, items) = self.split_item_errors(errors);
+                let Some(items) = items else {
+                    if !messages.is_empty() {
+                        out.insert(key.unwrap_or_else(non_field_errors), Value::Array(messages));
+                    }
+                    return;
+                };
+                // Field constraints applied to every element of a collection report
+                // their failures per index, e.g. `"tags": {"1": [...]}`.
+                let target = match key {
+                    Some(key) => {
+                        let slot = out.entry(key).or_insert_with(|| json!({}));
+                        *slot = json!({});
+                        slot.as_object_mut().expect("just made an object")
+                    }
+                    None => out,
+                };
+                if !messages.is_empty() {
+                    target.insert(NON_FIELD_ERRORS.to_string(), Value::Array(messages));
+                }
+                self.array(target, None, items);
+            }
+        }
+    }
+
+    /// Write the errors of an array into `out`: its messages under `key`, and those of
+    /// its items by index, or aggregated under `*` past the item limit.
+    fn array(&mut self, out: &mut Map<String, Value>, key: Option<String>, array: ArrayErrors) {
+        let key = match key {
+            // An array within an array: its items go in an object under its own index,
+            // next to its messages, like those of a field.
+            Some(key) if !array.items.is_empty() => {
+                let mut nested = Map::new();
+                self.array(&mut nested, None, array);
+                out.insert(key, Value::Object(nested));
+                return;
+            }
+            key => key,
+        };
+        if !array.errors.is_empty() {
+            let messages = self.messages(&array.errors);
+            out.insert(key.unwrap_or_else(non_field_errors), messages);
+        }
+        if array.items.is_empty() {
+            return;
+        }
+        if self
+            .item_limit
+            .is_none_or(|limit| array.items.len() <= limit)
+        {
+            return self.items(out, array.items);
+        }
+        // Each item is counted as soon as it is formatted, so only the distinct
+        // failures are kept however many items share them.
+        let mut aggregate = Aggregate::default();
+        for (index, error) in array.items {
+            let index = index.to_string();
+            let parent = self.enter(&index);
+            let mut item = Map::new();
+            self.errors(&mut item, Some(index), error);
+            self.path.truncate(parent);
+            for (_, value) in item {
+                aggregate.tally(&mut Vec::new(), value);
+            }
+        }
+        out.insert("*".to_string(), aggregate.finish());
+    }
+
+    fn items(&mut self, out: &mut Map<String, Value>, items: ItemErrorsMap<Error>) {
+        for (index, error) in items {
+            let index = index.to_string();
+            let parent = self.enter(&index);
+            self.errors(out, Some(index), error);
+            self.path.truncate(parent);
+        }
+    }
+
+    /// Append `segment` to the path, returning the length to truncate it back to.
+    fn enter(&mut self, segment: &str) -> usize {
+        let parent = self.path.len();
+        if parent > 0 {
+            self.path.push('.');
+        }
+        self.path.push_str(segment);
+        parent
+    }
+
+    fn messages(&self, errors: &[Error]) -> Value {
+        Value::Array(
+            errors
+                .iter()
+                .map(|error| (self.render)(&self.path, error))
+                .collect(),
+        )
+    }
+
+    /// Separate per-element errors (`Error::Items`) from the plain messages of a field.
+    fn split_item_errors(&self, errors: Vec<Error>) -> (Vec<Value>, Option<ArrayErrors>) {
+        let mut messages = Vec::new();
+        let mut items: Option<ArrayErrors> = None;
+        for error in errors {
+            match error {
+                Error::Items(array_errors) => {
+                    items = Some(match items {
+                        Some(existing) => existing.merge(array_errors),
+                        None => array_errors,
+                    });
+                }
+                error => messages.push((self.render)(&self.path, &error)),
+            }
+        }
+        (messages, items)
+    }
+}
+
+/// Keep at most `limit` messages per field, collapsing the rest into a last
+/// `and N more issues` message when `summarize` is set.
+pub(crate) fn limit_messages<'a>(
+    errors: impl Iterator<Item = &'a mut Value>,
+    limit: usize,
+    summarize: bool,
+) {
+    for value in errors {
+        match value {
+            Value::Array(messages) if messages.len() > limit => {
+                let more = messages.len() - limit;
+                messages.truncate(limit);
+                if summarize {
+                    let issues = if more == 1 { "issue" } else { "issues" };
+                    messages.push(Value::String(format!(
+                        "and {} more {issues}",
+                        thousands(more)
+                    )));
+                }
+            }
+            Value::Object(nested) => limit_messages(nested.values_mut(), limit, summarize),
+            _ => {}
+        }
+    }
+}
+
+/// Join the messages of each field into one sentence, e.g. `Too short.` and `Must only
+/// contain letters.` into `Too short and must only contain letters.`. Fields with
+/// messages that are not plain strings, such as documented ones, are left alone.
+pub(crate) fn join_messages<'a>(errors: impl Iterator<Item = &'a mut Value>) {
+    for value in errors {
+        match value {
+            Value::Array(messages) if messages.len() > 1 => {
+                let parts: Option<Vec<&str>> = messages.iter().map(Value::as_str).collect();
+                if let Some(parts) = parts {
+                    *messages = vec![Value::String(sentence(&parts))];
+                }
+            }
+            Value::Object(nested) => join_messages(nested.values_mut()),
+            _ => {}
+        }
+    }
+}
+
+fn sentence(parts: &[&str]) -> String {
+    let mut joined = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        let part = part.trim().trim_end_matches('.');
+        if index > 0 {
+            joined.push_str(if index + 1 == parts.len() {
+                " and "
+            } else {
+                ", "
+            });
+            joined.push_str(&lowercase_first(part));
+        } else {
+            joined.push_str(part);
+        }
+    }
+    joined.push('.');
+    joined
+}
+
+/// Lowercase the first letter of a message, unless it starts an acronym (`URL`, ...).
+fn lowercase_first(message: &str) -> String {
+    let mut chars = message.chars();
+    match (chars.next(), chars.next()) {
+        (Some(first), second) if !second.is_some_and(char::is_uppercase) => first
+            .to_lowercase()
+            .chain(message[first.len_utf8()..].chars())
+            .collect(),
+        _ => message.to_string(),
+    }
+}
+
+/// Strip control characters from the messages of an error body, line breaks and tabs
+/// becoming spaces, cut those longer than `max_length` characters with an ellipsis, then
+/// apply `escape`.
+pub(crate) fn sanitize_messages<'a>(
+    errors: impl Iterator<Item = &'a mut Value>,
+    max_length: Option<usize>,
+    escape: Option<MessageEscape>,
+) {
+    for value in errors {
+        match value {
+            Value::Array(messages) => {
+                for message in messages {
+                    match message {
+                        Value::String(text) => sanitize(text, max_length, escape),
+                        // Documented messages, and failures in debug details.
+                        Value::Object(fields) => {
+                            if let Some(Value::String(text)) = fields.get_mut("message") {
+                                sanitize(text, max_length, escape);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Value::Object(nested) => sanitize_messages(nested.values_mut(), max_length, escape),
+            _ => {}
+        }
+    }
+}
+
+fn sanitize(text: &mut String, max_length: Option<usize>, escape: Option<MessageEscape>) {
+    if text.contains(char::is_control) {
+        *text = text
+            .chars()
+            .filter_map(|c| match c {
+                '\n' | '\r' | '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+    }
+    if let Some(max_length) = max_length {
+        if text.char_indices().nth(max_length).is_some() {
+            let end = text
+                .char_indices()
+                .nth(max_length.saturating_sub(1))
+                .map_or(0, |(index, _)| index);
+            text.truncate(end);
+            text.push('…');
+        }
+    }
+    if let Some(escape) = escape {
+        escape.apply(text);
+    }
+}
+
+/// Visit each failed constraint of an error tree along with the dotted path of the
+/// failing value below `path`. Elements of collections have their index or key appended
+/// (`tags.1`), whether they failed a constraint of their own or one declared on the
+/// collection for each of its elements; `Error::Items` and `Error::Properties` are never
+/// visited themselves.
+pub(crate) fn walk<'a, F>(errors: &'a ValidationError, path: &str, visit: &mut F)
+where
+    F: FnMut(&str, &'a Error),
+{
+    match errors {
+        ValidationError::Array(array_errors) => walk_array(array_errors, path, visit),
+        ValidationError::Object(object_errors) => walk_object(object_errors, path, visit),
+        ValidationError::NewType(vec_errors) => walk_all(vec_errors, path, visit),
+    }
+}
+
+fn walk_array<'a, F>(errors: &'a ArrayErrors, path: &str, visit: &mut F)
+where
+    F: FnMut(&str, &'a Error),
+{
+    walk_all(&errors.errors, path, visit);
+    for (index, errors) in &errors.items {
+        walk(errors, &join(path, &index.to_string()), visit);
+    }
+}
+
+fn walk_object<'a, F>(errors: &'a ObjectErrors, path: &str, visit: &mut F)
+where
+    F: FnMut(&str, &'a Error),
+{
+    walk_all(&errors.errors, path, visit);
+    for (property, errors) in &errors.properties {
+        walk(errors, &join(path, property), visit);
+    }
+}
+
+fn walk_all<'a, F>(errors: &'a [Error], path: &str, visit: &mut F)
+where
+    F: FnMut(&str, &'a Error),
+{
+    for error in errors {
+        match error {
+            Error::Items(array_errors) => walk_array(array_errors, path, visit),
+            Error::Properties(object_errors) => walk_object(object_errors, path, visit),
+            error => visit(path, error),
+        }
+    }
+}
+
+/// Add `message` to the messages at the dotted `path`, creating nested objects on the
+/// way.
+pub(crate) fn insert(errors: &mut ErrorMap, path: &str, message: String) {
+    let mut segments = path.split('.');
+    let first = segments.next().unwrap_or(path);
+    let mut slot = errors.entry(first.to_string()).or_insert_with(|| json!([]));
+    for segment in segments {
+        if !slot.is_object() {
+            *slot = json!({});
+        }
+        slot = slot
+            .as_object_mut()
+            .expect("just made an object")
+            .entry(segment)
+            .or_insert_with(|| json!([]));
+    }
+    if let Value::Array(messages) = slot {
+        messages.push(Value::String(message));
+    }
+}
+
+pub(crate) fn non_field_errors() -> String {
+    NON_FIELD_ERRORS.to_string()
+}
+
+/// The errors of many array items merged into one value of the same shape, keeping each
+/// distinct message once, prefixed with the number of items that failed with it.
+#[derive(Default)]
+struct Aggregate {
+    /// Each distinct message, with its path and item count, in the order first seen.
+    counts: Vec<(Vec<String>, Value, usize)>,
+    /// Position in `counts` of each message, by path and message text.
+    index: HashMap<Vec<String>, HashMap<String, usize>>,
+}
+
+impl Aggregate {
+    fn tally(&mut self, path: &mut Vec<String>, value: Value) {
+        match value {
+            Value::Array(messages) => {
+                if path.is_empty() {
+                    path.push(String::new());
+                }
+                let index = match self.index.get_mut(path.as_slice()) {
+                    Some(index) => index,
+                    None => self.index.entry(path.clone()).or_default(),
+                };
+                for message in messages {
+                    let text = match &message {
+                        Value::String(text) => Cow::Borrowed(text.as_str()),
+                        message => Cow::Owned(message.to_string()),
+                    };
+                    match index.get(text.as_ref()) {
+                        Some(&position) => self.counts[position].2 += 1,
+                        None => {
+                            index.insert(text.into_owned(), self.counts.len());
+                            self.counts.push((path.clone(), message, 1));
+                        }
+                    }
+                }
+            }
+            Value::Object(object) => {
+                for (key, value) in object {
+                    path.push(key);
+                    self.tally(path, value);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> Value {
+        let mut result = Value::Object(serde_json::Map::new());
+        for (path, message, count) in self.counts {
+            let mut slot = &mut result;
+            for segment in path {
+                slot = slot
+                    .as_object_mut()
+                    .map(|object| object.entry(segment).or_insert_with(|| json!({})))
+                    .expect("aggregated paths lead through objects");
+            }
+            if !slot.is_array() {
+                *slot = json!([]);
+            }
+            if let Value::Array(messages) = slot {
+                messages.push(counted(message, count));
+            }
+        }
+        match result {
+            // Scalar items only have messages, kept directly under `*`.
+            Value::Object(mut object) if object.len() == 1 && object.contains_key("") => {
+                object.remove("").unwrap_or_default()
+            }
+            result => result,
+        }
+    }
+}
+
+/// Prefix a rendered message, plain or with a documentation link, with its item count.
+fn counted(message: Value, count: usize) -> Value {
+    let items = if count == 1 { "item" } else { "items" };
+    let prefix = format!("{} {items}: ", thousands(count));
+    match message {
+        Value::String(message) => Value::String(prefix + &message),
+        Value::Object(mut object) => {
+            if let Some(Value::String(message)) = object.get_mut("message") {
+                message.insert_str(0, &prefix);
+            }
+            Value::Object(object)
+        }
+        message => message,
+    }
+}
+
+/// `1204` as `1,204`.
+fn thousands(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
@@ -0,0 +1,132 @@
+use std::ops::Deref;
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_valid::{
+    validation::{Errors as ValidationError, ObjectErrors},
+    Validate,
+};
+
+use crate::{AppError, AppJson};
+
+/// The standard GraphQL-over-HTTP request envelope.
+///
+/// Use `Option<V>` as the variables type for operations where `variables` may be omitted.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest<V> {
+    pub query: String,
+    #[serde(default)]
+    pub operation_name: Option<String>,
+    pub variables: V,
+}
+
+impl<V: Validate> Validate for GraphQLRequest<V> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.variables.validate().map_err(|errors| {
+            ValidationError::Object(ObjectErrors::new(
+                vec![],
+                IndexMap::from([("variables".to_string(), errors)]),
+            ))
+        })
+    }
+}
+
+/// Extracts a GraphQL request envelope (`{query, operationName, variables}`) from a JSON
+/// body and validates `variables` as `V`.
+///
+/// Variable errors are reported under the `"variables"` key, e.g.
+/// `{"variables": {"first": ["The number must be `<= 100`."]}}`. The extractor shares
+/// [`JsonConfig`](crate::JsonConfig) with [`AppJson`].
+#[derive(Debug)]
+pub struct AppGraphQLRequest<V>(pub GraphQLRequest<V>);
+
+impl<V> AppGraphQLRequest<V> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> GraphQLRequest<V> {
+        self.0
+    }
+}
+
+impl<V> Deref for AppGraphQLRequest<V> {
+    type Target = GraphQLRequest<V>;
+
+    fn deref(&self) -> &GraphQLRequest<V> {
+        &self.0
+    }
+}
+
+impl<V> FromRequest for AppGraphQLRequest<V>
+where
+    V: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        AppJson::<GraphQLRequest<V>>::from_request(req, payload)
+            .map(|res| res.map(|json| AppGraphQLRequest(json.into_inner())))
+            .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, http::StatusCode, test, ResponseError};
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct ListFoods {
+        #[validate(maximum = 100)]
+        first: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_valid_graphql_request() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_payload(
+                json!({
+                    "query": "query ListFoods($first: Int!) { foods(first: $first) { name } }",
+                    "operationName": "ListFoods",
+                    "variables": {"first": 10}
+                })
+                .to_string(),
+            )
+            .to_http_parts();
+
+        let request = AppGraphQLRequest::<ListFoods>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(request.operation_name.as_deref(), Some("ListFoods"));
+        assert_eq!(request.variables.first, 10);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_graphql_variables() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_payload(
+                json!({
+                    "query": "query ListFoods($first: Int!) { foods(first: $first) { name } }",
+                    "variables": {"first": 1000}
+                })
+                .to_string(),
+            )
+            .to_http_parts();
+
+        let res = AppGraphQLRequest::<ListFoods>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            body,
+            json!({"variables": {"first": ["The number must be `<= 100`."]}}).to_string()
+        );
+    }
+}
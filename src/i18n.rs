@@ -0,0 +1,524 @@
+//! Translation of validation messages with [Fluent](https://projectfluent.org/), enabled
+//! by the `i18n` feature.
+
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use actix_web::{
+    http::header::{AcceptLanguage, Header, Preference},
+    HttpRequest,
+};
+use fluent::{
+    concurrent::FluentBundle, types::FluentNumber, FluentArgs, FluentResource, FluentValue,
+};
+use serde_valid::validation::Error;
+use unic_langid::LanguageIdentifier;
+
+use crate::{code, debug::constraint_name, template, JsonConfig};
+
+type Bundle = FluentBundle<FluentResource>;
+
+/// Translated validation messages, registered with [`JsonConfig::messages`].
+///
+/// Each locale is a Fluent resource with one message per constraint, named as in the
+/// `serde_valid` attribute (`min_length`, `maximum`, `pattern`, ...). Messages can use
+/// these variables:
+///
+/// - `$limit`: the bound of numeric, length, item and property count constraints, as a
+///   number, so it can select plural forms following the locale's CLDR rules,
+/// - `$values`: the accepted values of `enumerate`,
+/// - `$pattern`: the regular expression of `pattern`,
+/// - `$field`: the dotted path of the failing value, such as `profile.nickname`,
+/// - `$label`: its label from [`JsonConfig::label`], or the path when there is none,
+/// - `$message`: the original (English) message.
+///
+/// ```
+/// use actix_json_validator::Messages;
+///
+/// let messages = Messages::new()
+///     .add("fr", "min_length = La longueur doit être d'au moins { $limit }.")
+///     .unwrap()
+///     .add(
+///         "pl",
+///         r#"
+/// min_items = Lista musi zawierać co najmniej { $limit } { $limit ->
+///     [one] element
+///     [few] elementy
+///    *[many] elementów
+/// }.
+/// "#,
+///     )
+///     .unwrap();
+/// ```
+///
+/// Fluent's built-in functions such as `NUMBER()` are available.
+///
+/// Constraints without a translation, and messages customized with `message = "..."`
+/// on the attribute, are reported as they are.
+#[derive(Default)]
+pub struct Messages {
+    bundles: Vec<Bundle>,
+}
+
+impl Messages {
+    /// Create an empty set of translations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the translations for `locale` (e.g. `"pl"`, `"pt-BR"`) from Fluent (`.ftl`)
+    /// source. Fails if the locale or the source cannot be parsed.
+    pub fn add(mut self, locale: &str, source: impl Into<String>) -> Result<Self, String> {
+        let locale: LanguageIdentifier = locale
+            .parse()
+            .map_err(|err| format!("invalid locale `{locale}`: {err}"))?;
+        let resource = FluentResource::try_new(source.into())
+            .map_err(|(_, errors)| format!("invalid Fluent source for `{locale}`: {errors:?}"))?;
+
+        let mut bundle = Bundle::new_concurrent(vec![locale]);
+        // Unicode isolation marks only get in the way of JSON clients.
+        bundle.set_use_isolating(false);
+        bundle
+            .add_builtins()
+            .map_err(|err| format!("cannot register Fluent functions: {err}"))?;
+        bundle
+            .add_resource(resource)
+            .map_err(|errors| format!("conflicting Fluent messages: {errors:?}"))?;
+        self.bundles.push(bundle);
+        Ok(self)
+    }
+
+    /// Load the translations of every `<locale>.ftl` file in `dir`, e.g. `fr.ftl` and
+    /// `pt-BR.ftl`. Fails if the directory or a file cannot be read or parsed.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        let entries =
+            fs::read_dir(dir).map_err(|err| format!("cannot read `{}`: {err}", dir.display()))?;
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| format!("cannot read `{}`: {err}", dir.display()))?
+                .path();
+            if path.extension().is_some_and(|extension| extension == "ftl") {
+                files.push(path);
+            }
+        }
+        // Locales are negotiated in the order they were added.
+        files.sort();
+
+        files
+            .into_iter()
+            .try_fold(Messages::new(), |messages, path| {
+                let locale = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or("");
+                let source = fs::read_to_string(&path)
+                    .map_err(|err| format!("cannot read `{}`: {err}", path.display()))?;
+                messages.add(locale, source)
+            })
+    }
+
+    /// Pick the bundle for the first requested locale that is available, matching on the
+    /// language alone (`pt-BR` → `pt`) when there is no exact match.
+    fn negotiate(&self, requested: &[LanguageIdentifier]) -> Option<usize> {
+        requested.iter().find_map(|wanted| {
+            let exact = self
+                .bundles
+                .iter()
+                .position(|bundle| bundle.locales.first() == Some(wanted));
+            exact.or_else(|| {
+                self.bundles.iter().position(|bundle| {
+                    bundle
+                        .locales
+                        .first()
+                        .is_some_and(|locale| locale.language == wanted.language)
+                })
+            })
+        })
+    }
+}
+
+type Loader = dyn Fn() -> Result<Messages, String> + Send + Sync;
+
+/// A handle on translations that can be replaced while the app runs, so fixing a
+/// translation does not take a deployment. Registered with
+/// [`JsonConfig::message_catalog`]; clones share the translations.
+///
+/// ```no_run
+/// use actix_json_validator::{JsonConfig, MessageCatalog};
+/// use actix_web::{web, App, HttpResponse};
+///
+/// let catalog = MessageCatalog::from_dir("locales").unwrap();
+/// let config = JsonConfig::default().message_catalog(catalog.clone());
+///
+/// let app = App::new().app_data(config).route(
+///     "/admin/reload-translations",
+///     web::post().to(move || {
+///         let catalog = catalog.clone();
+///         async move {
+///             match catalog.reload() {
+///                 Ok(()) => HttpResponse::NoContent().finish(),
+///                 Err(err) => HttpResponse::UnprocessableEntity().body(err),
+///             }
+///         }
+///     }),
+/// );
+/// ```
+///
+/// Requests being rendered keep the translations they started with.
+#[derive(Clone)]
+pub struct MessageCatalog {
+    current: Arc<RwLock<Arc<Messages>>>,
+    loader: Option<Arc<Loader>>,
+}
+
+impl MessageCatalog {
+    /// A catalog of `messages`, replaced with [`replace`](Self::replace).
+    pub fn new(messages: Messages) -> Self {
+        MessageCatalog {
+            current: Arc::new(RwLock::new(Arc::new(messages))),
+            loader: None,
+        }
+    }
+
+    /// A catalog of the translations `load` returns, now and on every
+    /// [`reload`](Self::reload). Fails if the first load does.
+    pub fn from_loader<F>(load: F) -> Result<Self, String>
+    where
+        F: Fn() -> Result<Messages, String> + Send + Sync + 'static,
+    {
+        let mut catalog = MessageCatalog::new(load()?);
+        catalog.loader = Some(Arc::new(load));
+        Ok(catalog)
+    }
+
+    /// A catalog of the `.ftl` files in `dir`, read again on every
+    /// [`reload`](Self::reload), see [`Messages::from_dir`].
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        MessageCatalog::from_loader(move || Messages::from_dir(&dir))
+    }
+
+    /// Load the translations again. On failure, e.g. a syntax error in a file, the
+    /// current translations stay in use and the error is returned. Catalogs created with
+    /// [`new`](Self::new) have nothing to reload from.
+    pub fn reload(&self) -> Result<(), String> {
+        let load = self
+            .loader
+            .as_ref()
+            .ok_or_else(|| "the catalog has no loader".to_string())?;
+        self.replace(load()?);
+        Ok(())
+    }
+
+    /// Use `messages` from now on.
+    pub fn replace(&self, messages: Messages) {
+        let messages = Arc::new(messages);
+        match self.current.write() {
+            Ok(mut current) => *current = messages,
+            Err(poisoned) => *poisoned.into_inner() = messages,
+        }
+    }
+
+    fn current(&self) -> Arc<Messages> {
+        match self.current.read() {
+            Ok(current) => Arc::clone(&current),
+            Err(poisoned) => Arc::clone(&poisoned.into_inner()),
+        }
+    }
+}
+
+/// Renders messages in the locale negotiated for one request.
+pub(crate) struct Translator {
+    messages: Arc<Messages>,
+    bundle: usize,
+}
+
+/// The translator for `req`, if translations are configured and one of the requested
+/// locales is available. The locale from [`JsonConfig::locale`] comes first, then those
+/// of the `Accept-Language` header by preference.
+pub(crate) fn translator(config: &JsonConfig, req: &HttpRequest) -> Option<Translator> {
+    let messages = config.messages.as_ref()?.current();
+
+    let resolved = config
+        .locale
+        .as_ref()
+        .and_then(|resolve| resolve(req))
+        .and_then(|locale| locale.parse().ok());
+    let accepted = AcceptLanguage::parse(req)
+        .map(|header| header.ranked())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|preference| match preference {
+            Preference::Specific(tag) => tag.as_str().parse().ok(),
+            Preference::Any => None,
+        });
+    let requested: Vec<LanguageIdentifier> = resolved.into_iter().chain(accepted).collect();
+
+    let bundle = messages.negotiate(&requested)?;
+    Some(Translator { messages, bundle })
+}
+
+impl Translator {
+    /// Translate the failure at `path`, or `None` when there is no usable translation.
+    pub(crate) fn render(&self, path: &str, label: Option<&str>, error: &Error) -> Option<String> {
+        let bundle = &self.messages.bundles[self.bundle];
+        let pattern = bundle.get_message(constraint_name(error))?.value()?;
+        let original = code::message(error);
+        let mut args = arguments(error, &original)?;
+        args.set("field", path);
+        args.set("label", label.unwrap_or(path));
+
+        let mut errors = Vec::new();
+        let translated = bundle.format_pattern(pattern, Some(&args), &mut errors);
+        errors.is_empty().then(|| translated.into_owned())
+    }
+}
+
+/// The constraint's parameters, as read by [`template::parameter`]. Returns `None` for
+/// customized messages.
+fn arguments<'a>(error: &Error, message: &'a str) -> Option<FluentArgs<'a>> {
+    let mut args = FluentArgs::new();
+    match error {
+        Error::Minimum(_)
+        | Error::Maximum(_)
+        | Error::ExclusiveMinimum(_)
+        | Error::ExclusiveMaximum(_)
+        | Error::MultipleOf(_)
+        | Error::MinLength(_)
+        | Error::MaxLength(_)
+        | Error::MinItems(_)
+        | Error::MaxItems(_)
+        | Error::MinProperties(_)
+        | Error::MaxProperties(_) => {
+            // Keep the written precision: plural rules treat `1.0` differently from `1`.
+            let bound: FluentNumber = template::parameter(error)?.parse().ok()?;
+            args.set("limit", FluentValue::from(bound));
+        }
+        Error::Enumerate(_) => args.set("values", template::parameter(error)?),
+        Error::Pattern(_) => args.set("pattern", template::parameter(error)?),
+        Error::UniqueItems(_) => {
+            template::parameter(error)?;
+        }
+        _ => {}
+    }
+    args.set("message", message);
+    Some(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppJson;
+    use actix_web::{body::MessageBody, test, FromRequest, HttpMessage, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Food {
+        #[validate(min_length = 3)]
+        name: String,
+        #[validate(enumerate = ["small", "large"])]
+        size: String,
+        #[validate(maximum = 10, message = "Too spicy!")]
+        spice: u8,
+    }
+
+    fn config() -> JsonConfig {
+        let messages = Messages::new()
+            .add(
+                "fr",
+                "min_length = Au moins { $limit } caractères.\n\
+                 enumerate = Valeurs possibles : { $values }.\n\
+                 maximum = Au plus { $limit }.",
+            )
+            .unwrap()
+            .add("de", "min_length = Mindestens { $limit } Zeichen.")
+            .unwrap();
+        JsonConfig::default()
+            .messages(messages)
+            .locale(|req| req.extensions().get::<String>().cloned())
+    }
+
+    async fn errors(req: test::TestRequest) -> serde_json::Value {
+        let (req, mut payload) = req
+            .app_data(config())
+            .set_json(json!({"name": "Pi", "size": "huge", "spice": 11}))
+            .to_http_parts();
+        let err = AppJson::<Food>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_messages_follow_accept_language() {
+        let body =
+            errors(test::TestRequest::post().insert_header(("accept-language", "es, fr-CA;q=0.8")))
+                .await;
+
+        assert_eq!(
+            body,
+            json!({
+                "name": ["Au moins 3 caractères."],
+                "size": ["Valeurs possibles : small, large."],
+                "spice": ["Too spicy!"]
+            })
+        );
+
+        let body = errors(test::TestRequest::post()).await;
+        assert_eq!(
+            body["name"],
+            json!(["The length of the value must be `>= 3`."])
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_resolved_locale_takes_precedence() {
+        let req = test::TestRequest::post().insert_header(("accept-language", "fr"));
+        let (req, mut payload) = req
+            .app_data(config())
+            .set_json(json!({"name": "Pi", "size": "small", "spice": 1}))
+            .to_http_parts();
+        req.extensions_mut().insert("de".to_string());
+        let err = AppJson::<Food>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(body, json!({"name": ["Mindestens 3 Zeichen."]}).to_string());
+    }
+
+    #[actix_web::test]
+    async fn test_label_argument() {
+        let messages = Messages::new()
+            .add(
+                "de",
+                "min_length = { $label } muss mindestens { $limit } Zeichen haben.",
+            )
+            .unwrap();
+        let config = JsonConfig::default()
+            .messages(messages)
+            .label("name", "Der Name");
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .insert_header(("accept-language", "de"))
+            .set_json(json!({"name": "Pi", "size": "small", "spice": 11}))
+            .to_http_parts();
+
+        let err = AppJson::<Food>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "name": ["Der Name muss mindestens 3 Zeichen haben."],
+                "spice": ["Too spicy!"]
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_catalog_reload() {
+        let dir = std::env::temp_dir().join(format!("catalog-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fr.ftl"), "min_length = Trop court.").unwrap();
+        let catalog = MessageCatalog::from_dir(&dir).unwrap();
+        let config = JsonConfig::default().message_catalog(catalog.clone());
+
+        let name = || async {
+            let (req, mut payload) = test::TestRequest::post()
+                .app_data(config.clone())
+                .insert_header(("accept-language", "fr"))
+                .set_json(json!({"name": "Pi", "size": "small", "spice": 1}))
+                .to_http_parts();
+            let err = AppJson::<Food>::from_request(&req, &mut payload)
+                .await
+                .unwrap_err();
+            let body = err.error_response().into_body().try_into_bytes().unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["name"].clone()
+        };
+        assert_eq!(name().await, json!(["Trop court."]));
+
+        fs::write(
+            dir.join("fr.ftl"),
+            "min_length = Au moins { $limit } caractères.",
+        )
+        .unwrap();
+        catalog.reload().unwrap();
+        assert_eq!(name().await, json!(["Au moins 3 caractères."]));
+
+        // A broken file keeps the translations in use.
+        fs::write(dir.join("fr.ftl"), "min_length = { $limit").unwrap();
+        assert!(catalog.reload().is_err());
+        assert_eq!(name().await, json!(["Au moins 3 caractères."]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Basket {
+        #[validate(min_items = 1)]
+        one: Vec<u8>,
+        #[validate(min_items = 3)]
+        few: Vec<u8>,
+        #[validate(min_items = 5)]
+        many: Vec<u8>,
+        #[validate(min_items = 22)]
+        few_again: Vec<u8>,
+        #[validate(minimum = 1.5)]
+        weight: f32,
+    }
+
+    #[actix_web::test]
+    async fn test_plural_categories() {
+        let messages = Messages::new()
+            .add(
+                "pl",
+                r#"
+min_items = Co najmniej { $limit } { $limit ->
+    [one] element
+    [few] elementy
+   *[many] elementów
+}.
+minimum = Co najmniej { NUMBER($limit, minimumFractionDigits: 1) } { $limit ->
+    [one] kilogram
+    [few] kilogramy
+    [many] kilogramów
+   *[other] kilograma
+}.
+"#,
+            )
+            .unwrap();
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().messages(messages))
+            .insert_header(("accept-language", "pl-PL"))
+            .set_json(json!({"one": [], "few": [], "many": [], "few_again": [], "weight": 1}))
+            .to_http_parts();
+
+        let err = AppJson::<Basket>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "one": ["Co najmniej 1 element."],
+                "few": ["Co najmniej 3 elementy."],
+                "many": ["Co najmniej 5 elementów."],
+                "few_again": ["Co najmniej 22 elementy."],
+                "weight": ["Co najmniej 1.5 kilograma."]
+            })
+        );
+    }
+}
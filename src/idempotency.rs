@@ -0,0 +1,294 @@
+use std::time::Instant;
+
+use actix_web::{dev::Payload, http::header::HeaderName, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use serde_valid::Validate;
+
+use crate::{
+    extract,
+    json::{json_payload, Inline},
+    AppError, ErrorMap,
+};
+
+/// Format required of idempotency keys, see [`IdempotencyConfig::format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// Any visible ASCII.
+    Any,
+    /// A UUID in its hyphenated form, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    Uuid,
+    /// A random (version 4) UUID in its hyphenated form.
+    UuidV4,
+}
+
+/// Options for [`Idempotent`], registered as app data.
+#[derive(Clone)]
+pub struct IdempotencyConfig {
+    header: HeaderName,
+    required: bool,
+    format: KeyFormat,
+    min_length: usize,
+    max_length: usize,
+    key: String,
+}
+
+impl IdempotencyConfig {
+    /// Read the key from `header` instead of `Idempotency-Key`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Accept requests without a key. Keys that are present are still checked.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Require keys in `format`. Any visible ASCII is accepted by default.
+    pub fn format(mut self, format: KeyFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Require keys of `min` to `max` characters, 1 to 255 by default.
+    pub fn length(mut self, min: usize, max: usize) -> Self {
+        self.min_length = min;
+        self.max_length = max;
+        self
+    }
+
+    /// Report problems with the key under `key` instead of `"Idempotency-Key"`.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    fn check(&self, req: &HttpRequest) -> Result<Option<String>, Vec<String>> {
+        let Some(value) = req.headers().get(&self.header) else {
+            return match self.required {
+                true => Err(vec!["This header is required.".to_string()]),
+                false => Ok(None),
+            };
+        };
+        let Ok(value) = value.to_str() else {
+            return Err(vec!["The value must be visible ASCII.".to_string()]);
+        };
+
+        let mut messages = Vec::new();
+        if value.len() < self.min_length {
+            messages.push(format!(
+                "The length of the value must be `>= {}`.",
+                self.min_length
+            ));
+        }
+        if value.len() > self.max_length {
+            messages.push(format!(
+                "The length of the value must be `<= {}`.",
+                self.max_length
+            ));
+        }
+        match self.format {
+            KeyFormat::Uuid if !is_uuid(value) => {
+                messages.push("The value must be a UUID.".to_string())
+            }
+            KeyFormat::UuidV4 if !(is_uuid(value) && is_v4(value)) => {
+                messages.push("The value must be a version 4 UUID.".to_string())
+            }
+            _ => {}
+        }
+        match messages.is_empty() {
+            true => Ok(Some(value.to_string())),
+            false => Err(messages),
+        }
+    }
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        IdempotencyConfig {
+            header: HeaderName::from_static("idempotency-key"),
+            required: true,
+            format: KeyFormat::Any,
+            min_length: 1,
+            max_length: 255,
+            key: "Idempotency-Key".to_string(),
+        }
+    }
+}
+
+/// Whether `value` is a hyphenated UUID (`8-4-4-4-12` hex digits).
+fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(index, c)| match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Whether the hyphenated UUID `value` is a version 4, RFC 4122 variant UUID.
+fn is_v4(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes[14] == b'4' && matches!(bytes[19].to_ascii_lowercase(), b'8' | b'9' | b'a' | b'b')
+}
+
+/// Extract and validate a JSON body of type `T` together with the `Idempotency-Key`
+/// header of the request.
+///
+/// Header and body are both checked so that a single response lists every problem;
+/// problems with the key appear next to the body errors under `"Idempotency-Key"`
+/// (see [`IdempotencyConfig`]):
+///
+/// ```json
+/// {"Idempotency-Key": ["The value must be a version 4 UUID."], "amount": ["..."]}
+/// ```
+///
+/// Limits, error handler and debug settings are read from [`JsonConfig`](crate::JsonConfig).
+#[derive(Debug)]
+pub struct Idempotent<T> {
+    /// The idempotency key, `None` only when [`IdempotencyConfig::optional`] is set.
+    pub key: Option<String>,
+    pub payload: T,
+}
+
+impl<T> Idempotent<T> {
+    /// Deconstruct to the key and the payload
+    pub fn into_inner(self) -> (Option<String>, T) {
+        (self.key, self.payload)
+    }
+}
+
+impl<T> FromRequest for Idempotent<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let idempotency = req
+            .app_data::<IdempotencyConfig>()
+            .cloned()
+            .unwrap_or_default();
+        let type_name = std::any::type_name::<Self>();
+        let started = Instant::now();
+
+        let key = idempotency.check(req);
+        let body =
+            json_payload::<T, Inline>(req, payload, config.clone(), std::any::type_name::<T>());
+        let req = req.clone();
+
+        Box::pin(async move {
+            let result = match (key, body.await) {
+                (Ok(key), Ok(payload)) => Ok(Idempotent { key, payload }),
+                (Ok(_), Err(err)) => Err(err),
+                (Err(messages), body) => {
+                    let mut errors = ErrorMap::new();
+                    errors.insert(idempotency.key, json!(messages));
+                    match body {
+                        Err(AppError::ValidationError(map)) => errors.extend(map),
+                        Err(err) => {
+                            return extract::finish(&config, &req, type_name, started, Err(err))
+                        }
+                        Ok(_) => {}
+                    }
+                    Err(AppError::ValidationError(errors))
+                }
+            };
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Payment {
+        #[validate(minimum = 1)]
+        amount: u32,
+    }
+
+    async fn extract(
+        config: IdempotencyConfig,
+        key: Option<&str>,
+        body: Value,
+    ) -> Result<Idempotent<Payment>, Value> {
+        let mut req = test::TestRequest::post().app_data(config).set_json(body);
+        if let Some(key) = key {
+            req = req.insert_header(("Idempotency-Key", key));
+        }
+        let (req, mut payload) = req.to_http_parts();
+        Idempotent::<Payment>::from_request(&req, &mut payload)
+            .await
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_header_and_body_errors_are_reported_together() {
+        let config = IdempotencyConfig::default().format(KeyFormat::UuidV4);
+
+        let payment = extract(
+            config.clone(),
+            Some("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            json!({"amount": 10}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            payment.key.as_deref(),
+            Some("67e55044-10b1-426f-9247-bb680e5fe0c8")
+        );
+        assert_eq!(payment.payload.amount, 10);
+
+        assert_eq!(
+            extract(config.clone(), None, json!({"amount": 0}))
+                .await
+                .unwrap_err(),
+            json!({
+                "Idempotency-Key": ["This header is required."],
+                "amount": ["The number must be `>= 1`."]
+            })
+        );
+        assert_eq!(
+            extract(
+                config,
+                Some("67e55044-10b1-126f-9247-bb680e5fe0c8"),
+                json!({"amount": 1})
+            )
+            .await
+            .unwrap_err(),
+            json!({"Idempotency-Key": ["The value must be a version 4 UUID."]})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_optional_key_with_custom_report_key() {
+        let config = IdempotencyConfig::default()
+            .optional()
+            .length(8, 16)
+            .key("idempotency_key");
+
+        let payment = extract(config.clone(), None, json!({"amount": 1}))
+            .await
+            .unwrap();
+        assert_eq!(payment.key, None);
+
+        assert_eq!(
+            extract(config, Some("short"), json!({"amount": 1}))
+                .await
+                .unwrap_err(),
+            json!({"idempotency_key": ["The length of the value must be `>= 8`."]})
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::ops::Deref;
+
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use futures_util::future::{ready, Ready};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{extract, ErrorMap, JsonConfig};
+
+/// What [`AppJson`](crate::AppJson) does with keys of a body that its payload type does
+/// not know, see [`JsonConfig::unknown_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownKeys {
+    /// Collect them into the [`IgnoredKeys`] of the request.
+    Collect,
+    /// Collect them, and also report each one under
+    /// [`warnings`](crate::ValidationReport::warnings).
+    Warn,
+}
+
+/// The keys of the body that the payload type ignored, as dotted paths such as
+/// `address.floor`, in the order they appeared. Requires the `unknown-keys` feature and
+/// [`JsonConfig::unknown_keys`]:
+///
+/// ```
+/// use actix_json_validator::{AppJson, IgnoredKeys};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Order { sku: String }
+///
+/// async fn create_order(order: AppJson<Order>, ignored: IgnoredKeys) -> String {
+///     if !ignored.is_empty() {
+///         log::info!("client sent fields we do not support yet: {:?}", *ignored);
+///     }
+///     order.sku.clone()
+/// }
+/// ```
+///
+/// Must come after the payload in the handler's arguments; it is empty when the payload
+/// has not been extracted, or collection is off.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoredKeys(pub Vec<String>);
+
+impl Deref for IgnoredKeys {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Vec<String> {
+        &self.0
+    }
+}
+
+impl FromRequest for IgnoredKeys {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(req
+            .extensions()
+            .get::<IgnoredKeys>()
+            .cloned()
+            .unwrap_or_default()))
+    }
+}
+
+/// Deserialize a JSON body, collecting the paths of the keys `T` ignores.
+pub(crate) fn from_slice<T: DeserializeOwned>(
+    body: &[u8],
+) -> Result<(T, Vec<String>), serde_json::Error> {
+    let mut ignored = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let data = serde_ignored::deserialize(&mut deserializer, |path| {
+        ignored.push(dotted(&path));
+    })?;
+    deserializer.end()?;
+    Ok((data, ignored))
+}
+
+/// The path as the crate writes them, without the segments of options and newtypes.
+fn dotted(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+
+    let mut segments = Vec::new();
+    let mut path = path;
+    loop {
+        path = match path {
+            Path::Root => break,
+            Path::Seq { parent, index } => {
+                segments.push(index.to_string());
+                parent
+            }
+            Path::Map { parent, key } => {
+                segments.push(key.clone());
+                parent
+            }
+            Path::Some { parent }
+            | Path::NewtypeStruct { parent }
+            | Path::NewtypeVariant { parent } => parent,
+        };
+    }
+    segments.reverse();
+    segments.join(".")
+}
+
+/// Make the ignored keys available to the handler, warning about them if configured.
+pub(crate) fn record(config: &JsonConfig, req: &HttpRequest, ignored: Vec<String>) {
+    if config.unknown_keys == Some(UnknownKeys::Warn) && !ignored.is_empty() {
+        let warnings: ErrorMap = ignored
+            .iter()
+            .map(|path| (path.clone(), json!(["Unknown field, ignored."])))
+            .collect();
+        extract::warn(req, warnings);
+    }
+    req.extensions_mut().insert(IgnoredKeys(ignored));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, ValidationReport};
+    use actix_web::test;
+    use serde::Deserialize;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(min_length = 3)]
+        sku: String,
+        shipping: Option<Shipping>,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Shipping {
+        city: String,
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_keys_are_collected() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().unknown_keys(UnknownKeys::Warn))
+            .set_json(json!({
+                "sku": "A-100",
+                "gift_wrap": true,
+                "shipping": {"city": "Lagos", "floor": 3}
+            }))
+            .to_http_parts();
+
+        let order = AppJson::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(order.sku, "A-100");
+        assert_eq!(order.shipping.as_ref().unwrap().city, "Lagos");
+        let ignored = IgnoredKeys::from_request(&req, &mut Payload::None)
+            .await
+            .unwrap();
+        assert_eq!(*ignored, ["gift_wrap", "shipping.floor"]);
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(
+            json!(report.warnings),
+            json!({
+                "gift_wrap": ["Unknown field, ignored."],
+                "shipping.floor": ["Unknown field, ignored."]
+            })
+        );
+    }
+}
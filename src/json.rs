@@ -0,0 +1,987 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{
+    dev::{Decompress, Payload},
+    error::{JsonPayloadError, PayloadError},
+    web::{Bytes, JsonBody},
+    FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{
+    future::{self, LocalBoxFuture},
+    FutureExt, StreamExt,
+};
+use serde::de::DeserializeOwned;
+use serde_valid::{validation::Errors as ValidationErrors, Validate};
+
+use crate::{audit, budget, compat, extract, stats, stream, strict, AppError, JsonConfig};
+
+#[derive(Debug)]
+pub struct AppJson<T>(pub T);
+
+impl<T> AppJson<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Validate> AppJson<T> {
+    /// Run validation again, e.g. after the handler filled in defaults or merged stored
+    /// state into the payload. Errors are formatted with the [`JsonConfig`] of `req`,
+    /// exactly as the extractor formats them, and replace its
+    /// [`ValidationReport`](crate::ValidationReport).
+    pub fn revalidate(&self, req: &HttpRequest) -> Result<(), AppError> {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let result = extract::check(&self.0, &config, req, type_name);
+        extract::finish(&config, req, type_name, started, result)
+    }
+
+    /// Apply `f` to the payload, then [revalidate](AppJson::revalidate) it:
+    ///
+    /// ```
+    /// # use actix_json_validator::{AppError, AppJson};
+    /// # use actix_web::HttpRequest;
+    /// # #[derive(serde::Deserialize, serde_valid::Validate)]
+    /// # struct CreateFood { #[validate(min_length = 3)] name: String }
+    /// async fn create_food(
+    ///     food: AppJson<CreateFood>,
+    ///     req: HttpRequest,
+    /// ) -> Result<String, AppError> {
+    ///     let food =
+    ///         food.validated_mut(&req, |food| food.name = food.name.trim().to_string())?;
+    ///     Ok(food.into_inner().name)
+    /// }
+    /// ```
+    pub fn validated_mut<F>(mut self, req: &HttpRequest, f: F) -> Result<Self, AppError>
+    where
+        F: FnOnce(&mut T),
+    {
+        f(&mut self.0);
+        self.revalidate(req)?;
+        Ok(self)
+    }
+}
+
+impl<T> AsRef<T> for AppJson<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        extract_with::<T, Inline>(req, payload, extract::config(req))
+            .map(|result| result.map(AppJson))
+            .boxed_local()
+    }
+}
+
+/// Extract as [`AppJson`] does, with `config` instead of the request's and bodies parsed
+/// by `P`.
+pub(crate) fn extract_with<T, P>(
+    req: &HttpRequest,
+    payload: &mut Payload,
+    config: JsonConfig,
+) -> LocalBoxFuture<'static, Result<T, AppError>>
+where
+    T: DeserializeOwned + Validate + 'static,
+    P: Parser<T>,
+{
+    let type_name = std::any::type_name::<T>();
+    let started = Instant::now();
+
+    let body = json_payload::<T, P>(req, payload, config.clone(), type_name);
+    let req = req.clone();
+    async move {
+        let result = body.await;
+        extract::finish(&config, &req, type_name, started, result)
+    }
+    .boxed_local()
+}
+
+/// Read, deserialize and validate a JSON body as [`AppJson`] does, leaving the
+/// [`ValidationReport`](crate::ValidationReport) and error handler to the caller.
+pub(crate) fn json_payload<T, P>(
+    req: &HttpRequest,
+    payload: &mut Payload,
+    config: JsonConfig,
+    type_name: &'static str,
+) -> LocalBoxFuture<'static, Result<T, AppError>>
+where
+    T: DeserializeOwned + Validate + 'static,
+    P: Parser<T>,
+{
+    let req = req.clone();
+    // Audited payloads are buffered, so the audit sink gets the body as received, and so
+    // are payloads checked against a soft limit, whose size is only known once read,
+    // transformed before parsing or sampled for the stats endpoint.
+    // `JsonBody` always accepts `+json` types, so their opt-out is checked here too.
+    let buffered = config.audit.is_some()
+        || config.soft_limit.is_some()
+        || config.strict_json
+        || config.transform.is_some()
+        || stats::sampling(&config, &req)
+        || collects_ignored(&config)
+        || extract::sniffs(&req, &config)
+        || (config.content_type_required && !config.json_suffix);
+    // Only payloads parsed off the worker have their thresholds.
+    let threshold = config.blocking_threshold.filter(|_| P::OFFLOADS);
+    let body = match threshold {
+        _ if P::OFFLOADS && !buffered && stream::streams(&req, &config) => {
+            Body::Streamed(payload.take())
+        }
+        None if !buffered => Body::Json(compat::json_body(&req, payload, &config)),
+        threshold => Body::Buffered(payload.take(), threshold.unwrap_or(usize::MAX)),
+    };
+    async move {
+        extract::check_charset(&config, &req, type_name)?;
+        let _permit = budget::reserve(&config, &req, type_name).await?;
+        let payload_error = |e| extract::json_error(&config, &req, type_name, e);
+        match body {
+            Body::Json(body) => body
+                .await
+                .map_err(payload_error)
+                .and_then(|data| extract::validate(data, &config, &req, type_name)),
+            Body::Buffered(payload, threshold) => {
+                let body = read_buffered(&req, &config, payload)
+                    .await
+                    .map_err(payload_error)?;
+                if extract::sniffs(&req, &config) {
+                    extract::check_sniffed(&config, &req, type_name, &body)?;
+                }
+                let body = match &config.transform {
+                    Some(transform) => transform(body, &req)?,
+                    None => body,
+                };
+                if config.strict_json {
+                    strict::check(&body)
+                        .map_err(|err| payload_error(JsonPayloadError::Deserialize(err)))?;
+                }
+                let validate = extract::validator(&req, type_name);
+                let collect = collects_ignored(&config);
+                #[cfg_attr(not(feature = "unknown-keys"), allow(unused_variables))]
+                let (data, outcome, body, ignored) =
+                    P::buffered(body, threshold, collect, validate)
+                        .await
+                        .map_err(payload_error)?;
+                #[cfg(feature = "unknown-keys")]
+                crate::ignored::record(&config, &req, ignored);
+                extract::outcome(outcome, &config, &req, type_name)?;
+                audit::record(&config, &req, type_name, &body);
+                stats::sample(&config, &req, &body);
+                Ok(data)
+            }
+            Body::Streamed(payload) => {
+                accepts(&req, &config).map_err(payload_error)?;
+                let validate = extract::validator(&req, type_name);
+                let (data, outcome) = P::streamed(&req, &config, payload, validate)
+                    .await
+                    .map_err(payload_error)?;
+                extract::outcome(outcome, &config, &req, type_name)?;
+                Ok(data)
+            }
+        }
+    }
+    .boxed_local()
+}
+
+enum Body<T> {
+    Json(JsonBody<T>),
+    /// The payload and the size from which it is parsed on the blocking thread pool.
+    Buffered(Payload, usize),
+    /// The payload, parsed on the blocking thread pool as it arrives.
+    Streamed(Payload),
+}
+
+/// A buffered body deserialized and validated by a [`Parser`], with the keys it ignored.
+pub(crate) type Parsed<T> =
+    Result<(T, Result<(), ValidationErrors>, Bytes, Vec<String>), JsonPayloadError>;
+
+/// A body deserialized and validated by a [`Parser`] as it arrived.
+pub(crate) type Streamed<T> = Result<(T, Result<(), ValidationErrors>), JsonPayloadError>;
+
+/// Where [`json_payload`] deserializes and validates `T`: [`Inline`] on the worker, for
+/// any payload type, or [`Offloaded`](crate::offload::Offloaded) on the blocking thread
+/// pool past [`JsonConfig::blocking_threshold`] and
+/// [`JsonConfig::streaming_threshold`], for `Send` ones.
+pub(crate) trait Parser<T> {
+    /// Whether the thresholds apply at all.
+    const OFFLOADS: bool;
+
+    /// Parse a buffered body, off the worker from `threshold` bytes on if
+    /// [`OFFLOADS`](Parser::OFFLOADS) is set, along with the keys it ignored if `collect`
+    /// is set.
+    fn buffered<V>(
+        body: Bytes,
+        threshold: usize,
+        collect: bool,
+        validate: V,
+    ) -> LocalBoxFuture<'static, Parsed<T>>
+    where
+        V: Fn(&T) -> Result<(), ValidationErrors> + Send + 'static;
+
+    /// Parse the body as it arrives, only asked of parsers that
+    /// [offload](Parser::OFFLOADS).
+    fn streamed<V>(
+        req: &HttpRequest,
+        config: &JsonConfig,
+        payload: Payload,
+        validate: V,
+    ) -> LocalBoxFuture<'static, Streamed<T>>
+    where
+        V: Fn(&T) -> Result<(), ValidationErrors> + Send + 'static;
+}
+
+/// Parse every body on the worker, as [`AppJson`] does.
+pub(crate) struct Inline;
+
+impl<T: DeserializeOwned + 'static> Parser<T> for Inline {
+    const OFFLOADS: bool = false;
+
+    fn buffered<V>(
+        body: Bytes,
+        _threshold: usize,
+        collect: bool,
+        validate: V,
+    ) -> LocalBoxFuture<'static, Parsed<T>>
+    where
+        V: Fn(&T) -> Result<(), ValidationErrors> + Send + 'static,
+    {
+        future::ready(parse(body, collect, validate)).boxed_local()
+    }
+
+    fn streamed<V>(
+        req: &HttpRequest,
+        config: &JsonConfig,
+        payload: Payload,
+        validate: V,
+    ) -> LocalBoxFuture<'static, Streamed<T>>
+    where
+        V: Fn(&T) -> Result<(), ValidationErrors> + Send + 'static,
+    {
+        // Not reached, bodies are streamed only when offloading; buffer them all the same.
+        let req = req.clone();
+        let config = config.clone();
+        async move {
+            let body = read_buffered(&req, &config, payload).await?;
+            let (data, outcome, _, _) = parse(body, false, validate)?;
+            Ok((data, outcome))
+        }
+        .boxed_local()
+    }
+}
+
+/// Check the content type of `req` like [`JsonBody`] does: without
+/// `content_type_required`, any content type goes, and bodies without one are left to
+/// [`JsonConfig::sniff_content_type`] if it is set.
+fn accepts(req: &HttpRequest, config: &JsonConfig) -> Result<(), JsonPayloadError> {
+    let accepted = match req.mime_type() {
+        Ok(Some(mime)) if config.content_type_required => extract::is_json_type(mime, config),
+        Ok(None) if config.sniff_content_type => true,
+        _ => !config.content_type_required,
+    };
+    if !accepted {
+        return Err(JsonPayloadError::ContentType);
+    }
+    Ok(())
+}
+
+/// Buffer the body, with failures reported like those of [`JsonBody`].
+async fn read_buffered(
+    req: &HttpRequest,
+    config: &JsonConfig,
+    payload: Payload,
+) -> Result<Bytes, JsonPayloadError> {
+    accepts(req, config)?;
+    let limit = config.limit;
+    let length = extract::content_length(req);
+    if let Some(length) = length.filter(|&length| length > limit) {
+        return Err(JsonPayloadError::OverflowKnownLength { length, limit });
+    }
+
+    let payload = Decompress::from_headers(payload, req.headers());
+    let body = extract::read_body(req, Payload::from(payload.boxed_local()), limit)
+        .await
+        .map_err(|err| match err {
+            PayloadError::Overflow => JsonPayloadError::Overflow { limit },
+            err => JsonPayloadError::Payload(err),
+        })?;
+    extract::soft_limit(config, req, body.len());
+    Ok(body)
+}
+
+#[cfg_attr(not(feature = "unknown-keys"), allow(unused_variables))]
+fn deserialize<T: DeserializeOwned>(
+    body: &[u8],
+    collect: bool,
+) -> Result<(T, Vec<String>), serde_json::Error> {
+    #[cfg(feature = "unknown-keys")]
+    if collect {
+        return crate::ignored::from_slice(body);
+    }
+    serde_json::from_slice(body).map(|data| (data, Vec::new()))
+}
+
+/// Whether the keys ignored by the payload type are collected, see
+/// [`JsonConfig::unknown_keys`].
+#[cfg_attr(not(feature = "unknown-keys"), allow(unused_variables))]
+fn collects_ignored(config: &JsonConfig) -> bool {
+    #[cfg(feature = "unknown-keys")]
+    return config.unknown_keys.is_some();
+    #[cfg(not(feature = "unknown-keys"))]
+    false
+}
+
+/// Deserialize and validate a buffered body, along with the keys it ignored if `collect`
+/// is set.
+pub(crate) fn parse<T: DeserializeOwned>(
+    body: Bytes,
+    collect: bool,
+    validate: impl Fn(&T) -> Result<(), ValidationErrors>,
+) -> Parsed<T> {
+    let (data, ignored) =
+        deserialize::<T>(&body, collect).map_err(JsonPayloadError::Deserialize)?;
+    let outcome = validate(&data);
+    Ok((data, outcome, body, ignored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstraintKind, FailureClass, JsonConfig, OffloadedAppJson, ValidationReport};
+    use actix_web::{
+        body::MessageBody,
+        dev::{ServiceRequest, ServiceResponse},
+        error::ErrorConflict,
+        http::StatusCode,
+        middleware::{from_fn, Next},
+        test,
+        web::{self, Bytes},
+        App, HttpMessage, HttpResponse, ResponseError,
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Test {
+        #[validate(min_length = 3)]
+        name: String,
+    }
+
+    #[actix_web::test]
+    async fn test_revalidate_after_mutation() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"name": "Pizza"}))
+            .to_http_parts();
+        let mut data = AppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert!(data.revalidate(&req).is_ok());
+        data.0.name = "Pi".to_string();
+        let body = data
+            .revalidate(&req)
+            .unwrap_err()
+            .error_response()
+            .into_body()
+            .try_into_bytes()
+            .unwrap();
+        assert_eq!(
+            body,
+            json!({"name": ["The length of the value must be `>= 3`."]}).to_string()
+        );
+
+        let data = data
+            .validated_mut(&req, |test| test.name = "Pasta".to_string())
+            .unwrap();
+        assert_eq!(data.name, "Pasta");
+        let err = data
+            .validated_mut(&req, |test| test.name.truncate(1))
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_revalidate_formats_like_the_extractor() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Dish {
+            #[validate(min_length = 3)]
+            #[validate(pattern = "^[a-z]+$")]
+            name: String,
+        }
+
+        let config = || {
+            JsonConfig::default()
+                .label("name", "Dish name")
+                .messages_per_field(1)
+                .status(FailureClass::Schema, StatusCode::UNPROCESSABLE_ENTITY)
+        };
+        let response = |err: AppError| {
+            let response = err.error_response();
+            let status = response.status();
+            (status, response.into_body().try_into_bytes().unwrap())
+        };
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config())
+            .set_json(json!({"name": "P1"}))
+            .to_http_parts();
+        let extracted = AppJson::<Dish>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config())
+            .set_json(json!({"name": "pizza"}))
+            .to_http_parts();
+        let mut dish = AppJson::<Dish>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        dish.0.name = "P1".to_string();
+        let revalidated = dish.revalidate(&req).unwrap_err();
+
+        let (status, body) = response(revalidated);
+        assert_eq!(
+            (status, &body),
+            (StatusCode::UNPROCESSABLE_ENTITY, &response(extracted).1)
+        );
+        assert_eq!(
+            body,
+            json!({"name": ["The length of Dish name must be `>= 3`.", "and 1 more issue"]})
+                .to_string()
+        );
+        let report = req.extensions().get::<ValidationReport>().cloned().unwrap();
+        assert_eq!(report.error_count(), 2);
+    }
+
+    async fn tenant_config(
+        req: ServiceRequest,
+        next: Next<impl MessageBody>,
+    ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+        if req.headers().contains_key("x-tenant") {
+            let config = JsonConfig::default().response_handler(|err, _req, _type_name| {
+                let AppError::ValidationError(errors) = err else {
+                    unreachable!()
+                };
+                HttpResponse::UnprocessableEntity().json(json!({"fault": errors}))
+            });
+            req.extensions_mut().insert(config);
+        }
+        next.call(req).await
+    }
+
+    #[actix_web::test]
+    async fn test_request_extension_config_overrides_app_data() {
+        let app = test::init_service(
+            App::new()
+                .app_data(JsonConfig::default().debug_errors(true))
+                .wrap(from_fn(tenant_config))
+                .route("/", web::post().to(|_: AppJson<Test>| async { "ok" })),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(("x-tenant", "acme"))
+            .set_json(json!({"name": "Pi"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            json!({"fault": {"name": ["The length of the value must be `>= 3`."]}})
+        );
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"name": "Pi"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert!(body.get("_debug").is_some());
+    }
+
+    #[actix_web::test]
+    async fn test_error_handler_receives_app_error_and_type_name() {
+        let config = JsonConfig::default().error_handler(|err, _req, type_name| {
+            assert!(matches!(err, AppError::ValidationError(_)));
+            ErrorConflict(type_name)
+        });
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_payload(json!({"name": "tt"}).to_string())
+            .to_http_parts();
+
+        let res = AppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(res.status_code(), StatusCode::CONFLICT);
+        assert_eq!(res.to_string(), std::any::type_name::<Test>());
+    }
+
+    #[actix_web::test]
+    async fn test_error_body_renders_validation_errors_by_type() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Legacy {
+            #[validate(min_length = 3)]
+            name: String,
+        }
+
+        let config = JsonConfig::default()
+            .status(FailureClass::Schema, StatusCode::UNPROCESSABLE_ENTITY)
+            .error_body(|_errors, _req| json!("invalid"))
+            .error_body_for::<Legacy, _>(|errors, req| {
+                json!({"ok": false, "path": req.path(), "errors": errors.to_string()})
+            });
+        let extract = |body: serde_json::Value| {
+            test::TestRequest::post()
+                .uri("/v1/signup")
+                .app_data(config.clone())
+                .set_json(body)
+                .to_http_parts()
+        };
+
+        let (req, mut payload) = extract(json!({"name": "tt"}));
+        let err = AppJson::<Legacy>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["ok"], false);
+        assert_eq!(body["path"], "/v1/signup");
+        assert!(body["errors"].as_str().unwrap().contains("`>= 3`"));
+
+        let (req, mut payload) = extract(json!({"name": "tt"}));
+        let err = AppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(body, "\"invalid\"");
+
+        let (req, mut payload) = extract(json!({"title": "tt"}));
+        let err = AppJson::<Legacy>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        assert!(body.starts_with(b"{\"error\":"));
+    }
+
+    #[actix_web::test]
+    async fn test_response_handler_controls_full_response() {
+        let config = JsonConfig::default().response_handler(|err, _req, _type_name| {
+            let AppError::ValidationError(errors) = err else {
+                unreachable!()
+            };
+            HttpResponse::UnprocessableEntity()
+                .insert_header(("x-validation-failed", "1"))
+                .json(json!({ "errors": errors }))
+        });
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_payload(json!({"name": "tt"}).to_string())
+            .to_http_parts();
+
+        let res = AppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(res.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response = res.error_response();
+        assert_eq!(response.headers().get("x-validation-failed").unwrap(), "1");
+        let body = response.into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            body,
+            json!({"errors": {"name": ["The length of the value must be `>= 3`."]}}).to_string()
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_debug_errors_include_type_and_constraint() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Outer {
+            #[validate]
+            inner: Test,
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().debug_errors(true))
+            .set_payload(json!({"inner": {"name": "tt"}}).to_string())
+            .to_http_parts();
+
+        let res = AppJson::<Outer>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["_debug"],
+            json!({
+                "type": std::any::type_name::<Outer>(),
+                "errors": [{
+                    "path": "inner.name",
+                    "constraint": "min_length",
+                    "code": "validation.min_length",
+                    "message": "The length of the value must be `>= 3`."
+                }]
+            })
+        );
+        assert_eq!(
+            body["inner"],
+            json!({"name": ["The length of the value must be `>= 3`."]})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_debug_errors_include_deserialization_position() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().debug_errors(true))
+            .set_payload(r#"{"name": 12}"#)
+            .to_http_parts();
+
+        let res = AppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let detail = &body["_debug"]["errors"][0];
+        assert_eq!(detail["constraint"], "deserialization");
+        assert_eq!(detail["code"], "payload.malformed");
+        assert_eq!(detail["category"], "data");
+        assert_eq!(detail["line"], 1);
+        assert_eq!(detail["column"], 11);
+    }
+
+    #[actix_web::test]
+    async fn test_json_suffix_types() {
+        async fn accepted(config: JsonConfig, content_type: &str) -> bool {
+            let (req, mut payload) = test::TestRequest::post()
+                .app_data(config.content_type_required(true))
+                .insert_header(("content-type", content_type))
+                .set_payload(r#"{"name": "Pizza"}"#)
+                .to_http_parts();
+            AppJson::<Test>::from_request(&req, &mut payload)
+                .await
+                .is_ok()
+        }
+
+        for content_type in ["application/vnd.myapp+json", "application/hal+json"] {
+            assert!(accepted(JsonConfig::default(), content_type).await);
+            assert!(!accepted(JsonConfig::default().json_suffix(false), content_type).await);
+        }
+        assert!(accepted(JsonConfig::default().json_suffix(false), "application/json").await);
+    }
+
+    #[actix_web::test]
+    async fn test_missing_content_type_is_sniffed() {
+        async fn extract(body: &'static str) -> Result<Test, (StatusCode, serde_json::Value)> {
+            let config = JsonConfig::default()
+                .content_type_required(true)
+                .sniff_content_type(true);
+            let (req, mut payload) = test::TestRequest::post()
+                .app_data(config)
+                .set_payload(body)
+                .to_http_parts();
+            AppJson::<Test>::from_request(&req, &mut payload)
+                .await
+                .map(AppJson::into_inner)
+                .map_err(|err| {
+                    let status = err.status_code();
+                    let body = err.error_response().into_body().try_into_bytes().unwrap();
+                    (status, serde_json::from_slice(&body).unwrap())
+                })
+        }
+
+        let test = extract("\n  {\"name\": \"Pizza\"}").await.unwrap();
+        assert_eq!(test.name, "Pizza");
+        assert_eq!(
+            extract("name=Pizza").await.unwrap_err(),
+            (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                json!({"error": ["Missing content type, and the body is not JSON"]})
+            )
+        );
+        assert_eq!(
+            extract(r#"{"name": "Pi"}"#).await.unwrap_err(),
+            (
+                StatusCode::BAD_REQUEST,
+                json!({"name": ["The length of the value must be `>= 3`."]})
+            )
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_only_large_errors_are_compressed() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Import {
+            #[validate]
+            rows: Vec<Test>,
+        }
+
+        let app = test::init_service(
+            App::new()
+                .app_data(JsonConfig::default().compress_errors(1024))
+                .wrap(actix_web::middleware::Compress::default())
+                .route("/", web::post().to(|_: AppJson<Import>| async { "ok" })),
+        )
+        .await;
+        for (rows, encoding) in [(1, None), (100, Some("gzip"))] {
+            let req = test::TestRequest::post()
+                .uri("/")
+                .insert_header(("accept-encoding", "gzip"))
+                .set_json(json!({"rows": vec![json!({"name": "tt"}); rows]}))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let content_encoding = resp.headers().get("content-encoding");
+            assert_eq!(
+                content_encoding.and_then(|value| value.to_str().ok()),
+                encoding.or(Some("identity"))
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_debug_errors_requested_by_query() {
+        async fn debug_of(uri: &str, staging: bool) -> serde_json::Value {
+            let config = JsonConfig::default()
+                .debug_query("__debug_errors")
+                .debug_query_guard(move |_| staging);
+            let (req, mut payload) = test::TestRequest::post()
+                .uri(uri)
+                .app_data(config)
+                .set_json(json!({"name": "tt"}))
+                .to_http_parts();
+            let res = AppJson::<Test>::from_request(&req, &mut payload)
+                .await
+                .unwrap_err();
+            let body = res.error_response().into_body().try_into_bytes().unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["_debug"].take()
+        }
+
+        assert_eq!(
+            debug_of("/?__debug_errors=1", true).await["type"],
+            std::any::type_name::<Test>()
+        );
+        assert!(debug_of("/?__debug_errors=0", true).await.is_null());
+        assert!(debug_of("/?__debug_errors=1", false).await.is_null());
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(min_length = 3)]
+        name: String,
+        #[validate(maximum = 10)]
+        quantity: u8,
+        #[validate(enumerate = ["card", "cash"])]
+        payment: String,
+    }
+
+    #[actix_web::test]
+    async fn test_messages_link_to_documentation() {
+        let config = JsonConfig::default()
+            .error_docs("https://docs.example.com/errors/")
+            .error_doc("maximum", "https://docs.example.com/limits");
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"name": "Pi", "quantity": 11, "payment": "cash"}))
+            .to_http_parts();
+
+        let err = AppJson::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let report = req.extensions().get::<crate::ValidationReport>().cloned();
+        assert_eq!(report.unwrap().error_count(), 2);
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "name": [{
+                    "message": "The length of the value must be `>= 3`.",
+                    "type": "https://docs.example.com/errors/min_length"
+                }],
+                "quantity": [{
+                    "message": "The number must be `<= 10`.",
+                    "type": "https://docs.example.com/limits"
+                }]
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_blocking_threshold_keeps_responses() {
+        async fn respond(config: JsonConfig, req: test::TestRequest) -> (StatusCode, Bytes) {
+            let (req, mut payload) = req.app_data(config.limit(64)).to_http_parts();
+            match OffloadedAppJson::<Test>::from_request(&req, &mut payload).await {
+                Ok(data) => (StatusCode::OK, Bytes::from(data.into_inner().name)),
+                Err(err) => (
+                    err.status_code(),
+                    err.error_response().into_body().try_into_bytes().unwrap(),
+                ),
+            }
+        }
+
+        let requests = [
+            || test::TestRequest::post().set_json(json!({"name": "Pizza"})),
+            || test::TestRequest::post().set_json(json!({"name": "Pi"})),
+            || test::TestRequest::post().set_json(json!({"name": "P".repeat(80)})),
+            || {
+                test::TestRequest::post()
+                    .insert_header(("content-type", "application/json"))
+                    .set_payload(r#"{"name": "Pizza""#)
+            },
+            || {
+                test::TestRequest::post()
+                    .insert_header(("content-type", "text/plain"))
+                    .set_payload(r#"{"name": "Pizza"}"#)
+            },
+        ];
+        for request in requests {
+            let inline = respond(JsonConfig::default(), request()).await;
+            for threshold in [0, 1024] {
+                let config = JsonConfig::default().blocking_threshold(threshold);
+                assert_eq!(respond(config, request()).await, inline);
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_truncated_bodies_are_not_syntax_errors() {
+        for config in [
+            JsonConfig::default(),
+            JsonConfig::default().blocking_threshold(0),
+        ] {
+            let (req, _) = test::TestRequest::post()
+                .app_data(config)
+                .insert_header(("content-type", "application/json"))
+                .insert_header(("content-length", "17"))
+                .to_http_parts();
+            let chunks: Vec<Result<Bytes, PayloadError>> = vec![
+                Ok(Bytes::from_static(br#"{"name": "#)),
+                Err(PayloadError::Incomplete(None)),
+            ];
+            let mut payload = Payload::from(futures_util::stream::iter(chunks).boxed_local());
+
+            let err = OffloadedAppJson::<Test>::from_request(&req, &mut payload)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.error_response().into_body().try_into_bytes().unwrap(),
+                json!({"error": [extract::TRUNCATED]}).to_string()
+            );
+            let extensions = req.extensions();
+            let report = extensions.get::<crate::ValidationReport>().unwrap();
+            assert_eq!(report.failures[0].kind, ConstraintKind::Truncated);
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().blocking_threshold(0))
+            .insert_header(("content-type", "application/json"))
+            .set_payload(r#"{"name": "Pizza"}"#)
+            .insert_header(("content-length", "40"))
+            .to_http_parts();
+        let err = OffloadedAppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.error_response().into_body().try_into_bytes().unwrap(),
+            json!({"error": [extract::TRUNCATED]}).to_string()
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_require_utf8() {
+        for (config, status) in [
+            (JsonConfig::default(), StatusCode::OK),
+            (
+                JsonConfig::default().require_utf8(true),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (
+                JsonConfig::default()
+                    .require_utf8(true)
+                    .blocking_threshold(0),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ),
+        ] {
+            let (req, mut payload) = test::TestRequest::post()
+                .app_data(config)
+                .insert_header(("content-type", "application/json; charset=ISO-8859-1"))
+                .set_payload(r#"{"name": "Pizza"}"#)
+                .to_http_parts();
+            match OffloadedAppJson::<Test>::from_request(&req, &mut payload).await {
+                Ok(_) => assert_eq!(status, StatusCode::OK),
+                Err(err) => {
+                    assert_eq!(err.status_code(), status);
+                    assert_eq!(
+                        err.error_response().into_body().try_into_bytes().unwrap(),
+                        json!({"error": ["Unsupported charset `iso-8859-1`, JSON must be UTF-8"]})
+                            .to_string()
+                    );
+                }
+            }
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().require_utf8(true))
+            .insert_header(("content-type", "application/json; charset=UTF-8"))
+            .set_payload(r#"{"name": "Pizza"}"#)
+            .to_http_parts();
+        assert!(AppJson::<Test>::from_request(&req, &mut payload)
+            .await
+            .is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_transform_body() {
+        // Bodies arrive reversed, and empty ones cannot be decoded.
+        let config = JsonConfig::default().transform_body(|body, _req| {
+            if body.is_empty() {
+                return Err(AppError::Handled(ErrorConflict("Empty envelope")));
+            }
+            Ok(body.iter().rev().copied().collect::<Vec<u8>>().into())
+        });
+        let extract = |body: &'static str| {
+            let (req, mut payload) = test::TestRequest::post()
+                .app_data(config.clone())
+                .insert_header(("content-type", "application/json"))
+                .set_payload(body)
+                .to_http_parts();
+            async move { AppJson::<Test>::from_request(&req, &mut payload).await }
+        };
+
+        assert_eq!(extract(r#"}"azziP" :"eman"{"#).await.unwrap().name, "Pizza");
+        let err = extract(r#"}"iP" :"eman"{"#).await.unwrap_err();
+        assert_eq!(
+            err.error_response().into_body().try_into_bytes().unwrap(),
+            json!({"name": ["The length of the value must be `>= 3`."]}).to_string()
+        );
+        let err = extract("").await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+    }
+}
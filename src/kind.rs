@@ -0,0 +1,183 @@
+use serde_valid::validation::{Error, Errors as ValidationError};
+
+use crate::{code, format};
+
+/// What a failure of a rejected payload is about, for middleware and hooks that branch
+/// on it, listed in [`ValidationReport::failures`](crate::ValidationReport::failures).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConstraintKind {
+    Minimum,
+    Maximum,
+    ExclusiveMinimum,
+    ExclusiveMaximum,
+    MultipleOf,
+    MinLength,
+    MaxLength,
+    Pattern,
+    MinItems,
+    MaxItems,
+    UniqueItems,
+    MinProperties,
+    MaxProperties,
+    Enumerate,
+    /// A `custom` validator failed, with the code of its
+    /// [`coded_error`](crate::coded_error), or its message when it has none.
+    Custom(String),
+    /// The body is not valid JSON for the payload type.
+    Deserialization,
+    /// The body does not have an accepted content type.
+    ContentType,
+    /// The body exceeds the size limit.
+    Limit,
+    /// The body ended before its `Content-Length`, or the connection dropped mid-body.
+    Truncated,
+    /// The body could not be read or decoded for another reason.
+    Payload,
+}
+
+impl ConstraintKind {
+    /// The name of the constraint, as in the `serde_valid` attribute (`min_length`,
+    /// `custom`, ...), or of the kind of payload failure (`deserialization`, ...).
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConstraintKind::Minimum => "minimum",
+            ConstraintKind::Maximum => "maximum",
+            ConstraintKind::ExclusiveMinimum => "exclusive_minimum",
+            ConstraintKind::ExclusiveMaximum => "exclusive_maximum",
+            ConstraintKind::MultipleOf => "multiple_of",
+            ConstraintKind::MinLength => "min_length",
+            ConstraintKind::MaxLength => "max_length",
+            ConstraintKind::Pattern => "pattern",
+            ConstraintKind::MinItems => "min_items",
+            ConstraintKind::MaxItems => "max_items",
+            ConstraintKind::UniqueItems => "unique_items",
+            ConstraintKind::MinProperties => "min_properties",
+            ConstraintKind::MaxProperties => "max_properties",
+            ConstraintKind::Enumerate => "enumerate",
+            ConstraintKind::Custom(_) => "custom",
+            ConstraintKind::Deserialization => "deserialization",
+            ConstraintKind::ContentType => "content_type",
+            ConstraintKind::Limit => "limit",
+            ConstraintKind::Truncated => "truncated",
+            ConstraintKind::Payload => "payload",
+        }
+    }
+
+    fn of(error: &Error) -> Self {
+        match error {
+            Error::Minimum(_) => ConstraintKind::Minimum,
+            Error::Maximum(_) => ConstraintKind::Maximum,
+            Error::ExclusiveMinimum(_) => ConstraintKind::ExclusiveMinimum,
+            Error::ExclusiveMaximum(_) => ConstraintKind::ExclusiveMaximum,
+            Error::MultipleOf(_) => ConstraintKind::MultipleOf,
+            Error::MinLength(_) => ConstraintKind::MinLength,
+            Error::MaxLength(_) => ConstraintKind::MaxLength,
+            Error::Pattern(_) => ConstraintKind::Pattern,
+            Error::MinItems(_) => ConstraintKind::MinItems,
+            Error::MaxItems(_) => ConstraintKind::MaxItems,
+            Error::UniqueItems(_) => ConstraintKind::UniqueItems,
+            Error::MinProperties(_) => ConstraintKind::MinProperties,
+            Error::MaxProperties(_) => ConstraintKind::MaxProperties,
+            Error::Enumerate(_) => ConstraintKind::Enumerate,
+            error => ConstraintKind::Custom(
+                code::code(error)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| code::message(error)),
+            ),
+        }
+    }
+}
+
+/// One failure of a rejected payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The dotted path of the offending value, empty for the payload as a whole.
+    pub path: String,
+    pub kind: ConstraintKind,
+}
+
+/// The failures in a validation error tree.
+pub(crate) fn failures(errors: &ValidationError) -> Vec<Failure> {
+    let mut failures = Vec::new();
+    format::walk(errors, "", &mut |path, error| {
+        failures.push(Failure {
+            path: path.to_string(),
+            kind: ConstraintKind::of(error),
+        })
+    });
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coded_error, AppJson, ValidationReport};
+    use actix_web::{test, FromRequest, HttpMessage};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    fn available(_: &str) -> Result<(), Error> {
+        Err(coded_error("already_exists", "This name is taken."))
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    #[allow(dead_code)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        #[validate(custom = available)]
+        name: String,
+        #[validate(max_items = 1)]
+        #[validate(min_length = 1)]
+        tags: Vec<String>,
+    }
+
+    async fn failures_of(req: test::TestRequest) -> Vec<Failure> {
+        let (req, mut payload) = req.to_http_parts();
+        assert!(AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .is_err());
+        let mut failures = req
+            .extensions()
+            .get::<ValidationReport>()
+            .unwrap()
+            .failures
+            .clone();
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        failures
+    }
+
+    #[actix_web::test]
+    async fn test_failures_are_classified() {
+        let req = test::TestRequest::post().set_json(json!({"name": "Al", "tags": ["a", ""]}));
+        let failures = failures_of(req).await;
+        let kinds: Vec<_> = failures
+            .iter()
+            .map(|failure| (failure.path.as_str(), &failure.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("name", &ConstraintKind::MinLength),
+                (
+                    "name",
+                    &ConstraintKind::Custom("already_exists".to_string())
+                ),
+                ("tags", &ConstraintKind::MaxItems),
+                ("tags.1", &ConstraintKind::MinLength),
+            ]
+        );
+
+        let req = test::TestRequest::post()
+            .insert_header(("content-type", "application/json"))
+            .set_payload(r#"{"name": 1}"#);
+        assert_eq!(
+            failures_of(req).await,
+            [Failure {
+                path: String::new(),
+                kind: ConstraintKind::Deserialization
+            }]
+        );
+    }
+}
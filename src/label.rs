@@ -0,0 +1,93 @@
+//! Human-friendly field labels, configured with
+//! [`JsonConfig::label`](crate::JsonConfig::label).
+
+use crate::severity::path_matches;
+
+/// The label registered for the value at `path`; the last matching one wins.
+pub(crate) fn lookup<'a>(labels: &'a [(String, String)], path: &str) -> Option<&'a str> {
+    labels
+        .iter()
+        .rev()
+        .find(|(pattern, _)| path_matches(pattern, path))
+        .map(|(_, label)| label.as_str())
+}
+
+/// Replace the generic subject of a default `serde_valid` message ("The value",
+/// "The number", ...) with `label`. Other messages are returned unchanged.
+pub(crate) fn relabel(message: String, label: &str) -> String {
+    const SUBJECTS: [(&str, &str); 6] = [
+        ("The length of the value", "The length of "),
+        ("The length of the items", "The number of items in "),
+        ("The size of the properties", "The number of properties in "),
+        ("The items", "The items of "),
+        ("The number", ""),
+        ("The value", ""),
+    ];
+
+    for (subject, replacement) in SUBJECTS {
+        if let Some(rest) = message.strip_prefix(subject) {
+            return format!("{replacement}{label}{rest}");
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AppJson, JsonConfig};
+    use actix_web::{body::MessageBody, test, FromRequest, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        given_name: String,
+        #[validate(minimum = 18)]
+        age: u8,
+        #[validate]
+        addresses: Vec<Address>,
+        #[validate(max_length = 2)]
+        nickname: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Address {
+        #[validate(pattern = r"^\d{5}$")]
+        zip: String,
+    }
+
+    #[actix_web::test]
+    async fn test_labels_replace_message_subjects() {
+        let config = JsonConfig::default()
+            .label("given_name", "Given name")
+            .label("age", "Age")
+            .label("addresses.*.zip", "ZIP code");
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({
+                "given_name": "Al",
+                "age": 16,
+                "addresses": [{"zip": "12345"}, {"zip": "1234"}],
+                "nickname": "Ally"
+            }))
+            .to_http_parts();
+
+        let err = AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "given_name": ["The length of Given name must be `>= 3`."],
+                "age": ["Age must be `>= 18`."],
+                "addresses": {"1": {"zip": ["ZIP code must match the pattern of \"^\\d{5}$\"."]}},
+                "nickname": ["The length of the value must be `<= 2`."]
+            })
+        );
+    }
+}
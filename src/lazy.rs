@@ -0,0 +1,161 @@
+use std::cell::OnceCell;
+
+use actix_web::{dev::Payload, web::Bytes, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{AppError, Pipeline};
+
+/// Like [`AppJson`](crate::AppJson), but only buffers the body during extraction:
+/// parsing and validation wait until the handler asks for the payload, so handlers that
+/// return early (failed authorization, cache hits, ...) never pay for them.
+///
+/// ```
+/// use actix_json_validator::{AppError, LazyAppJson};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Report { #[validate(min_length = 1)] title: String }
+/// # fn cached(_: &actix_web::HttpRequest) -> Option<String> { None }
+///
+/// async fn submit(req: actix_web::HttpRequest, report: LazyAppJson<Report>) -> Result<String, AppError> {
+///     if let Some(response) = cached(&req) {
+///         return Ok(response);
+///     }
+///     let report = report.get().await?;
+///     Ok(report.title.clone())
+/// }
+/// ```
+///
+/// Content type and size limit are still checked during extraction. Failures of
+/// [`get`](LazyAppJson::get) go through the error handler of
+/// [`JsonConfig`](crate::JsonConfig) and end up in the
+/// [`ValidationReport`](crate::ValidationReport) like those of `AppJson`.
+pub struct LazyAppJson<T> {
+    body: Bytes,
+    req: HttpRequest,
+    value: OnceCell<T>,
+}
+
+impl<T> LazyAppJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    /// Parse and validate the payload on first call; later calls return the same value.
+    /// A failure is returned again on every call.
+    pub async fn get(&self) -> Result<&T, AppError> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = self.parse()?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Parse and validate the payload, unless [`get`](LazyAppJson::get) already did.
+    pub async fn into_inner(mut self) -> Result<T, AppError> {
+        match self.value.take() {
+            Some(value) => Ok(value),
+            None => self.parse(),
+        }
+    }
+
+    /// The buffered body, as received.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    fn parse(&self) -> Result<T, AppError> {
+        let pipeline = Pipeline::new::<T>(&self.req);
+        let result = pipeline
+            .deserialize::<T>(&self.body)
+            .and_then(|data| pipeline.validate(data));
+        pipeline.finish(result)
+    }
+}
+
+impl<T> FromRequest for LazyAppJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let pipeline = Pipeline::new::<T>(req);
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            match pipeline.collect(payload).await {
+                Ok(body) => Ok(LazyAppJson {
+                    body,
+                    req,
+                    value: OnceCell::new(),
+                }),
+                Err(err) => pipeline.finish(Err(err)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonConfig, ValidationReport};
+    use actix_web::{test, HttpMessage};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Report {
+        #[validate(min_length = 3)]
+        title: String,
+    }
+
+    #[actix_web::test]
+    async fn test_parses_on_first_access() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"title": "Pi"}))
+            .to_http_parts();
+
+        let report = LazyAppJson::<Report>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert!(req.extensions().get::<ValidationReport>().is_none());
+
+        let err = report.get().await.unwrap_err();
+        assert_eq!(
+            json!(match err {
+                AppError::ValidationError(errors) => errors,
+                AppError::Handled(err) => panic!("unexpected error: {err}"),
+            }),
+            json!({"title": ["The length of the value must be `>= 3`."]})
+        );
+        assert_eq!(
+            req.extensions()
+                .get::<ValidationReport>()
+                .map(ValidationReport::error_count),
+            Some(1)
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_limit_is_checked_during_extraction() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().limit(8))
+            .set_json(json!({"title": "Pizza report"}))
+            .to_http_parts();
+
+        assert!(LazyAppJson::<Report>::from_request(&req, &mut payload)
+            .await
+            .is_err());
+
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"title": "Pizza"}))
+            .to_http_parts();
+        let report = LazyAppJson::<Report>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(report.get().await.unwrap().title, "Pizza");
+        assert_eq!(report.into_inner().await.unwrap().title, "Pizza");
+    }
+}
@@ -1,256 +1,176 @@
-use std::{collections::HashMap, ops::Deref, sync::Arc};
-
-use actix_web::{
-    dev::Payload, http::StatusCode, web::JsonBody, Error, FromRequest, HttpRequest, HttpResponse,
-    HttpResponseBuilder, ResponseError,
+mod app_config;
+mod audit;
+mod authorized;
+#[cfg(feature = "avro")]
+mod avro;
+mod batch;
+mod borrowed;
+mod budget;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod code;
+mod compat;
+mod config;
+#[cfg(feature = "config-file")]
+mod config_file;
+mod coverage;
+mod custom;
+mod debug;
+mod deferred;
+mod error;
+mod error_code;
+mod error_format;
+mod escape;
+mod example;
+mod expect;
+mod extract;
+#[cfg(feature = "session")]
+mod flash;
+mod form;
+mod format;
+mod graphql;
+#[cfg(feature = "i18n")]
+mod i18n;
+mod idempotency;
+#[cfg(feature = "unknown-keys")]
+mod ignored;
+mod json;
+mod kind;
+mod label;
+mod lazy;
+mod manifest;
+mod maybe_invalid;
+mod metadata;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "multipart")]
+mod multipart;
+mod offload;
+mod openapi;
+#[cfg(feature = "rayon")]
+mod par;
+mod params;
+mod path;
+mod pipeline;
+mod prefix;
+#[cfg(feature = "profiling")]
+mod profile;
+mod query;
+mod raw;
+mod reference;
+mod report;
+mod seed;
+mod severity;
+mod shape;
+mod soft_limit;
+mod stats;
+mod status;
+mod strategy;
+mod stream;
+mod strict;
+mod template;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod truncate;
+mod try_json;
+mod unique;
+mod valid;
+#[cfg(feature = "xml")]
+mod xml;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use app_config::{validate_app_config, ValidatedAppData};
+pub use audit::AuditRecord;
+pub use authorized::{Authorized, PrincipalRules};
+#[cfg(feature = "avro")]
+pub use avro::{AppAvro, AvroSchemas, AVRO_FINGERPRINT_HEADER};
+pub use batch::{AppBatch, BatchConfig, BatchPart};
+pub use borrowed::BorrowedAppJson;
+pub use budget::MemoryBudget;
+#[cfg(feature = "cbor")]
+pub use cbor::AppCbor;
+pub use code::coded_error;
+pub use config::{JsonConfig, Profile};
+pub use coverage::{constraint_coverage, Coverage};
+pub use custom::{AppCustomJson, DeserializeBody};
+pub use deferred::ValidationDeferred;
+pub use error::{AppError, ErrorMap, IntoAppError};
+pub use error_code::ErrorCode;
+pub use error_format::problem_details;
+pub use escape::MessageEscape;
+pub use example::{examples_of, Examples, InvalidExample};
+pub use expect::ExpectContinue;
+#[cfg(feature = "session")]
+pub use flash::{flash_errors, take_flash_errors};
+pub use form::AppForm;
+pub use graphql::{AppGraphQLRequest, GraphQLRequest};
+#[cfg(feature = "i18n")]
+pub use i18n::{MessageCatalog, Messages};
+pub use idempotency::{IdempotencyConfig, Idempotent, KeyFormat};
+#[cfg(feature = "unknown-keys")]
+pub use ignored::{IgnoredKeys, UnknownKeys};
+pub use json::AppJson;
+pub use kind::{ConstraintKind, Failure};
+pub use lazy::LazyAppJson;
+pub use manifest::{RouteManifest, ValidationManifest};
+pub use maybe_invalid::MaybeInvalid;
+pub use metadata::{
+    constraints_of, Constraint, ConstraintSet, DeclaredConstraint, Describe, FieldMetadata,
+    MetadataRegistry, TypeMetadata,
 };
-use futures_util::{future::LocalBoxFuture, FutureExt};
-use serde::de::DeserializeOwned;
-use serde_json::{json, Value};
-use serde_valid::{validation::Errors as ValidationError, Validate};
-
-#[derive(Debug, thiserror::Error)]
-pub enum AppError {
-    #[error("{{\"non_field_errors\": [\"Validation failed\"]}}")]
-    ValidationError(HashMap<String, Value>),
-}
-
-impl ResponseError for AppError {
-    fn status_code(&self) -> actix_web::http::StatusCode {
-        match self {
-            AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
-        }
-    }
-
-    fn error_response(&self) -> HttpResponse {
-        let response_body = match self {
-            AppError::ValidationError(errors) => {
-                serde_json::json!(errors)
-            }
-        };
-
-        HttpResponseBuilder::new(self.status_code()).json(response_body)
-    }
-}
-
-fn format_errors(errors: ValidationError) -> HashMap<String, Value> {
-    let mut result = HashMap::new();
-    process_errors(&mut result, None, errors);
-    result
-}
-
-fn process_errors(
-    result: &mut HashMap<String, Value>,
-    key: Option<String>,
-    errors: ValidationError,
-) {
-    match errors {
-        ValidationError::Array(array_errors) => {
-            if !array_errors.errors.is_empty() {
-                let error_messages: Vec<String> = array_errors
-                    .errors
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect();
-                result.insert(
-                    key.clone()
-                        .unwrap_or_else(|| "non_field_errors".to_string()),
-                    json!(error_messages),
-                );
-            }
-
-            // Recursively process nested errors
-            if !array_errors.items.is_empty() {
-                let mut nested_map: HashMap<String, Value> = HashMap::new();
-                for (prop, error) in array_errors.items {
-                    process_errors(&mut nested_map, Some(prop.to_string()), error);
-                }
-                for (prop, value) in nested_map {
-                    result.insert(prop, value);
-                }
-            }
-        }
-
-        ValidationError::Object(object_errors) => {
-            // 1) Collect any direct (top-level) errors on this object
-            if !object_errors.errors.is_empty() {
-                let msgs: Vec<String> = object_errors
-                    .errors
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect();
-
-                result.insert(
-                    // If there's a parent key, use it; otherwise use "non_field_errors"
-                    key.clone().unwrap_or_else(|| "non_field_errors".into()),
-                    json!(msgs),
-                );
-            }
-
-            // 2) For each property, recurse and gather its errors in a local map
-            let mut child_map = serde_json::Map::new();
-            for (prop, err) in object_errors.properties {
-                let mut child_result = HashMap::new();
-                process_errors(&mut child_result, None, err);
-                // child_result is HashMap<String, Value>; we typically expect
-                // it to have either "non_field_errors" or property keys.
-
-                // Merge child_result into a single Value
-                // If it has only one key that is "non_field_errors", we flatten:
-                //    "prop": [ ...error array... ]
-                // else store the entire map:
-                //    "prop": { ... }
-
-                if child_result.len() == 1 && child_result.contains_key("non_field_errors") {
-                    child_map.insert(prop, child_result.remove("non_field_errors").unwrap());
-                } else {
-                    child_map.insert(prop, json!(child_result));
-                }
-            }
-
-            // 3) Now we have a map of child properties. If there's a parent key,
-            //    nest them under that parent key. Otherwise, store them top-level.
-            if !child_map.is_empty() {
-                if let Some(parent) = key {
-                    // If the parent key already exists in result and is an object,
-                    // we can merge. If it's an array, or doesn't exist yet, handle accordingly.
-                    match result.get_mut(&parent) {
-                        Some(val) if val.is_object() => {
-                            // Merge child_map into the existing object
-                            if let Some(obj) = val.as_object_mut() {
-                                for (child_prop, child_val) in child_map {
-                                    obj.insert(child_prop, child_val);
-                                }
-                            }
-                        }
-                        _ => {
-                            // Overwrite or create new
-                            result.insert(parent, json!(child_map));
-                        }
-                    }
-                } else {
-                    // We are top-level
-                    for (child_prop, child_val) in child_map {
-                        result.insert(child_prop, child_val);
-                    }
-                }
-            }
-        }
-
-        ValidationError::NewType(vec_errors) => {
-            if !vec_errors.is_empty() {
-                let error_messages: Vec<String> =
-                    vec_errors.iter().map(ToString::to_string).collect();
-                result.insert(
-                    key.unwrap_or_else(|| "non_field_errors".to_string()),
-                    json!(error_messages),
-                );
-            }
-        }
-    }
-}
-
-#[derive(Debug)]
-pub struct AppJson<T>(pub T);
-
-impl<T> AppJson<T> {
-    /// Deconstruct to an inner value
-    pub fn into_inner(self) -> T {
-        self.0
-    }
-}
-
-impl<T> AsRef<T> for AppJson<T> {
-    fn as_ref(&self) -> &T {
-        &self.0
-    }
-}
-
-impl<T> Deref for AppJson<T> {
-    type Target = T;
-
-    fn deref(&self) -> &T {
-        &self.0
-    }
-}
-
-impl<T> FromRequest for AppJson<T>
-where
-    T: DeserializeOwned + Validate + 'static,
-{
-    type Error = AppError;
-    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
-
-    #[inline]
-    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
-        let (limit, ctype) = req
-            .app_data::<JsonConfig>()
-            .map(|c| (c.limit, c.content_type.clone()))
-            .unwrap_or((32768, None));
-
-        JsonBody::<T>::new(req, payload, ctype.as_deref(), false)
-            .limit(limit)
-            .map(|res| match res {
-                Ok(data) => data
-                    .validate()
-                    .map_err(|err: serde_valid::validation::Errors| {
-                        println!("{:?}", err);
-                        Self::Error::ValidationError(format_errors(err))
-                    })
-                    .map(|_| AppJson(data)),
-                Err(e) => Err(Self::Error::ValidationError({
-                    let mut formatted_errors = HashMap::new();
-                    formatted_errors.insert("error".to_string(), json!(vec![e.to_string()]));
-                    formatted_errors
-                })),
-            })
-            .boxed_local()
-    }
-}
-
-type ErrHandler = Arc<dyn Fn(Error, &HttpRequest) -> actix_web::Error + Send + Sync>;
-
-#[derive(Clone)]
-pub struct JsonConfig {
-    limit: usize,
-    ehandler: Option<ErrHandler>,
-    content_type: Option<Arc<dyn Fn(mime::Mime) -> bool + Send + Sync>>,
-}
-
-impl JsonConfig {
-    /// Change max size of payload. By default max size is 32Kb
-    pub fn limit(mut self, limit: usize) -> Self {
-        self.limit = limit;
-        self
-    }
-
-    /// Set custom error handler
-    pub fn error_handler<F>(mut self, f: F) -> Self
-    where
-        F: Fn(Error, &HttpRequest) -> actix_web::Error + Send + Sync + 'static,
-    {
-        self.ehandler = Some(Arc::new(f));
-        self
-    }
-
-    /// Set predicate for allowed content types
-    pub fn content_type<F>(mut self, predicate: F) -> Self
-    where
-        F: Fn(mime::Mime) -> bool + Send + Sync + 'static,
-    {
-        self.content_type = Some(Arc::new(predicate));
-        self
+#[cfg(feature = "msgpack")]
+pub use msgpack::AppMsgPack;
+#[cfg(feature = "multipart")]
+pub use multipart::{AppMultipart, MultipartFile};
+pub use offload::OffloadedAppJson;
+pub use openapi::{OpenApiVersion, ParameterLocation};
+#[cfg(feature = "rayon")]
+pub use par::ParVec;
+pub use params::{AppParams, ParamsConfig};
+pub use path::{AppPath, PathConfig};
+pub use pipeline::Pipeline;
+pub use prefix::ConfigByPrefix;
+#[cfg(feature = "profiling")]
+pub use profile::{profiled, ValidationProfile};
+pub use query::{AppQuery, ListFormat, QueryConfig};
+pub use raw::{AppRawJson, RawJson, RawJsonConfig};
+pub use reference::{ReferenceFields, Referenced, References};
+pub use report::{ExtractionOutcome, ValidationReport};
+pub use seed::{AppSeededJson, JsonSeed};
+pub use severity::Severity;
+pub use soft_limit::soft_limit_warning;
+pub use stats::{validation_stats_service, ValidationStats};
+pub use status::FailureClass;
+pub use strategy::{AppJsonWith, FailFast, Strategy, Verbose};
+#[cfg(feature = "test-util")]
+pub use test_util::{
+    assert_rejects_examples, assert_rejects_invalid_examples, InMemoryChecker, TestRequestExt,
+};
+pub use try_json::TryAppJson;
+pub use unique::{Unique, UniqueFields, Uniqueness, UniquenessChecker};
+pub use valid::Valid;
+#[cfg(feature = "xml")]
+pub use xml::{xml_errors, AppXml};
+#[cfg(feature = "yaml")]
+pub use yaml::AppYaml;
+
+// Lets the derive macros name this crate from inside it, e.g. in its own tests.
+extern crate self as actix_json_validator;
+
+/// Internals exercised by the fuzz targets in `fuzz/`.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod __fuzz {
+    pub fn format_errors(errors: serde_valid::validation::Errors) -> crate::ErrorMap {
+        crate::format::format_errors(errors)
     }
 }
 
-impl Default for JsonConfig {
-    fn default() -> Self {
-        JsonConfig {
-            limit: 32768,
-            ehandler: None,
-            content_type: None,
-        }
-    }
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::metadata::Nested;
+    pub use serde_json;
 }
 
 #[cfg(test)]
@@ -259,7 +179,7 @@ mod tests {
     use actix_web::body::MessageBody;
     use actix_web::http::StatusCode;
     use actix_web::web::Bytes;
-    use actix_web::{test, ResponseError};
+    use actix_web::{test, FromRequest, ResponseError};
     use serde::Deserialize;
     use serde_json::json;
     use serde_valid::{validation::Error as SVError, Validate};
@@ -465,4 +385,444 @@ mod tests {
         let expected_bytes = Bytes::from(expected_string);
         assert_eq!(body, expected_bytes);
     }
+
+    #[actix_web::test]
+    async fn test_per_item_field_errors() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Tags {
+            #[validate(min_length = 3)]
+            #[validate(max_items = 3)]
+            tags: Vec<String>,
+        }
+
+        let payload_data = json!({"tags": ["sweet", "ab", "hot", "x"]}).to_string();
+        let (req, mut payload) = test::TestRequest::post()
+            .set_payload(payload_data)
+            .to_http_parts();
+
+        let res = AppJson::<Tags>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let expected = json!({
+            "tags": {
+                "non_field_errors": ["The length of the items must be `<= 3`."],
+                "1": ["The length of the value must be `>= 3`."],
+                "3": ["The length of the value must be `>= 3`."]
+            }
+        });
+        assert_eq!(body, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_aggregated_item_errors() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Import {
+            #[validate(min_length = 3)]
+            tags: Vec<String>,
+            #[validate]
+            rows: Vec<Row>,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Row {
+            #[validate(min_length = 1)]
+            sku: String,
+            #[validate(minimum = 1)]
+            count: u32,
+        }
+
+        let rows: Vec<_> = (0..1204)
+            .map(|index| json!({"sku": "", "count": index % 2}))
+            .collect();
+        let payload_data = json!({"tags": ["ab", "sweet", "x"], "rows": rows});
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(
+                crate::JsonConfig::default()
+                    .aggregate_items(2)
+                    .limit(1 << 20),
+            )
+            .set_payload(payload_data.to_string())
+            .to_http_parts();
+
+        let res = AppJson::<Import>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let expected = json!({
+            "tags": {"0": ["The length of the value must be `>= 3`."], "2": ["The length of the value must be `>= 3`."]},
+            "rows": {"*": {
+                "sku": ["1,204 items: The length of the value must be `>= 1`."],
+                "count": ["602 items: The number must be `>= 1`."]
+            }}
+        });
+        assert_eq!(body, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_nested_generic_errors() {
+        use std::collections::HashMap;
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Order {
+            #[validate]
+            lines: Option<Vec<Option<Line>>>,
+            #[validate]
+            by_sku: HashMap<String, Line>,
+            #[validate]
+            pallets: Vec<Vec<Line>>,
+            #[validate]
+            split: Vec<Option<Vec<Line>>>,
+            #[validate]
+            warehouses: HashMap<String, Vec<Option<Line>>>,
+            #[validate]
+            batches: Vec<HashMap<String, Vec<Line>>>,
+            #[validate]
+            primary: Option<Primary>,
+            #[validate]
+            backorder: Backorder,
+            #[validate(min_length = 2)]
+            codes: Option<Vec<Option<String>>>,
+            #[validate(min_length = 2)]
+            groups: Vec<Vec<String>>,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Line {
+            #[validate(minimum = 1)]
+            count: u32,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Primary(#[validate] Line);
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Backorder(#[validate] Vec<Line>);
+
+        let bad = json!({"count": 0});
+        let good = json!({"count": 1});
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({
+                "lines": [null, bad],
+                "by_sku": {"A-1": bad},
+                "pallets": [[good, good], [good, bad]],
+                "split": [null, [good, bad]],
+                "warehouses": {"oslo": [null, bad]},
+                "batches": [{"north": [bad]}],
+                "primary": bad,
+                "backorder": [good, bad],
+                "codes": ["ok", null, "x"],
+                "groups": [["ok"], ["ok", "x"]]
+            }))
+            .to_http_parts();
+        let res = AppJson::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let count = json!({"count": ["The number must be `>= 1`."]});
+        let length = json!(["The length of the value must be `>= 2`."]);
+        assert_eq!(
+            body,
+            json!({
+                "lines": {"1": count},
+                "by_sku": {"A-1": count},
+                "pallets": {"1": {"1": count}},
+                "split": {"1": {"1": count}},
+                "warehouses": {"oslo": {"1": count}},
+                "batches": {"0": {"north": {"0": count}}},
+                "primary": count,
+                "backorder": {"1": count},
+                "codes": {"2": length},
+                "groups": {"1": {"1": length}}
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_map_value_constraints_are_reported_by_position() {
+        use std::collections::HashMap;
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Menu {
+            #[validate(min_length = 2)]
+            names: HashMap<String, String>,
+            #[validate]
+            dishes: HashMap<String, Dish>,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Dish {
+            #[validate(min_length = 2)]
+            name: String,
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"names": {"pizza": "P"}, "dishes": {"pizza": {"name": "P"}}}))
+            .to_http_parts();
+        let res = AppJson::<Menu>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        // serde_valid checks the values of a map of primitives by position, without their
+        // keys; nested payloads keep theirs.
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let length = json!(["The length of the value must be `>= 2`."]);
+        assert_eq!(
+            body,
+            json!({
+                "names": {"0": length},
+                "dishes": {"pizza": {"name": length}}
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_truncated_error_summary() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Import {
+            #[validate]
+            rows: Vec<Row>,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Row {
+            #[validate(min_length = 1)]
+            sku: String,
+            #[validate(minimum = 1)]
+            count: u32,
+        }
+
+        let rows = vec![json!({"sku": "", "count": 0}); 10];
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(crate::JsonConfig::default().truncate_errors(5))
+            .set_json(json!({"rows": rows}))
+            .to_http_parts();
+
+        let res = AppJson::<Import>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["_truncated"], true);
+        assert_eq!(body["_total_errors"], 20);
+        assert_eq!(body["_by_code"], json!({"min_length": 10, "minimum": 10}));
+        let kept: usize = body["rows"]
+            .as_object()
+            .unwrap()
+            .values()
+            .flat_map(|row| row.as_object().unwrap().values())
+            .map(|messages| messages.as_array().unwrap().len())
+            .sum();
+        assert_eq!(kept, 5);
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[actix_web::test]
+    async fn test_errors_in_declaration_order() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Order {
+            #[validate(min_length = 3)]
+            zone: String,
+            #[validate]
+            lines: Vec<Line>,
+            #[validate(minimum = 1)]
+            amount: u32,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Line {
+            #[validate(min_length = 1)]
+            sku: String,
+            #[validate(minimum = 1)]
+            count: u32,
+        }
+
+        let payload_data = json!({
+            "zone": "eu",
+            "lines": (0..12).map(|_| json!({"sku": "", "count": 0})).collect::<Vec<_>>(),
+            "amount": 0
+        });
+        let (req, mut payload) = test::TestRequest::post()
+            .set_payload(payload_data.to_string())
+            .to_http_parts();
+
+        let res = AppJson::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let line = json!({
+            "sku": ["The length of the value must be `>= 1`."],
+            "count": ["The number must be `>= 1`."]
+        });
+        let mut expected =
+            String::from(r#"{"zone":["The length of the value must be `>= 3`."],"lines":{"#);
+        for index in 0..12 {
+            let separator = if index == 0 { "" } else { "," };
+            expected.push_str(&format!(r#"{separator}"{index}":{line}"#));
+        }
+        expected.push_str(r#"},"amount":["The number must be `>= 1`."]}"#);
+        assert_eq!(body, Bytes::from(expected));
+    }
+
+    #[actix_web::test]
+    async fn test_messages_per_field() {
+        fn strong(password: &str) -> Result<(), Vec<serde_valid::validation::Error>> {
+            let mut missing = Vec::new();
+            if !password.chars().any(|c| c.is_ascii_digit()) {
+                missing.push("No digit.");
+            }
+            if !password.chars().any(char::is_uppercase) {
+                missing.push("No upper case letter.");
+            }
+            if !password.chars().any(|c| c.is_ascii_punctuation()) {
+                missing.push("No symbol.");
+            }
+            if password.len() < 12 {
+                missing.push("Fewer than 12 characters.");
+            }
+            if missing.is_empty() {
+                return Ok(());
+            }
+            Err(missing
+                .into_iter()
+                .map(|message| serde_valid::validation::Error::Custom(message.to_string()))
+                .collect())
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Signup {
+            #[validate(custom = strong)]
+            password: String,
+            #[validate(min_length = 3)]
+            name: String,
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(crate::JsonConfig::default().messages_per_field(2))
+            .set_json(json!({"password": "hunter2", "name": "Al"}))
+            .to_http_parts();
+
+        let res = AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let expected = json!({
+            "password": ["No upper case letter.", "No symbol.", "and 1 more issue"],
+            "name": ["The length of the value must be `>= 3`."]
+        });
+        assert_eq!(body, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_joined_messages() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Signup {
+            #[validate(min_length = 3)]
+            #[validate(pattern = "^[a-z]+$")]
+            name: String,
+            #[validate(minimum = 18)]
+            age: u8,
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(crate::JsonConfig::default().join_messages(true))
+            .set_json(json!({"name": "A1", "age": 12}))
+            .to_http_parts();
+
+        let res = AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let expected = json!({
+            "name": ["The length of the value must be `>= 3` and the value must match the pattern of \"^[a-z]+$\"."],
+            "age": ["The number must be `>= 18`."]
+        });
+        assert_eq!(body, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_first_error_only() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Signup {
+            #[validate(pattern = "^[a-z]+$")]
+            #[validate(min_length = 3)]
+            name: String,
+            #[validate]
+            address: Address,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Address {
+            #[validate(max_length = 2)]
+            #[validate(pattern = "^[A-Z]+$")]
+            country: String,
+        }
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(
+                crate::JsonConfig::default()
+                    .first_error_only(true)
+                    .messages_per_field(5),
+            )
+            .set_json(json!({"name": "A1", "address": {"country": "France"}}))
+            .to_http_parts();
+
+        let res = AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let expected = json!({
+            "name": ["The value must match the pattern of \"^[a-z]+$\"."],
+            "address": {"country": ["The length of the value must be `<= 2`."]}
+        });
+        assert_eq!(body, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_sanitized_messages() {
+        fn known(city: &str) -> Result<(), serde_valid::validation::Error> {
+            Err(serde_valid::validation::Error::Custom(format!(
+                "Unknown city `{city}`."
+            )))
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Trip {
+            #[validate(custom = known)]
+            city: String,
+        }
+
+        let city = format!("Oslo\u{7}\n{}", "o".repeat(1 << 20));
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(
+                crate::JsonConfig::default()
+                    .max_message_length(24)
+                    .limit(2 << 20),
+            )
+            .set_json(json!({ "city": city }))
+            .to_http_parts();
+
+        let res = AppJson::<Trip>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({"city": ["Unknown city `Oslo oooo…"]}));
+    }
 }
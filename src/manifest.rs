@@ -0,0 +1,133 @@
+use serde::Serialize;
+
+use crate::{constraints_of, ConstraintSet, Describe};
+
+/// The payload types of an app's routes and their constraints, for contract tests and
+/// other tooling that checks clients against the server.
+///
+/// Routes are listed next to their registration, then the manifest is dumped as JSON,
+/// typically at startup or from a dedicated binary:
+///
+/// ```
+/// use actix_json_validator::{AppJson, Describe, ValidationManifest};
+/// use actix_web::{web, App};
+/// # #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// # struct CreateOrder { #[validate(min_length = 1)] reference: String }
+/// # async fn create_order(_: AppJson<CreateOrder>) -> &'static str { "ok" }
+///
+/// let manifest = ValidationManifest::new().route::<CreateOrder>("POST", "/orders");
+/// let app = App::new().route("/orders", web::post().to(create_order));
+///
+/// // [{"method": "POST", "path": "/orders", "payload": "CreateOrder",
+/// //   "constraints": [{"path": "reference", "kind": "min_length", "parameters": 1}]}]
+/// let json = serde_json::to_string_pretty(&manifest).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct ValidationManifest {
+    routes: Vec<RouteManifest>,
+}
+
+/// A route of a [`ValidationManifest`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteManifest {
+    /// The HTTP method, e.g. `POST`.
+    pub method: String,
+    /// The path pattern, as registered with actix.
+    pub path: String,
+    /// The name of the payload type.
+    pub payload: &'static str,
+    /// The constraints declared on the payload type and its nested payloads.
+    pub constraints: ConstraintSet,
+}
+
+impl ValidationManifest {
+    /// Create an empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a route whose body is a `T`, e.g. one with an [`AppJson<T>`](crate::AppJson)
+    /// extractor.
+    pub fn route<T: Describe>(
+        mut self,
+        method: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Self {
+        self.routes.push(RouteManifest {
+            method: method.into().to_ascii_uppercase(),
+            path: path.into(),
+            payload: T::metadata().name,
+            constraints: constraints_of::<T>(),
+        });
+        self
+    }
+
+    /// The route registered for `method` and `path`.
+    pub fn get(&self, method: &str, path: &str) -> Option<&RouteManifest> {
+        self.routes
+            .iter()
+            .find(|route| route.method.eq_ignore_ascii_case(method) && route.path == path)
+    }
+
+    /// All routes, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &RouteManifest> {
+        self.routes.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Deserialize, Validate, Describe)]
+    #[allow(dead_code)]
+    struct CreateOrder {
+        #[validate(min_length = 1)]
+        reference: String,
+        #[validate]
+        lines: Vec<Line>,
+    }
+
+    #[derive(Deserialize, Validate, Describe)]
+    #[allow(dead_code)]
+    struct Line {
+        #[validate(minimum = 1)]
+        quantity: u32,
+    }
+
+    #[test]
+    fn test_manifest_lists_routes_with_constraints() {
+        let manifest = ValidationManifest::new()
+            .route::<CreateOrder>("post", "/orders")
+            .route::<Line>("PUT", "/orders/{id}/lines/{line}");
+
+        assert_eq!(
+            manifest.get("POST", "/orders").unwrap().payload,
+            "CreateOrder"
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            json!([
+                {
+                    "method": "POST",
+                    "path": "/orders",
+                    "payload": "CreateOrder",
+                    "constraints": [
+                        {"path": "reference", "kind": "min_length", "parameters": 1},
+                        {"path": "lines.*.quantity", "kind": "minimum", "parameters": 1}
+                    ]
+                },
+                {
+                    "method": "PUT",
+                    "path": "/orders/{id}/lines/{line}",
+                    "payload": "Line",
+                    "constraints": [{"path": "quantity", "kind": "minimum", "parameters": 1}]
+                }
+            ])
+        );
+    }
+}
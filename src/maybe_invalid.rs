@@ -0,0 +1,140 @@
+use std::{convert::Infallible, time::Instant};
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_valid::Validate;
+
+use crate::{budget, compat, extract, AppError, ErrorMap};
+
+/// Extract a JSON payload without rejecting it when validation fails.
+///
+/// Meant for endpoints that accept and flag bad data, such as analytics collectors:
+/// the handler gets the deserialized value together with its validation errors, in the
+/// same format as the error responses of [`AppJson`](crate::AppJson).
+///
+/// ```
+/// use actix_json_validator::MaybeInvalid;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Event { #[validate(min_length = 1)] name: String }
+///
+/// async fn collect(event: MaybeInvalid<Event>) -> String {
+///     match (&event.value, event.errors.len()) {
+///         (Some(_), 0) => "stored".to_string(),
+///         (Some(_), issues) => format!("stored with {issues} issues"),
+///         (None, _) => "dropped".to_string(),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MaybeInvalid<T> {
+    /// The payload, or `None` when the body could not be read or deserialized.
+    pub value: Option<T>,
+    /// Validation errors, or the reason `value` is missing. Empty when the payload is
+    /// valid.
+    pub errors: ErrorMap,
+}
+
+impl<T> MaybeInvalid<T> {
+    /// Whether the payload was deserialized and passed validation.
+    pub fn is_valid(&self) -> bool {
+        self.value.is_some() && self.errors.is_empty()
+    }
+}
+
+impl<T> FromRequest for MaybeInvalid<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = Infallible;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+
+        let req = req.clone();
+        let body = compat::json_body::<T>(&req, payload, &config);
+        async move {
+            let (value, result) = match budget::reserve(&config, &req, type_name).await {
+                Ok(_permit) => match body.await {
+                    Ok(data) => {
+                        let result = extract::check(&data, &config, &req, type_name);
+                        (Some(data), result)
+                    }
+                    Err(e) => (None, Err(extract::json_error(&config, &req, type_name, e))),
+                },
+                Err(err) => (None, Err(err)),
+            };
+            extract::report(&config, &req, type_name, started, &result);
+
+            let errors = match result {
+                Err(AppError::ValidationError(errors)) => errors,
+                Err(AppError::Handled(err)) => {
+                    ErrorMap::from([("error".to_string(), Value::from(vec![err.to_string()]))])
+                }
+                Ok(()) => ErrorMap::new(),
+            };
+            Ok(MaybeInvalid { value, errors })
+        }
+        .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstraintKind, ValidationReport};
+    use actix_web::{test, HttpMessage};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Event {
+        #[validate(min_length = 1)]
+        name: String,
+        #[validate(maximum = 1000)]
+        duration_ms: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_value_is_kept_with_errors() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"name": "", "duration_ms": 5000}))
+            .to_http_parts();
+
+        let event = MaybeInvalid::<Event>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert!(!event.is_valid());
+        assert_eq!(event.value.unwrap().duration_ms, 5000);
+        assert_eq!(
+            json!(event.errors),
+            json!({
+                "name": ["The length of the value must be `>= 1`."],
+                "duration_ms": ["The number must be `<= 1000`."]
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_undeserializable_payload_has_no_value() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"name": "click"}))
+            .to_http_parts();
+
+        let event = MaybeInvalid::<Event>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert!(event.value.is_none());
+        assert!(event.errors.contains_key("error"));
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+}
@@ -0,0 +1,462 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::debug::join;
+
+pub use actix_json_validator_derive::Describe;
+
+/// Runtime description of a payload type: its fields, their labels and the constraints
+/// declared on them, e.g. for generating form hints in a frontend.
+///
+/// Derive it next to `Validate`; the derive reads the same `#[validate(...)]` attributes,
+/// the serde attributes that change field names or requiredness, and an optional
+/// `#[app_json(label = "...")]`:
+///
+/// ```
+/// use actix_json_validator::Describe;
+/// use serde::Deserialize;
+/// use serde_valid::Validate;
+///
+/// #[derive(Deserialize, Validate, Describe)]
+/// #[serde(rename_all = "camelCase")]
+/// struct Signup {
+///     #[validate(min_length = 3)]
+///     #[validate(max_length = 32)]
+///     #[app_json(label = "Username")]
+///     user_name: String,
+///     #[validate(enumerate = ["free", "pro"])]
+///     plan: Option<String>,
+/// }
+///
+/// let metadata = Signup::metadata();
+/// let field = metadata.field("userName").unwrap();
+/// assert_eq!(field.label, Some("Username"));
+/// assert_eq!(field.constraints[1].code, "max_length");
+/// assert!(!metadata.field("plan").unwrap().required);
+/// ```
+///
+/// Fields validated with a bare `#[validate]` are described recursively, so their type
+/// (or the item type of a `Vec` or `Option`) must derive `Describe` too.
+pub trait Describe {
+    /// The description of this type.
+    fn metadata() -> TypeMetadata;
+}
+
+/// Description of a payload type, see [`Describe`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeMetadata {
+    /// The Rust name of the type.
+    pub name: &'static str,
+    /// Constraints declared on the type itself, such as `custom` validations.
+    pub constraints: Vec<Constraint>,
+    /// The fields, in declaration order, named as they appear in the payload.
+    pub fields: Vec<FieldMetadata>,
+}
+
+impl TypeMetadata {
+    /// The field at a dotted `path` such as `address.city`, looking into nested types.
+    pub fn field(&self, path: &str) -> Option<&FieldMetadata> {
+        let (name, rest) = match path.split_once('.') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+        let field = self.fields.iter().find(|field| field.name == name)?;
+        match rest {
+            Some(rest) => field.nested.as_ref()?.field(rest),
+            None => Some(field),
+        }
+    }
+}
+
+/// Description of one field of a payload type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldMetadata {
+    /// The name of the field in the payload, after serde renames.
+    pub name: &'static str,
+    /// The label from `#[app_json(label = "...")]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<&'static str>,
+    /// The Rust type of the field, as written.
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    /// Whether the payload must contain the field, i.e. it is neither an `Option` nor
+    /// `#[serde(default)]`.
+    pub required: bool,
+    /// The declared constraints, in order.
+    pub constraints: Vec<Constraint>,
+    /// The description of a nested payload validated with `#[validate]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nested: Option<TypeMetadata>,
+}
+
+/// A declared constraint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Constraint {
+    /// The constraint, named as in the `serde_valid` attribute (`min_length`,
+    /// `enumerate`, ...).
+    pub code: &'static str,
+    /// Its parameter: the bound, the accepted values, the pattern, or the name of the
+    /// function for `custom`.
+    pub value: Value,
+}
+
+/// The descriptions of several payload types, looked up by type name.
+///
+/// It serializes to an object keyed by type name, ready to be served to a frontend:
+///
+/// ```
+/// use actix_json_validator::{Describe, MetadataRegistry};
+/// use actix_web::{web, App, HttpResponse};
+/// # #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// # struct Signup { #[validate(min_length = 3)] name: String }
+///
+/// let registry = web::Data::new(MetadataRegistry::new().register::<Signup>());
+/// let app = App::new().app_data(registry).route(
+///     "/forms",
+///     web::get().to(|registry: web::Data<MetadataRegistry>| async move {
+///         HttpResponse::Ok().json(registry.get_ref())
+///     }),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct MetadataRegistry {
+    types: IndexMap<&'static str, TypeMetadata>,
+}
+
+impl MetadataRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the description of `T`.
+    pub fn register<T: Describe>(mut self) -> Self {
+        let metadata = T::metadata();
+        self.types.insert(metadata.name, metadata);
+        self
+    }
+
+    /// The description of the type named `name`.
+    pub fn get(&self, name: &str) -> Option<&TypeMetadata> {
+        self.types.get(name)
+    }
+
+    /// All registered descriptions, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &TypeMetadata> {
+        self.types.values()
+    }
+}
+
+/// Every constraint declared on the payload type `T` and its nested payloads, by path.
+///
+/// ```
+/// use actix_json_validator::{constraints_of, Describe};
+/// # #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// # struct Order { #[validate] lines: Vec<Line> }
+/// # #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// # struct Line { #[validate(minimum = 1)] quantity: u32 }
+///
+/// let constraints = constraints_of::<Order>();
+/// assert!(constraints.contains("lines.*.quantity", "minimum"));
+/// ```
+pub fn constraints_of<T: Describe>() -> ConstraintSet {
+    let mut set = ConstraintSet::default();
+    set.collect("", &T::metadata());
+    set
+}
+
+/// The constraints of a payload type, see [`constraints_of`].
+///
+/// It serializes to a list of `{"path", "kind", "parameters"}` objects.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ConstraintSet {
+    constraints: Vec<DeclaredConstraint>,
+}
+
+/// A constraint declared at a path of a payload type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeclaredConstraint {
+    /// The dotted path of the constrained value, as used by [`JsonConfig::label`], with
+    /// `*` for the elements of collections. Constraints on a type itself have the path of
+    /// the field holding it, or an empty path at the top.
+    ///
+    /// [`JsonConfig::label`]: crate::JsonConfig::label
+    pub path: String,
+    /// The constraint, named as in the `serde_valid` attribute.
+    pub kind: &'static str,
+    /// Its parameter, see [`Constraint::value`].
+    pub parameters: Value,
+}
+
+impl ConstraintSet {
+    fn collect(&mut self, path: &str, metadata: &TypeMetadata) {
+        self.push_all(path, &metadata.constraints);
+        for field in &metadata.fields {
+            let field_path = join(path, field.name);
+            self.push_all(&field_path, &field.constraints);
+            if let Some(nested) = &field.nested {
+                let nested_path =
+                    (0..collection_depth(field.ty)).fold(field_path, |path, _| join(&path, "*"));
+                self.collect(&nested_path, nested);
+            }
+        }
+    }
+
+    fn push_all(&mut self, path: &str, constraints: &[Constraint]) {
+        self.constraints
+            .extend(constraints.iter().map(|constraint| DeclaredConstraint {
+                path: path.to_string(),
+                kind: constraint.code,
+                parameters: constraint.value.clone(),
+            }));
+    }
+
+    /// The constraints at `path`.
+    pub fn at<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a DeclaredConstraint> {
+        self.constraints
+            .iter()
+            .filter(move |constraint| constraint.path == path)
+    }
+
+    /// Whether a constraint of `kind` is declared at `path`.
+    pub fn contains(&self, path: &str, kind: &str) -> bool {
+        self.at(path).any(|constraint| constraint.kind == kind)
+    }
+
+    /// All constraints, in declaration order.
+    pub fn iter(&self) -> std::slice::Iter<'_, DeclaredConstraint> {
+        self.constraints.iter()
+    }
+
+    /// The number of constraints.
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Whether no constraint is declared.
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a ConstraintSet {
+    type Item = &'a DeclaredConstraint;
+    type IntoIter = std::slice::Iter<'a, DeclaredConstraint>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// How many levels of collections a nested payload of type `ty` (as written) sits in,
+/// e.g. 2 for `Option<Vec<Vec<Line>>>`.
+pub(crate) fn collection_depth(ty: &str) -> usize {
+    let Some((name, rest)) = ty.split_once('<') else {
+        return 0;
+    };
+    let inner = rest.strip_suffix('>').unwrap_or(rest);
+    match name.rsplit("::").next().unwrap_or(name) {
+        "Vec" | "ParVec" | "VecDeque" | "HashSet" | "BTreeSet" | "IndexSet" => {
+            1 + collection_depth(inner)
+        }
+        "HashMap" | "BTreeMap" | "IndexMap" => {
+            1 + collection_depth(inner.split_once(',').map_or(inner, |(_, value)| value))
+        }
+        _ => collection_depth(inner),
+    }
+}
+
+/// The description of a field validated with a bare `#[validate]`, looking through the
+/// containers `serde_valid` validates item by item.
+#[doc(hidden)]
+pub trait Nested {
+    fn nested_metadata() -> TypeMetadata;
+}
+
+impl<T: Describe> Nested for T {
+    fn nested_metadata() -> TypeMetadata {
+        T::metadata()
+    }
+}
+
+impl<T: Nested> Nested for Vec<T> {
+    fn nested_metadata() -> TypeMetadata {
+        T::nested_metadata()
+    }
+}
+
+impl<T: Nested> Nested for Option<T> {
+    fn nested_metadata() -> TypeMetadata {
+        T::nested_metadata()
+    }
+}
+
+impl<K, V: Nested, S> Nested for std::collections::HashMap<K, V, S> {
+    fn nested_metadata() -> TypeMetadata {
+        V::nested_metadata()
+    }
+}
+
+impl<K, V: Nested, S> Nested for indexmap::IndexMap<K, V, S> {
+    fn nested_metadata() -> TypeMetadata {
+        V::nested_metadata()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Nested> Nested for crate::ParVec<T> {
+    fn nested_metadata() -> TypeMetadata {
+        T::nested_metadata()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Describe;
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::{validation::Error, Validate};
+
+    fn check_owner(_: &Order) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Validate, Describe)]
+    #[validate(custom = check_owner)]
+    struct Order {
+        #[serde(rename = "ref")]
+        #[validate(pattern = r"^[A-Z]{3}\d+$", message = "Bad reference")]
+        reference: String,
+        #[validate(min_items = 1)]
+        #[validate(max_items = 10)]
+        #[validate(unique_items)]
+        #[validate]
+        lines: Vec<Line>,
+        #[serde(default)]
+        note: String,
+        #[serde(skip)]
+        internal: u8,
+    }
+
+    #[derive(Debug, Deserialize, Validate, Describe, PartialEq, Eq, Hash)]
+    struct Line {
+        #[validate(enumerate = ["kg", "pcs"])]
+        #[app_json(label = "Unit")]
+        unit: String,
+        #[validate(minimum = 1)]
+        #[validate(exclusive_maximum = 100)]
+        amount: Option<u32>,
+    }
+
+    #[test]
+    fn test_derived_metadata() {
+        let metadata = Order::metadata();
+
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            json!({
+                "name": "Order",
+                "constraints": [{"code": "custom", "value": "check_owner"}],
+                "fields": [
+                    {
+                        "name": "ref",
+                        "type": "String",
+                        "required": true,
+                        "constraints": [{"code": "pattern", "value": r"^[A-Z]{3}\d+$"}]
+                    },
+                    {
+                        "name": "lines",
+                        "type": "Vec<Line>",
+                        "required": true,
+                        "constraints": [
+                            {"code": "min_items", "value": 1},
+                            {"code": "max_items", "value": 10},
+                            {"code": "unique_items", "value": true}
+                        ],
+                        "nested": {
+                            "name": "Line",
+                            "constraints": [],
+                            "fields": [
+                                {
+                                    "name": "unit",
+                                    "label": "Unit",
+                                    "type": "String",
+                                    "required": true,
+                                    "constraints": [
+                                        {"code": "enumerate", "value": ["kg", "pcs"]}
+                                    ]
+                                },
+                                {
+                                    "name": "amount",
+                                    "type": "Option<u32>",
+                                    "required": false,
+                                    "constraints": [
+                                        {"code": "minimum", "value": 1},
+                                        {"code": "exclusive_maximum", "value": 100}
+                                    ]
+                                }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "note",
+                        "type": "String",
+                        "required": false,
+                        "constraints": []
+                    }
+                ]
+            })
+        );
+        assert_eq!(metadata.field("lines.unit").unwrap().label, Some("Unit"));
+        assert!(metadata.field("internal").is_none());
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = MetadataRegistry::new()
+            .register::<Order>()
+            .register::<Line>();
+
+        assert_eq!(registry.get("Line").unwrap().fields.len(), 2);
+        assert_eq!(
+            registry
+                .iter()
+                .map(|metadata| metadata.name)
+                .collect::<Vec<_>>(),
+            ["Order", "Line"]
+        );
+    }
+
+    #[test]
+    fn test_constraints_by_path() {
+        let constraints = constraints_of::<Order>();
+
+        assert_eq!(
+            constraints
+                .iter()
+                .map(|constraint| format!("{} {}", constraint.path, constraint.kind))
+                .collect::<Vec<_>>(),
+            [
+                " custom",
+                "ref pattern",
+                "lines min_items",
+                "lines max_items",
+                "lines unique_items",
+                "lines.*.unit enumerate",
+                "lines.*.amount minimum",
+                "lines.*.amount exclusive_maximum",
+            ]
+        );
+        assert!(constraints.contains("lines.*.amount", "minimum"));
+        assert!(!constraints.contains("lines.*.amount", "maximum"));
+        assert_eq!(
+            constraints.at("lines.*.unit").next().unwrap().parameters,
+            json!(["kg", "pcs"])
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_multipart::Multipart;
+use actix_web::{
+    dev::{Decompress, Payload},
+    error::PayloadError,
+    web::{Bytes, BytesMut},
+    FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ConstraintKind, QueryConfig};
+
+/// A file part of a [`AppMultipart`] body, read into memory.
+#[derive(Debug, Clone)]
+pub struct MultipartFile {
+    /// The name of the form field.
+    pub field: String,
+    /// The file name given by the client, if any.
+    pub file_name: Option<String>,
+    pub content_type: Option<mime::Mime>,
+    pub bytes: Bytes,
+}
+
+/// Extract a validated `multipart/form-data` body: its text parts are deserialized into
+/// `T` and validated like the fields of an [`AppForm`](crate::AppForm), with errors in
+/// the same shape as [`AppJson`](crate::AppJson), and its file parts are kept aside.
+/// Requires the `multipart` feature.
+///
+/// ```
+/// use actix_json_validator::AppMultipart;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Avatar { #[validate(max_length = 80)] caption: String }
+///
+/// async fn upload(upload: AppMultipart<Avatar>) -> String {
+///     let size: usize = upload.files.iter().map(|file| file.bytes.len()).sum();
+///     format!("{}: {size} bytes", upload.caption)
+/// }
+/// ```
+///
+/// The whole body, files included, counts against the
+/// [`limit`](crate::JsonConfig::limit) of the [`JsonConfig`](crate::JsonConfig). Text
+/// parts given more than once fill list fields configured on [`QueryConfig`].
+#[derive(Debug)]
+pub struct AppMultipart<T> {
+    pub data: T,
+    pub files: Vec<MultipartFile>,
+}
+
+impl<T> AppMultipart<T> {
+    /// Deconstruct to the inner value and the files
+    pub fn into_parts(self) -> (T, Vec<MultipartFile>) {
+        (self.data, self.files)
+    }
+}
+
+impl<T> Deref for AppMultipart<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T> FromRequest for AppMultipart<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let query_config = req.app_data::<QueryConfig>().cloned().unwrap_or_default();
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                let is_multipart = matches!(
+                    req.mime_type(),
+                    Ok(Some(mime)) if mime.essence_str() == mime::MULTIPART_FORM_DATA.essence_str()
+                );
+                if !is_multipart {
+                    extract::fail(&req, ConstraintKind::ContentType);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "content_type",
+                        "Content type error".to_string(),
+                    ));
+                }
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let payload = Decompress::from_headers(payload, req.headers());
+                let mut multipart = Multipart::new(req.headers(), payload);
+                let mut text = form_urlencoded::Serializer::new(String::new());
+                let mut files = Vec::new();
+                let mut size = 0;
+                while let Some(field) = multipart.next().await {
+                    let mut field = field.map_err(|err| {
+                        extract::fail(&req, ConstraintKind::Payload);
+                        extract::decode_error(&config, type_name, "payload", err.to_string())
+                    })?;
+                    let name = field.name().unwrap_or_default().to_string();
+                    let file_name = field
+                        .content_disposition()
+                        .and_then(|disposition| disposition.get_filename())
+                        .map(str::to_string);
+                    let content_type = field.content_type().cloned();
+
+                    let mut bytes = BytesMut::new();
+                    while let Some(chunk) = field.next().await {
+                        let chunk = chunk.map_err(|err| {
+                            extract::fail(&req, ConstraintKind::Payload);
+                            extract::decode_error(&config, type_name, "payload", err.to_string())
+                        })?;
+                        size += chunk.len();
+                        if size > config.limit {
+                            return Err(extract::body_error(
+                                &config,
+                                &req,
+                                type_name,
+                                PayloadError::Overflow,
+                            ));
+                        }
+                        bytes.extend_from_slice(&chunk);
+                    }
+
+                    match file_name {
+                        None => {
+                            let value = std::str::from_utf8(&bytes).map_err(|_| {
+                                extract::fail(&req, ConstraintKind::Deserialization);
+                                let message = format!("Multipart part `{name}` is not UTF-8");
+                                extract::decode_error(
+                                    &config,
+                                    type_name,
+                                    "deserialization",
+                                    message,
+                                )
+                            })?;
+                            text.append_pair(&name, value);
+                        }
+                        Some(file_name) => files.push(MultipartFile {
+                            field: name,
+                            file_name: Some(file_name),
+                            content_type,
+                            bytes: bytes.freeze(),
+                        }),
+                    }
+                }
+                extract::soft_limit(&config, &req, size);
+
+                let data = query_config
+                    .deserialize::<T>(&text.finish())
+                    .map_err(|err| {
+                        extract::fail(&req, ConstraintKind::Deserialization);
+                        let message = format!("Multipart deserialize error: {err}");
+                        extract::decode_error(&config, type_name, "deserialization", message)
+                    })?;
+                let data = extract::validate(data, &config, &req, type_name)?;
+                Ok(AppMultipart { data, files })
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonConfig;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Avatar {
+        #[validate(min_length = 3)]
+        caption: String,
+        #[validate(maximum = 5)]
+        rating: u8,
+    }
+
+    async fn extract(config: JsonConfig, caption: &str) -> Result<AppMultipart<Avatar>, Value> {
+        let body = format!(
+            "--X\r\n\
+             Content-Disposition: form-data; name=\"caption\"\r\n\r\n\
+             {caption}\r\n\
+             --X\r\n\
+             Content-Disposition: form-data; name=\"rating\"\r\n\r\n\
+             4\r\n\
+             --X\r\n\
+             Content-Disposition: form-data; name=\"image\"; filename=\"cat.png\"\r\n\
+             Content-Type: image/png\r\n\r\n\
+             PNG...\r\n\
+             --X--\r\n"
+        );
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .insert_header(("content-type", "multipart/form-data; boundary=X"))
+            .set_payload(body)
+            .to_http_parts();
+        AppMultipart::<Avatar>::from_request(&req, &mut payload)
+            .await
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_text_parts_are_validated() {
+        let upload = extract(JsonConfig::default(), "Whiskers").await.unwrap();
+        assert_eq!((upload.caption.as_str(), upload.rating), ("Whiskers", 4));
+        assert_eq!(upload.files.len(), 1);
+        assert_eq!(upload.files[0].field, "image");
+        assert_eq!(upload.files[0].file_name.as_deref(), Some("cat.png"));
+        assert_eq!(upload.files[0].content_type, Some(mime::IMAGE_PNG));
+        assert_eq!(upload.files[0].bytes, "PNG...");
+
+        assert_eq!(
+            extract(JsonConfig::default(), "Wh").await.unwrap_err(),
+            json!({"caption": ["The length of the value must be `>= 3`."]})
+        );
+        assert_eq!(
+            extract(JsonConfig::default().limit(10), "Whiskers")
+                .await
+                .unwrap_err(),
+            json!({"error": ["payload reached size limit"]})
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::{ops::Deref, panic};
+
+use actix_web::{dev::Payload, web::Bytes, FromRequest, HttpRequest};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde_valid::{validation::Errors as ValidationErrors, Validate};
+
+use crate::{
+    extract,
+    json::{self, Parsed, Parser, Streamed},
+    stream, AppError, JsonConfig,
+};
+
+/// Like [`AppJson`](crate::AppJson), but large bodies are deserialized and validated on
+/// the blocking thread pool, past [`JsonConfig::blocking_threshold`], or parsed there as
+/// they arrive, past [`JsonConfig::streaming_threshold`], so multi-megabyte imports do
+/// not stall the other requests of the same worker:
+///
+/// ```
+/// use actix_json_validator::{JsonConfig, OffloadedAppJson};
+/// use actix_web::App;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Import { #[validate(max_items = 100_000)] rows: Vec<u32> }
+///
+/// async fn import(rows: OffloadedAppJson<Import>) -> String {
+///     format!("{} rows imported", rows.rows.len())
+/// }
+///
+/// let app = App::new().app_data(JsonConfig::default().blocking_threshold(1024 * 1024));
+/// ```
+///
+/// Responses are the same as those of [`AppJson`](crate::AppJson), which parses every
+/// body on the worker; the payload type must be `Send` to cross threads.
+#[derive(Debug)]
+pub struct OffloadedAppJson<T>(pub T);
+
+impl<T> OffloadedAppJson<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for OffloadedAppJson<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for OffloadedAppJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for OffloadedAppJson<T>
+where
+    T: DeserializeOwned + Validate + Send + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        json::extract_with::<T, Offloaded>(req, payload, extract::config(req))
+            .map(|result| result.map(OffloadedAppJson))
+            .boxed_local()
+    }
+}
+
+/// Parse bodies past the configured thresholds on the blocking thread pool.
+pub(crate) struct Offloaded;
+
+impl<T: DeserializeOwned + Send + 'static> Parser<T> for Offloaded {
+    const OFFLOADS: bool = true;
+
+    fn buffered<V>(
+        body: Bytes,
+        threshold: usize,
+        collect: bool,
+        validate: V,
+    ) -> LocalBoxFuture<'static, Parsed<T>>
+    where
+        V: Fn(&T) -> Result<(), ValidationErrors> + Send + 'static,
+    {
+        if body.len() < threshold {
+            return Box::pin(async move { json::parse(body, collect, validate) });
+        }
+        Box::pin(async move {
+            let parse = move || json::parse(body, collect, validate);
+            match actix_web::rt::task::spawn_blocking(parse).await {
+                Ok(result) => result,
+                Err(err) => panic::resume_unwind(err.into_panic()),
+            }
+        })
+    }
+
+    fn streamed<V>(
+        req: &HttpRequest,
+        config: &JsonConfig,
+        payload: Payload,
+        validate: V,
+    ) -> LocalBoxFuture<'static, Streamed<T>>
+    where
+        V: Fn(&T) -> Result<(), ValidationErrors> + Send + 'static,
+    {
+        let req = req.clone();
+        let config = config.clone();
+        Box::pin(async move { stream::parse_streamed(&req, &config, payload, validate).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppJson;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::{marker::PhantomData, rc::Rc};
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Tags {
+        #[validate(min_items = 1)]
+        tags: Vec<String>,
+        #[serde(skip)]
+        owner: PhantomData<Rc<()>>,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Import {
+        #[validate(max_items = 3)]
+        rows: Vec<u32>,
+    }
+
+    #[actix_web::test]
+    async fn test_app_json_takes_payloads_that_are_not_send() {
+        // `Rc` cannot cross threads, so thresholds are left to `OffloadedAppJson`.
+        let config = JsonConfig::default()
+            .blocking_threshold(0)
+            .streaming_threshold(0);
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config.clone())
+            .set_json(json!({"tags": ["spicy"]}))
+            .to_http_parts();
+        let tags = AppJson::<Tags>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(tags.tags, ["spicy"]);
+
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"tags": []}))
+            .to_http_parts();
+        let err = AppJson::<Tags>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.error_response().into_body().try_into_bytes().unwrap(),
+            json!({"tags": ["The length of the items must be `>= 1`."]}).to_string()
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_offloaded_responses_match_app_json() {
+        for config in [
+            JsonConfig::default().blocking_threshold(0),
+            JsonConfig::default().streaming_threshold(0),
+        ] {
+            for body in [json!({"rows": [1, 2]}), json!({"rows": [1, 2, 3, 4]})] {
+                let (req, mut payload) = test::TestRequest::post()
+                    .app_data(config.clone())
+                    .set_json(&body)
+                    .to_http_parts();
+                let offloaded = OffloadedAppJson::<Import>::from_request(&req, &mut payload)
+                    .await
+                    .map(|import| import.into_inner().rows)
+                    .map_err(|err| err.error_response().into_body().try_into_bytes().unwrap());
+
+                let (req, mut payload) = test::TestRequest::post().set_json(&body).to_http_parts();
+                let inline = AppJson::<Import>::from_request(&req, &mut payload)
+                    .await
+                    .map(|import| import.into_inner().rows)
+                    .map_err(|err| err.error_response().into_body().try_into_bytes().unwrap());
+                assert_eq!(offloaded, inline);
+            }
+        }
+    }
+}
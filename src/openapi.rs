@@ -0,0 +1,365 @@
+use serde_json::{json, Map, Value};
+
+use crate::{debug::DEBUG_KEY, metadata::FieldMetadata, JsonConfig, TypeMetadata};
+
+/// Version of the OpenAPI specification to generate schemas for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenApiVersion {
+    /// OpenAPI 3.0.x, whose schemas are an extended subset of JSON Schema.
+    V3_0,
+    /// OpenAPI 3.1.x, whose schemas are JSON Schema 2020-12.
+    #[default]
+    V3_1,
+}
+
+/// Where a parameter described by [`TypeMetadata::parameters`] is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+    /// The query string, as read by [`AppQuery`](crate::AppQuery).
+    Query,
+    /// The matched path segments, as read by [`AppParams`](crate::AppParams).
+    Path,
+}
+
+impl JsonConfig {
+    /// The OpenAPI schemas of the error responses produced with this configuration, to
+    /// embed under `components.schemas` of a specification:
+    ///
+    /// ```
+    /// use actix_json_validator::{JsonConfig, OpenApiVersion};
+    /// use serde_json::json;
+    ///
+    /// let config = JsonConfig::default().error_docs("https://docs.example.com/errors/");
+    /// let spec = json!({
+    ///     "openapi": "3.1.0",
+    ///     "components": {"schemas": config.error_schemas(OpenApiVersion::V3_1)},
+    /// });
+    /// ```
+    ///
+    /// The error body is `ValidationErrors`, whose messages are `ValidationMessage`s. The
+    /// schemas follow the options that change the body, such as
+    /// [`error_docs`](Self::error_docs) and [`debug_errors`](Self::debug_errors), but know
+    /// nothing of the bodies built by a custom [`error_handler`](Self::error_handler).
+    pub fn error_schemas(&self, version: OpenApiVersion) -> Value {
+        let message = if self.documents_errors() {
+            json!({
+                "type": "object",
+                "required": ["message", "type"],
+                "properties": {
+                    "message": {"type": "string"},
+                    "type": {
+                        "type": "string",
+                        "format": "uri",
+                        "description": "Documentation of the failed constraint, as in RFC 7807."
+                    }
+                }
+            })
+        } else {
+            json!({"type": "string"})
+        };
+
+        let mut errors = json!({
+            "type": "object",
+            "description": "Validation errors by field. Values are the messages of a field, \
+                or the errors of a nested object or array keyed by property or index. \
+                Errors of the payload as a whole are under `non_field_errors`, and \
+                unreadable payloads are reported under `error`.",
+            "additionalProperties": {
+                "oneOf": [
+                    {
+                        "type": "array",
+                        "items": {"$ref": "#/components/schemas/ValidationMessage"}
+                    },
+                    {"$ref": "#/components/schemas/ValidationErrors"}
+                ]
+            }
+        });
+        if self.debug_errors {
+            errors["properties"] = json!({
+                DEBUG_KEY: {
+                    "type": "object",
+                    "description": "Diagnostics about the failure, for development only.",
+                    "additionalProperties": true
+                }
+            });
+        }
+
+        let example = json!({"name": [self.example_message()]});
+        match version {
+            OpenApiVersion::V3_0 => errors["example"] = example,
+            OpenApiVersion::V3_1 => errors["examples"] = json!([example]),
+        }
+
+        let mut schemas = Map::new();
+        schemas.insert("ValidationErrors".to_string(), errors);
+        schemas.insert("ValidationMessage".to_string(), message);
+        Value::Object(schemas)
+    }
+
+    fn example_message(&self) -> Value {
+        let message = "The length of the value must be `>= 3`.";
+        if !self.documents_errors() {
+            return json!(message);
+        }
+        let url = self.doc_url("min_length");
+        json!({"message": message, "type": url.as_deref().unwrap_or("about:blank")})
+    }
+}
+
+impl TypeMetadata {
+    /// The OpenAPI schema of the type, e.g. for a request body, with its constraints
+    /// expressed as schema keywords (`minLength`, `maximum`, `pattern`, `enum`, ...):
+    ///
+    /// ```
+    /// use actix_json_validator::{Describe, OpenApiVersion};
+    /// # #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+    /// # struct Signup { #[validate(min_length = 3)] name: String }
+    ///
+    /// let schema = Signup::metadata().schema(OpenApiVersion::V3_1);
+    /// assert_eq!(schema["properties"]["name"]["minLength"], 3);
+    /// ```
+    ///
+    /// Field types are mapped from their Rust names, so types the schema cannot know
+    /// about, such as `custom` validations or types with their own `Deserialize`, are
+    /// left unconstrained.
+    pub fn schema(&self, version: OpenApiVersion) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for field in &self.fields {
+            properties.insert(field.name.to_string(), field_schema(field, version));
+            if field.required {
+                required.push(field.name);
+            }
+        }
+
+        let mut schema = json!({"type": "object", "properties": properties});
+        if !required.is_empty() {
+            schema["required"] = json!(required);
+        }
+        schema
+    }
+
+    /// The OpenAPI parameter objects of the type's fields, for a query string or path
+    /// extracted into it, with the same constraint keywords as [`schema`](Self::schema).
+    pub fn parameters(&self, location: ParameterLocation, version: OpenApiVersion) -> Vec<Value> {
+        let location = match location {
+            ParameterLocation::Query => "query",
+            ParameterLocation::Path => "path",
+        };
+        self.fields
+            .iter()
+            .map(|field| {
+                json!({
+                    "name": field.name,
+                    "in": location,
+                    // Path parameters are always required by the specification.
+                    "required": field.required || location == "path",
+                    "schema": field_schema(field, version),
+                })
+            })
+            .collect()
+    }
+}
+
+fn field_schema(field: &FieldMetadata, version: OpenApiVersion) -> Value {
+    let mut schema = type_schema(field.ty, field.nested.as_ref(), version);
+    for constraint in &field.constraints {
+        let target = if is_collection_constraint(constraint.code) {
+            &mut schema
+        } else {
+            // `serde_valid` applies scalar constraints to each element of a collection.
+            innermost(&mut schema)
+        };
+        apply(target, constraint.code, &constraint.value, version);
+    }
+    if let Some(label) = field.label {
+        schema["title"] = json!(label);
+    }
+    schema
+}
+
+/// The schema of a Rust type name as written, e.g. `Option<Vec<String>>`.
+fn type_schema(ty: &str, nested: Option<&TypeMetadata>, version: OpenApiVersion) -> Value {
+    let (name, argument) = match ty.split_once('<') {
+        Some((name, rest)) => (name, rest.strip_suffix('>')),
+        None => (ty, None),
+    };
+    let name = name.rsplit("::").next().unwrap_or(name);
+
+    match (name, argument) {
+        ("Option", Some(inner)) => nullable(type_schema(inner, nested, version), version),
+        ("Box" | "Rc" | "Arc", Some(inner)) => type_schema(inner, nested, version),
+        ("Vec" | "ParVec" | "VecDeque" | "HashSet" | "BTreeSet" | "IndexSet", Some(inner)) => {
+            json!({"type": "array", "items": type_schema(inner, nested, version)})
+        }
+        ("HashMap" | "BTreeMap" | "IndexMap", Some(arguments)) => {
+            let value = arguments.split_once(',').map_or("", |(_, value)| value);
+            json!({"type": "object", "additionalProperties": type_schema(value, nested, version)})
+        }
+        ("String" | "str" | "&str" | "char", None) => json!({"type": "string"}),
+        ("bool", None) => json!({"type": "boolean"}),
+        ("f32" | "f64", None) => json!({"type": "number"}),
+        (
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+            | "isize",
+            None,
+        ) => json!({"type": "integer"}),
+        _ => nested.map_or_else(|| json!({}), |nested| nested.schema(version)),
+    }
+}
+
+fn nullable(mut schema: Value, version: OpenApiVersion) -> Value {
+    match version {
+        OpenApiVersion::V3_0 => schema["nullable"] = json!(true),
+        OpenApiVersion::V3_1 => {
+            if let Some(ty) = schema.get("type").cloned() {
+                schema["type"] = json!([ty, "null"]);
+            }
+        }
+    }
+    schema
+}
+
+fn is_collection_constraint(code: &str) -> bool {
+    matches!(
+        code,
+        "min_items" | "max_items" | "unique_items" | "min_properties" | "max_properties"
+    )
+}
+
+/// The schema of the elements of a (possibly nested) array schema.
+fn innermost(schema: &mut Value) -> &mut Value {
+    if schema.get("items").is_some() {
+        innermost(&mut schema["items"])
+    } else {
+        schema
+    }
+}
+
+fn apply(schema: &mut Value, code: &str, value: &Value, version: OpenApiVersion) {
+    let keyword = match code {
+        "exclusive_minimum" | "exclusive_maximum" => {
+            let (bound, exclusive) = match code {
+                "exclusive_minimum" => ("minimum", "exclusiveMinimum"),
+                _ => ("maximum", "exclusiveMaximum"),
+            };
+            match version {
+                // OpenAPI 3.0 marks the bound itself as exclusive with a boolean.
+                OpenApiVersion::V3_0 => {
+                    schema[bound] = value.clone();
+                    schema[exclusive] = json!(true);
+                }
+                OpenApiVersion::V3_1 => schema[exclusive] = value.clone(),
+            }
+            return;
+        }
+        "minimum" => "minimum",
+        "maximum" => "maximum",
+        "multiple_of" => "multipleOf",
+        "min_length" => "minLength",
+        "max_length" => "maxLength",
+        "pattern" => "pattern",
+        "min_items" => "minItems",
+        "max_items" => "maxItems",
+        "unique_items" => "uniqueItems",
+        "min_properties" => "minProperties",
+        "max_properties" => "maxProperties",
+        "enumerate" => "enum",
+        _ => return,
+    };
+    schema[keyword] = value.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Describe;
+
+    #[test]
+    fn test_error_schemas_follow_config() {
+        let schemas = JsonConfig::default().error_schemas(OpenApiVersion::V3_0);
+        assert_eq!(schemas["ValidationMessage"], json!({"type": "string"}));
+        assert_eq!(
+            schemas["ValidationErrors"]["example"],
+            json!({"name": ["The length of the value must be `>= 3`."]})
+        );
+        assert!(schemas["ValidationErrors"].get("properties").is_none());
+
+        let schemas = JsonConfig::default()
+            .error_docs("https://docs.example.com/errors/")
+            .debug_errors(true)
+            .error_schemas(OpenApiVersion::V3_1);
+        assert_eq!(
+            schemas["ValidationMessage"]["required"],
+            json!(["message", "type"])
+        );
+        assert_eq!(
+            schemas["ValidationErrors"]["examples"][0]["name"][0]["type"],
+            "https://docs.example.com/errors/min_length"
+        );
+        assert!(schemas["ValidationErrors"]["properties"]["_debug"].is_object());
+    }
+
+    #[derive(serde::Deserialize, serde_valid::Validate, crate::Describe)]
+    #[allow(dead_code)]
+    struct Search {
+        #[validate(min_length = 2)]
+        #[validate(pattern = "^[a-z]+$")]
+        #[app_json(label = "Search terms")]
+        q: String,
+        #[validate(max_items = 3)]
+        #[validate(enumerate = ["new", "used"])]
+        conditions: Vec<String>,
+        #[validate(exclusive_minimum = 0)]
+        #[validate(maximum = 100)]
+        page_size: Option<u32>,
+    }
+
+    #[test]
+    fn test_constraints_become_schema_keywords() {
+        let schema = Search::metadata().schema(OpenApiVersion::V3_1);
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "q": {
+                        "type": "string",
+                        "title": "Search terms",
+                        "minLength": 2,
+                        "pattern": "^[a-z]+$"
+                    },
+                    "conditions": {
+                        "type": "array",
+                        "maxItems": 3,
+                        "items": {"type": "string", "enum": ["new", "used"]}
+                    },
+                    "page_size": {
+                        "type": ["integer", "null"],
+                        "exclusiveMinimum": 0,
+                        "maximum": 100
+                    }
+                },
+                "required": ["q", "conditions"]
+            })
+        );
+
+        let parameters =
+            Search::metadata().parameters(ParameterLocation::Query, OpenApiVersion::V3_0);
+        assert_eq!(
+            parameters[2],
+            json!({
+                "name": "page_size",
+                "in": "query",
+                "required": false,
+                "schema": {
+                    "type": "integer",
+                    "nullable": true,
+                    "minimum": 0,
+                    "exclusiveMinimum": true,
+                    "maximum": 100
+                }
+            })
+        );
+    }
+}
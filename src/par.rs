@@ -0,0 +1,158 @@
+use std::ops::{Deref, DerefMut};
+
+use indexmap::IndexMap;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_valid::{
+    validation::{
+        ArrayErrors, Errors as ValidationError, ValidateMaxItems, ValidateMinItems,
+        ValidateUniqueItems,
+    },
+    MaxItemsError, MinItemsError, UniqueItemsError, Validate,
+};
+
+/// Items validated by a single task, so small collections stay on the calling thread.
+const MIN_ITEMS_PER_TASK: usize = 1024;
+
+/// A `Vec` whose items are validated in parallel on the rayon thread pool when it is
+/// large, for imports with tens of thousands of rows. Requires the `rayon` feature.
+///
+/// Use it in place of `Vec` on a field validated with a bare `#[validate]`; failures are
+/// reported by index exactly as for a `Vec`:
+///
+/// ```
+/// use actix_json_validator::ParVec;
+/// use serde::Deserialize;
+/// use serde_valid::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Import {
+///     #[validate(max_items = 100_000)]
+///     #[validate]
+///     rows: ParVec<Row>,
+/// }
+///
+/// #[derive(Deserialize, Validate)]
+/// struct Row {
+///     #[validate(min_length = 1)]
+///     sku: String,
+/// }
+/// ```
+///
+/// Validation still blocks the worker until all items are checked; extract it with
+/// [`OffloadedAppJson`](crate::OffloadedAppJson) and
+/// [`JsonConfig::blocking_threshold`](crate::JsonConfig::blocking_threshold) to keep the
+/// worker responsive meanwhile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ParVec<T>(pub Vec<T>);
+
+impl<T> ParVec<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for ParVec<T> {
+    fn from(items: Vec<T>) -> Self {
+        ParVec(items)
+    }
+}
+
+impl<T> Deref for ParVec<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ParVec<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T: Validate + Sync> Validate for ParVec<T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let failures: Vec<(usize, ValidationError)> = self
+            .0
+            .par_iter()
+            .with_min_len(MIN_ITEMS_PER_TASK)
+            .enumerate()
+            .filter_map(|(index, item)| item.validate().err().map(|errors| (index, errors)))
+            .collect();
+        if failures.is_empty() {
+            return Ok(());
+        }
+        let items: IndexMap<usize, ValidationError> = failures.into_iter().collect();
+        Err(ValidationError::Array(ArrayErrors::new(Vec::new(), items)))
+    }
+}
+
+impl<T> ValidateMinItems for ParVec<T> {
+    fn validate_min_items(&self, min_items: usize) -> Result<(), MinItemsError> {
+        self.0.validate_min_items(min_items)
+    }
+}
+
+impl<T> ValidateMaxItems for ParVec<T> {
+    fn validate_max_items(&self, max_items: usize) -> Result<(), MaxItemsError> {
+        self.0.validate_max_items(max_items)
+    }
+}
+
+impl<T: Eq + std::hash::Hash + std::fmt::Debug> ValidateUniqueItems for ParVec<T> {
+    fn validate_unique_items(&self) -> Result<(), UniqueItemsError> {
+        self.0.validate_unique_items()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::format_errors;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Row {
+        #[validate(min_length = 1)]
+        sku: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Import {
+        #[validate(max_items = 3)]
+        #[validate]
+        rows: ParVec<Row>,
+    }
+
+    #[test]
+    fn test_errors_match_vec() {
+        let rows: Vec<_> = (0..5000)
+            .map(|index| json!({"sku": if index % 1500 == 7 { "" } else { "A1" }}))
+            .collect();
+        let import: Import = serde_json::from_value(json!({"rows": rows})).unwrap();
+
+        let sequential = Vec::from_iter(import.rows.iter().map(|row| Row {
+            sku: row.sku.clone(),
+        }));
+        assert_eq!(
+            json!(format_errors(import.rows.validate().unwrap_err())),
+            json!(format_errors(sequential.validate().unwrap_err()))
+        );
+        assert_eq!(
+            json!(format_errors(import.validate().unwrap_err())),
+            json!({
+                "rows": {
+                    "non_field_errors": ["The length of the items must be `<= 3`."],
+                    "7": {"sku": ["The length of the value must be `>= 1`."]},
+                    "1507": {"sku": ["The length of the value must be `>= 1`."]},
+                    "3007": {"sku": ["The length of the value must be `>= 1`."]},
+                    "4507": {"sku": ["The length of the value must be `>= 1`."]}
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::time::Instant;
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::future::{ready, Ready};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use serde_valid::Validate;
+
+use crate::{extract, path::extract_path, query::extract_query, AppError, ErrorMap};
+
+/// Options for [`AppParams`], registered as app data.
+#[derive(Clone)]
+pub struct ParamsConfig {
+    path_key: Option<String>,
+    query_key: Option<String>,
+}
+
+impl ParamsConfig {
+    /// Report path and query errors under the given keys instead of `"path"` and `"query"`.
+    pub fn keys(mut self, path_key: impl Into<String>, query_key: impl Into<String>) -> Self {
+        self.path_key = Some(path_key.into());
+        self.query_key = Some(query_key.into());
+        self
+    }
+
+    /// Report path and query errors side by side at the top level of the error body.
+    pub fn merged(mut self) -> Self {
+        self.path_key = None;
+        self.query_key = None;
+        self
+    }
+}
+
+impl Default for ParamsConfig {
+    fn default() -> Self {
+        ParamsConfig {
+            path_key: Some("path".to_string()),
+            query_key: Some("query".to_string()),
+        }
+    }
+}
+
+/// Extract and validate both the path parameters (`P`) and the query string (`Q`) of a
+/// request in one go.
+///
+/// Both parts are always checked so that a single response lists every problem; errors
+/// appear under `"path"` and `"query"` (see [`ParamsConfig`]):
+///
+/// ```json
+/// {"path": {"id": ["The number must be `>= 1`."]}, "query": {"page": ["..."]}}
+/// ```
+#[derive(Debug)]
+pub struct AppParams<P, Q> {
+    pub path: P,
+    pub query: Q,
+}
+
+impl<P, Q> AppParams<P, Q> {
+    /// Deconstruct to the inner path and query values
+    pub fn into_inner(self) -> (P, Q) {
+        (self.path, self.query)
+    }
+}
+
+impl<P, Q> FromRequest for AppParams<P, Q>
+where
+    P: DeserializeOwned + Validate + 'static,
+    Q: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let params_config = req.app_data::<ParamsConfig>().cloned().unwrap_or_default();
+        let type_name = std::any::type_name::<Self>();
+        let started = Instant::now();
+
+        let path = extract_path::<P>(req, &config, std::any::type_name::<P>());
+        let query = extract_query::<Q>(req, &config, std::any::type_name::<Q>());
+
+        let result = match (path, query) {
+            (Ok(path), Ok(query)) => Ok(AppParams { path, query }),
+            (path, query) => {
+                let mut errors = ErrorMap::new();
+                let parts = [
+                    (params_config.path_key, path.err()),
+                    (params_config.query_key, query.err()),
+                ];
+                for (key, err) in parts {
+                    if let Some(AppError::ValidationError(map)) = err {
+                        merge(&mut errors, key, map);
+                    }
+                }
+                Err(AppError::ValidationError(errors))
+            }
+        };
+
+        ready(extract::finish(&config, req, type_name, started, result))
+    }
+}
+
+fn merge(errors: &mut ErrorMap, key: Option<String>, map: ErrorMap) {
+    match key {
+        Some(key) => {
+            errors.insert(key, json!(map));
+        }
+        None => errors.extend(map),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, http::StatusCode, test, web, App, ResponseError};
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct FoodPath {
+        #[validate(minimum = 1)]
+        id: u32,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Expand {
+        #[validate(max_length = 5)]
+        expand: String,
+    }
+
+    async fn detail(params: AppParams<FoodPath, Expand>) -> String {
+        format!("{}:{}", params.path.id, params.query.expand)
+    }
+
+    #[actix_web::test]
+    async fn test_valid_params() {
+        let app = test::init_service(App::new().route("/foods/{id}", web::get().to(detail))).await;
+        let req = test::TestRequest::get()
+            .uri("/foods/7?expand=tags")
+            .to_request();
+
+        let body = test::call_and_read_body(&app, req).await;
+
+        assert_eq!(body, "7:tags");
+    }
+
+    #[actix_web::test]
+    async fn test_path_and_query_errors_are_prefixed() {
+        let app = test::init_service(App::new().route("/foods/{id}", web::get().to(detail))).await;
+        let req = test::TestRequest::get()
+            .uri("/foods/0?expand=everything")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(
+            body,
+            json!({
+                "path": {"id": ["The number must be `>= 1`."]},
+                "query": {"expand": ["The length of the value must be `<= 5`."]}
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_merged_error_keys() {
+        let (req, mut payload) = test::TestRequest::get()
+            .app_data(ParamsConfig::default().merged())
+            .uri("/foods/0?expand=tags")
+            .param("id", "0")
+            .to_http_parts();
+
+        let res = AppParams::<FoodPath, Expand>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            body,
+            json!({"id": ["The number must be `>= 1`."]}).to_string()
+        );
+    }
+}
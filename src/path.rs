@@ -0,0 +1,174 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_router::PathDeserializer;
+use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpRequest};
+use futures_util::future::{ready, Ready};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{extract, AppError, ConstraintKind, JsonConfig};
+
+/// Options for the path parameters of [`AppPath`] and [`AppParams`](crate::AppParams),
+/// registered as app data.
+///
+/// API style guides disagree on whether a malformed resource address is a
+/// `404 Not Found` or a `400 Bad Request`; both failures respond with `400` and the
+/// usual error body by default:
+///
+/// ```
+/// use actix_json_validator::PathConfig;
+///
+/// // `/orders/abc` and `/orders/0` are not found, rather than bad requests.
+/// let config = PathConfig::default()
+///     .not_found_on_mismatch(true)
+///     .not_found_on_violation(true);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PathConfig {
+    mismatch_not_found: bool,
+    violation_not_found: bool,
+}
+
+impl PathConfig {
+    /// Respond with `404 Not Found` when a segment does not parse as its field's type.
+    pub fn not_found_on_mismatch(mut self, enabled: bool) -> Self {
+        self.mismatch_not_found = enabled;
+        self
+    }
+
+    /// Respond with `404 Not Found` when the parameters fail validation.
+    pub fn not_found_on_violation(mut self, enabled: bool) -> Self {
+        self.violation_not_found = enabled;
+        self
+    }
+}
+
+/// Deserialize and validate the request's matched path segments.
+pub(crate) fn extract_path<T>(
+    req: &HttpRequest,
+    config: &JsonConfig,
+    type_name: &str,
+) -> Result<T, AppError>
+where
+    T: DeserializeOwned + Validate,
+{
+    let path_config = req.app_data::<PathConfig>().cloned().unwrap_or_default();
+    let not_found = |enabled: bool| {
+        if enabled {
+            extract::escalate(req, StatusCode::NOT_FOUND);
+        }
+    };
+    let data = T::deserialize(PathDeserializer::new(req.match_info())).map_err(|err| {
+        not_found(path_config.mismatch_not_found);
+        extract::fail(req, ConstraintKind::Deserialization);
+        let message = format!("Path deserialize error: {err}");
+        extract::decode_error(config, type_name, "deserialization", message)
+    })?;
+    extract::validate(data, config, req, type_name)
+        .inspect_err(|_| not_found(path_config.violation_not_found))
+}
+
+/// Extract typed information from the request's path segments and validate it with
+/// `serde_valid`, returning errors in the same shape as [`AppJson`](crate::AppJson).
+/// See [`PathConfig`] for the status of failures.
+#[derive(Debug)]
+pub struct AppPath<T>(pub T);
+
+impl<T> AppPath<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppPath<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppPath<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppPath<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+
+        let result = extract_path(req, &config, type_name).map(AppPath);
+
+        ready(extract::finish(&config, req, type_name, started, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use actix_web::{test, web, App, HttpMessage};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct OrderPath {
+        #[validate(minimum = 1)]
+        id: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_failure_statuses() {
+        async fn status(config: PathConfig, uri: &str) -> StatusCode {
+            let app = test::init_service(App::new().app_data(config).route(
+                "/orders/{id}",
+                web::get().to(|path: AppPath<OrderPath>| async move { path.id.to_string() }),
+            ))
+            .await;
+            let req = test::TestRequest::get().uri(uri).to_request();
+            test::call_service(&app, req).await.status()
+        }
+
+        let strict = PathConfig::default()
+            .not_found_on_mismatch(true)
+            .not_found_on_violation(true);
+        for (uri, default, not_found) in [
+            ("/orders/7", StatusCode::OK, StatusCode::OK),
+            (
+                "/orders/abc",
+                StatusCode::BAD_REQUEST,
+                StatusCode::NOT_FOUND,
+            ),
+            ("/orders/0", StatusCode::BAD_REQUEST, StatusCode::NOT_FOUND),
+        ] {
+            assert_eq!(status(PathConfig::default(), uri).await, default);
+            assert_eq!(status(strict.clone(), uri).await, not_found);
+        }
+        let mismatch_only = PathConfig::default().not_found_on_mismatch(true);
+        assert_eq!(
+            status(mismatch_only, "/orders/0").await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_mismatch_is_a_deserialization_failure() {
+        let (req, mut payload) = test::TestRequest::get().param("id", "abc").to_http_parts();
+        AppPath::<OrderPath>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+}
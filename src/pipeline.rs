@@ -0,0 +1,163 @@
+use std::time::Instant;
+
+use actix_web::{dev::Payload, web::Bytes, HttpRequest};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{extract, AppError, JsonConfig};
+
+/// The stages of a JSON extraction as building blocks, for extractors that need one
+/// stage of their own, such as decrypting the body, and the crate's behavior for the
+/// rest:
+///
+/// ```
+/// use actix_json_validator::{AppError, Pipeline};
+/// use actix_web::{dev::Payload, FromRequest, HttpRequest};
+/// use futures_util::future::LocalBoxFuture;
+/// use serde::de::DeserializeOwned;
+/// use serde_valid::Validate;
+/// # fn decrypt(body: &[u8]) -> Option<Vec<u8>> { Some(body.to_vec()) }
+///
+/// struct Sealed<T>(T);
+///
+/// impl<T: DeserializeOwned + Validate + 'static> FromRequest for Sealed<T> {
+///     type Error = AppError;
+///     type Future = LocalBoxFuture<'static, Result<Self, AppError>>;
+///
+///     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+///         let pipeline = Pipeline::new::<T>(req);
+///         let payload = payload.take();
+///         Box::pin(async move {
+///             let result = async {
+///                 let body = pipeline.collect(payload).await?;
+///                 let body = decrypt(&body).ok_or_else(|| {
+///                     pipeline.reject("decryption", "The body could not be decrypted")
+///                 })?;
+///                 let data = pipeline.deserialize::<T>(&body)?;
+///                 pipeline.validate(data)
+///             }
+///             .await;
+///             pipeline.finish(result).map(Sealed)
+///         })
+///     }
+/// }
+/// ```
+///
+/// The stages use the [`JsonConfig`] of the request, and their failures end up in the
+/// [`ValidationReport`](crate::ValidationReport) and go through the error handler once
+/// passed to [`finish`](Pipeline::finish), as with [`AppJson`](crate::AppJson).
+#[derive(Clone)]
+pub struct Pipeline {
+    config: JsonConfig,
+    req: HttpRequest,
+    type_name: &'static str,
+    started: Instant,
+}
+
+impl Pipeline {
+    /// Start the extraction of a `T` from `req`.
+    pub fn new<T>(req: &HttpRequest) -> Self {
+        Pipeline {
+            config: extract::config(req),
+            req: req.clone(),
+            type_name: std::any::type_name::<T>(),
+            started: Instant::now(),
+        }
+    }
+
+    /// The configuration of the extraction.
+    pub fn config(&self) -> &JsonConfig {
+        &self.config
+    }
+
+    /// Collect the body: check its content type, reserve room in the memory budget while
+    /// reading it, enforce the size limit, and apply the
+    /// [`transform_body`](JsonConfig::transform_body) hook, if any.
+    pub async fn collect(&self, payload: Payload) -> Result<Bytes, AppError> {
+        extract::json_body(&self.config, &self.req, payload, self.type_name).await
+    }
+
+    /// Deserialize a collected body.
+    pub fn deserialize<T: DeserializeOwned>(&self, body: &[u8]) -> Result<T, AppError> {
+        extract::deserialize(&self.config, &self.req, self.type_name, body)
+    }
+
+    /// Validate a deserialized payload, with the configured severities, business code
+    /// statuses and error formatting.
+    pub fn validate<T: Validate>(&self, data: T) -> Result<T, AppError> {
+        extract::validate(data, &self.config, &self.req, self.type_name)
+    }
+
+    /// The error of a custom stage named `step`, reported under `"error"` like those of
+    /// the body.
+    pub fn reject(&self, step: &str, message: impl Into<String>) -> AppError {
+        extract::decode_error(&self.config, self.type_name, step, message.into())
+    }
+
+    /// Record the [`ValidationReport`](crate::ValidationReport) of the extraction, then
+    /// pass any error through the configured error handler.
+    pub fn finish<T>(&self, result: Result<T, AppError>) -> Result<T, AppError> {
+        extract::finish(
+            &self.config,
+            &self.req,
+            self.type_name,
+            self.started,
+            result,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConstraintKind, ValidationReport};
+    use actix_web::{body::MessageBody, test::TestRequest, HttpMessage, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Note {
+        #[validate(min_length = 3)]
+        title: String,
+    }
+
+    async fn extract(body: &'static str) -> (Result<Note, AppError>, HttpRequest) {
+        let (req, mut payload) = TestRequest::post()
+            .insert_header(("content-type", "application/json"))
+            .set_payload(body)
+            .to_http_parts();
+        let pipeline = Pipeline::new::<Note>(&req);
+        let result = async {
+            let body = pipeline.collect(payload.take()).await?;
+            // A custom stage: bodies arrive reversed.
+            let body: Vec<u8> = body.iter().rev().copied().collect();
+            if body.is_empty() {
+                return Err(pipeline.reject("reverse", "Nothing to reverse"));
+            }
+            let data = pipeline.deserialize::<Note>(&body)?;
+            pipeline.validate(data)
+        }
+        .await;
+        (pipeline.finish(result), req)
+    }
+
+    #[actix_web::test]
+    async fn test_custom_stage_between_builtin_ones() {
+        let (note, _) = extract(r#"}"azziP" :"eltit"{"#).await;
+        assert_eq!(note.unwrap().title, "Pizza");
+
+        let (err, req) = extract(r#"}"iP" :"eltit"{"#).await;
+        let body = err.unwrap_err().error_response().into_body();
+        assert_eq!(
+            body.try_into_bytes().unwrap(),
+            json!({"title": ["The length of the value must be `>= 3`."]}).to_string()
+        );
+        assert!(req.extensions().get::<ValidationReport>().is_some());
+
+        let (err, req) = extract(r#"}"iP"{"#).await;
+        assert!(err.is_err());
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+}
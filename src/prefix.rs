@@ -0,0 +1,92 @@
+use actix_web::HttpRequest;
+
+use crate::JsonConfig;
+
+/// Settings for all the routes under a path prefix, registered as app data, so whole
+/// areas of an API get their own limits without a [`JsonConfig`] on every route:
+///
+/// ```
+/// use actix_json_validator::{ConfigByPrefix, JsonConfig};
+/// use actix_web::App;
+///
+/// let app = App::new()
+///     .app_data(JsonConfig::default().limit(64 * 1024))
+///     .app_data(
+///         ConfigByPrefix::new()
+///             .prefix("/api/v1/imports/*", JsonConfig::default().limit(20 * 1024 * 1024))
+///             .prefix("/internal", JsonConfig::default().debug_errors(true)),
+///     );
+/// ```
+///
+/// A prefix matches whole path segments: `/api/v1/imports` covers `/api/v1/imports` and
+/// `/api/v1/imports/orders`, but not `/api/v1/imports-legacy`; a trailing `/*` is
+/// optional. The configs of the matching prefixes are layered over the request's config
+/// with [`JsonConfig::merge`], the longest prefix last, so the most specific settings
+/// win. A config inserted into the request extensions is used as it is.
+#[derive(Clone, Default)]
+pub struct ConfigByPrefix {
+    prefixes: Vec<(String, JsonConfig)>,
+}
+
+impl ConfigByPrefix {
+    /// Create an empty set of prefixes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Layer `config` over the config of the requests whose path starts with `prefix`.
+    pub fn prefix(mut self, prefix: impl Into<String>, config: JsonConfig) -> Self {
+        let prefix = prefix.into();
+        let prefix = prefix
+            .trim_end_matches('*')
+            .trim_end_matches('/')
+            .to_string();
+        self.prefixes.push((prefix, config));
+        self.prefixes.sort_by_key(|(prefix, _)| prefix.len());
+        self
+    }
+}
+
+/// Layer the configs of the prefixes registered for `req` over `config`.
+pub(crate) fn apply(config: JsonConfig, req: &HttpRequest) -> JsonConfig {
+    let Some(by_prefix) = req.app_data::<ConfigByPrefix>() else {
+        return config;
+    };
+    let path = req.path();
+    by_prefix
+        .prefixes
+        .iter()
+        .filter(|(prefix, _)| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .fold(config, |config, (_, overlay)| config.merge(overlay.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let by_prefix = ConfigByPrefix::new()
+            .prefix("/api/v1/imports/*", JsonConfig::default().limit(20_000_000))
+            .prefix("/api", JsonConfig::default().limit(1024).debug_errors(true));
+        let config = |path: &str| {
+            let req = TestRequest::post()
+                .uri(path)
+                .app_data(JsonConfig::default().limit(512))
+                .app_data(by_prefix.clone())
+                .to_http_request();
+            let config = extract::config(&req);
+            (config.limit, config.debug_errors)
+        };
+
+        assert_eq!(config("/api/v1/imports/orders"), (20_000_000, true));
+        assert_eq!(config("/api/v1/imports"), (20_000_000, true));
+        assert_eq!(config("/api/v1/imports-legacy"), (1024, true));
+        assert_eq!(config("/apis"), (512, false));
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_valid::{validation::Errors, Validate};
+
+thread_local! {
+    /// Timings of the [`profiled`] validators of the validation running on this thread.
+    static SCOPE: RefCell<Option<Vec<(&'static str, Duration)>>> = const { RefCell::new(None) };
+}
+
+/// Time spent validating payloads, by payload type and by [`profiled`] custom validator,
+/// to find the constraints that dominate request latency. Registered as app data:
+///
+/// ```
+/// use actix_json_validator::ValidationProfile;
+/// use actix_web::{web, App, HttpRequest, HttpResponse};
+///
+/// // Create once, outside the `HttpServer` factory, so workers share the timings.
+/// let profile = ValidationProfile::new();
+/// let app = App::new().app_data(profile.clone()).route(
+///     "/admin/profile",
+///     web::get().to(|req: HttpRequest| async move {
+///         let profile = req.app_data::<ValidationProfile>().unwrap();
+///         HttpResponse::Ok().json(profile.snapshot())
+///     }),
+/// );
+/// ```
+///
+/// `serde_valid` checks the built-in constraints of a type in one pass, so their time is
+/// only known as part of the type's `validate()`; custom validators are timed one by one
+/// when wrapped in [`profiled`].
+#[derive(Clone, Default)]
+pub struct ValidationProfile {
+    inner: Arc<Mutex<HashMap<String, Timing>>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Timing {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    name: String,
+    calls: u64,
+    total_us: u128,
+    mean_us: u128,
+    max_us: u128,
+}
+
+impl ValidationProfile {
+    /// Create an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The timings, hottest first. Custom validators are named after their payload type:
+    ///
+    /// ```json
+    /// [
+    ///     {"name": "CreateOrder", "calls": 1200, "total_us": 96000, "mean_us": 80, "max_us": 2100},
+    ///     {"name": "CreateOrder.sku", "calls": 5400, "total_us": 38000, "mean_us": 7, "max_us": 950}
+    /// ]
+    /// ```
+    pub fn snapshot(&self) -> serde_json::Value {
+        let timings = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut entries: Vec<Entry> = timings
+            .iter()
+            .map(|(name, timing)| Entry {
+                name: name.clone(),
+                calls: timing.calls,
+                total_us: timing.total.as_micros(),
+                mean_us: timing.total.as_micros() / u128::from(timing.calls.max(1)),
+                max_us: timing.max.as_micros(),
+            })
+            .collect();
+        entries.sort_by(|a, b| b.total_us.cmp(&a.total_us).then(a.name.cmp(&b.name)));
+        serde_json::to_value(entries).unwrap_or_default()
+    }
+
+    fn record(&self, name: String, elapsed: Duration) {
+        let mut timings = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let timing = timings.entry(name).or_default();
+        timing.calls += 1;
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+    }
+
+    /// Validate `data`, recording the time taken under `type_name`.
+    pub(crate) fn validate<T: Validate>(&self, data: &T, type_name: &str) -> Result<(), Errors> {
+        let outer = SCOPE.with(|scope| scope.borrow_mut().replace(Vec::new()));
+        let started = Instant::now();
+        let result = data.validate();
+        let elapsed = started.elapsed();
+        let validators = SCOPE.with(|scope| std::mem::replace(&mut *scope.borrow_mut(), outer));
+
+        self.record(type_name.to_string(), elapsed);
+        for (name, elapsed) in validators.unwrap_or_default() {
+            self.record(format!("{type_name}.{name}"), elapsed);
+        }
+        result
+    }
+}
+
+/// Time a custom validator for the [`ValidationProfile`] of the app, under `name`:
+///
+/// ```
+/// use actix_json_validator::profiled;
+/// use serde_valid::{validation::Error, Validate};
+///
+/// fn sku_format(sku: &str) -> Result<(), Error> {
+///     // An expensive pattern match.
+/// #   let _ = sku;
+///     Ok(())
+/// }
+///
+/// #[derive(serde::Deserialize, Validate)]
+/// struct CreateOrder {
+///     #[validate(custom = |value| profiled("sku", || sku_format(value)))]
+///     sku: String,
+/// }
+/// ```
+///
+/// Outside of a profiled extraction, `f` just runs.
+pub fn profiled<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    let started = Instant::now();
+    let result = f();
+    let elapsed = started.elapsed();
+    SCOPE.with(|scope| {
+        if let Some(validators) = scope.borrow_mut().as_mut() {
+            validators.push((name, elapsed));
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppJson;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::validation::Error;
+
+    fn code(code: &str) -> Result<(), Error> {
+        std::thread::sleep(Duration::from_millis(2));
+        match code.len() {
+            3 => Ok(()),
+            _ => Err(Error::Custom("Codes have three characters".to_string())),
+        }
+    }
+
+    #[derive(Deserialize, Validate)]
+    struct Shipment {
+        #[validate(min_length = 1)]
+        #[validate(custom = |value| profiled("origin", || code(value)))]
+        origin: String,
+        #[validate(custom = |value| profiled("destination", || code(value)))]
+        destination: String,
+    }
+
+    #[actix_web::test]
+    async fn test_records_types_and_validators() {
+        let profile = ValidationProfile::new();
+        let app = init_service(App::new().app_data(profile.clone()).route(
+            "/shipments",
+            web::post().to(|_: AppJson<Shipment>| async { HttpResponse::Ok().finish() }),
+        ))
+        .await;
+        for destination in ["OSL", "OSLO"] {
+            let req = TestRequest::post()
+                .uri("/shipments")
+                .set_json(json!({"origin": "LHR", "destination": destination}))
+                .to_request();
+            call_service(&app, req).await;
+        }
+
+        let snapshot = profile.snapshot();
+        let entries = snapshot.as_array().unwrap();
+        let names: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names[0], std::any::type_name::<Shipment>());
+        assert!(names.iter().any(|name| name.ends_with("Shipment.origin")));
+        assert!(names
+            .iter()
+            .any(|name| name.ends_with("Shipment.destination")));
+        assert!(entries.iter().all(|entry| entry["calls"] == 2));
+        assert!(entries[0]["total_us"].as_u64().unwrap() >= 8000);
+    }
+
+    #[test]
+    fn test_profiled_without_scope() {
+        assert_eq!(profiled("unscoped", || 42), 42);
+    }
+}
@@ -0,0 +1,297 @@
+mod pairs;
+
+use std::{collections::HashMap, ops::Deref, time::Instant};
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::future::{ready, Ready};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{extract, AppError, ConstraintKind, JsonConfig};
+
+/// Query string parsing options for [`AppQuery`], registered as app data.
+///
+/// Error handler and debug settings are shared with the other extractors and read
+/// from [`JsonConfig`].
+#[derive(Clone, Default)]
+pub struct QueryConfig {
+    #[cfg(feature = "qs")]
+    deep_objects: bool,
+    list_fields: HashMap<String, ListFormat>,
+}
+
+/// How clients encode a list-valued query parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    /// One value per occurrence: `?tag=a&tag=b`.
+    Repeated,
+    /// Comma-separated values, optionally repeated: `?tag=a,b&tag=c`.
+    CommaSeparated,
+}
+
+impl QueryConfig {
+    /// Parse nested bracket syntax (`filter[status]=active&filter[tags][0]=x`) into
+    /// nested structs and sequences using `serde_qs`. Disabled by default.
+    #[cfg(feature = "qs")]
+    pub fn deep_objects(mut self, enabled: bool) -> Self {
+        self.deep_objects = enabled;
+        self
+    }
+
+    /// Collect the query parameter `name` into a sequence field (e.g. `Vec<String>`) using
+    /// the given encoding. A parameter given once still yields a one-element list.
+    ///
+    /// Ignored when deep-object parsing (feature `qs`) is enabled.
+    pub fn list_field(mut self, name: impl Into<String>, format: ListFormat) -> Self {
+        self.list_fields.insert(name.into(), format);
+        self
+    }
+
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self, query: &str) -> Result<T, String> {
+        #[cfg(feature = "qs")]
+        if self.deep_objects {
+            return serde_qs::Config::new(5, false)
+                .deserialize_str(query)
+                .map_err(|err| err.to_string());
+        }
+
+        if self.list_fields.is_empty() {
+            serde_urlencoded::from_str(query).map_err(|err| err.to_string())
+        } else {
+            pairs::from_str(query, &self.list_fields).map_err(|err| err.to_string())
+        }
+    }
+}
+
+/// Deserialize and validate the request's query string.
+pub(crate) fn extract_query<T>(
+    req: &HttpRequest,
+    config: &JsonConfig,
+    type_name: &str,
+) -> Result<T, AppError>
+where
+    T: DeserializeOwned + Validate,
+{
+    let query_config = req.app_data::<QueryConfig>().cloned().unwrap_or_default();
+    let data = query_config
+        .deserialize::<T>(req.query_string())
+        .map_err(|err| {
+            extract::fail(req, ConstraintKind::Deserialization);
+            let message = format!("Query deserialize error: {err}");
+            extract::decode_error(config, type_name, "deserialization", message)
+        })?;
+    extract::validate(data, config, req, type_name)
+}
+
+/// Extract typed information from the request's query string and validate it with
+/// `serde_valid`, returning errors in the same shape as [`AppJson`](crate::AppJson).
+#[derive(Debug)]
+pub struct AppQuery<T>(pub T);
+
+impl<T> AppQuery<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppQuery<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppQuery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppQuery<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+
+        let result = extract_query(req, &config, type_name).map(AppQuery);
+
+        ready(extract::finish(&config, req, type_name, started, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use actix_web::{body::MessageBody, http::StatusCode, test, HttpMessage, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Pagination {
+        #[validate(minimum = 1)]
+        page: u32,
+        #[validate(maximum = 100)]
+        per_page: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_valid_query() {
+        let (req, mut payload) = test::TestRequest::get()
+            .uri("/foods?page=2&per_page=20")
+            .to_http_parts();
+
+        let query = AppQuery::<Pagination>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(query.page, 2);
+        assert_eq!(query.per_page, 20);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_query() {
+        let (req, mut payload) = test::TestRequest::get()
+            .uri("/foods?page=0&per_page=500")
+            .to_http_parts();
+
+        let res = AppQuery::<Pagination>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "page": ["The number must be `>= 1`."],
+                "per_page": ["The number must be `<= 100`."]
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_undeserializable_query_is_reported() {
+        let (req, mut payload) = test::TestRequest::get()
+            .uri("/foods?page=two&per_page=20")
+            .to_http_parts();
+
+        let res = AppQuery::<Pagination>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(res.status_code(), StatusCode::BAD_REQUEST);
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct TagFilter {
+        #[validate(min_length = 2)]
+        tag: Vec<String>,
+        #[validate(maximum = 100)]
+        limit: Option<u32>,
+    }
+
+    #[actix_web::test]
+    async fn test_repeated_and_comma_separated_lists() {
+        let config = QueryConfig::default().list_field("tag", ListFormat::CommaSeparated);
+        let (req, mut payload) = test::TestRequest::get()
+            .app_data(config)
+            .uri("/foods?tag=spicy,sweet&limit=10&tag=sour")
+            .to_http_parts();
+
+        let query = AppQuery::<TagFilter>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(query.tag, ["spicy", "sweet", "sour"]);
+        assert_eq!(query.limit, Some(10));
+
+        let config = QueryConfig::default().list_field("tag", ListFormat::Repeated);
+        let (req, mut payload) = test::TestRequest::get()
+            .app_data(config)
+            .uri("/foods?tag=a,b&tag=cd")
+            .to_http_parts();
+
+        let query = AppQuery::<TagFilter>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(query.tag, ["a,b", "cd"]);
+        assert_eq!(query.limit, None);
+    }
+
+    #[actix_web::test]
+    async fn test_list_item_errors_are_indexed() {
+        let config = QueryConfig::default().list_field("tag", ListFormat::Repeated);
+        let (req, mut payload) = test::TestRequest::get()
+            .app_data(config)
+            .uri("/foods?tag=spicy&tag=x&limit=500")
+            .to_http_parts();
+
+        let res = AppQuery::<TagFilter>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "tag": {"1": ["The length of the value must be `>= 2`."]},
+                "limit": ["The number must be `<= 100`."]
+            })
+        );
+    }
+
+    #[cfg(feature = "qs")]
+    #[actix_web::test]
+    async fn test_deep_object_query() {
+        #[derive(Debug, Deserialize, Validate)]
+        struct Search {
+            #[validate]
+            filter: Filter,
+        }
+
+        #[derive(Debug, Deserialize, Validate)]
+        struct Filter {
+            #[validate(enumerate = ["active", "archived"])]
+            status: String,
+            #[validate(max_items = 1)]
+            tags: Vec<String>,
+        }
+
+        let (req, mut payload) = test::TestRequest::get()
+            .app_data(QueryConfig::default().deep_objects(true))
+            .uri("/foods?filter[status]=deleted&filter[tags][0]=x&filter[tags][1]=y")
+            .to_http_parts();
+
+        let res = AppQuery::<Search>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = res.error_response().into_body().try_into_bytes().unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "filter": {
+                    "status": ["The value must be in [active, archived]."],
+                    "tags": ["The length of the items must be `<= 1`."]
+                }
+            })
+        );
+    }
+}
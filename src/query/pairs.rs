@@ -0,0 +1,140 @@
+//! Query string deserializer that collects configured parameters into sequences.
+
+use std::collections::HashMap;
+
+use serde::{
+    de::{
+        self,
+        value::{Error, MapDeserializer, SeqDeserializer},
+        IntoDeserializer, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+
+use super::ListFormat;
+
+pub(super) fn from_str<T: de::DeserializeOwned>(
+    query: &str,
+    list_fields: &HashMap<String, ListFormat>,
+) -> Result<T, Error> {
+    let mut entries: Vec<(String, PairValue)> = Vec::new();
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        let Some(format) = list_fields.get(key.as_ref()) else {
+            entries.push((key.into_owned(), PairValue::Single(value.into_owned())));
+            continue;
+        };
+
+        let position = entries.iter().position(|(existing, _)| *existing == key);
+        let index = position.unwrap_or_else(|| {
+            entries.push((key.into_owned(), PairValue::List(Vec::new())));
+            entries.len() - 1
+        });
+        let PairValue::List(items) = &mut entries[index].1 else {
+            unreachable!("list fields are always collected into lists")
+        };
+        match format {
+            ListFormat::Repeated => items.push(value.into_owned()),
+            ListFormat::CommaSeparated => items.extend(
+                value
+                    .split(',')
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string),
+            ),
+        }
+    }
+
+    T::deserialize(MapDeserializer::new(entries.into_iter()))
+}
+
+enum PairValue {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl<'de> IntoDeserializer<'de, Error> for PairValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self {
+                    PairValue::Single(value) => {
+                        visitor.$visit(value.parse().map_err(de::Error::custom)?)
+                    }
+                    list => list.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for PairValue {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            PairValue::Single(value) => visitor.visit_string(value),
+            PairValue::List(items) => {
+                SeqDeserializer::new(items.into_iter().map(PairValue::Single))
+                    .deserialize_any(visitor)
+            }
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            PairValue::Single(value) => PairValue::List(vec![value]).deserialize_any(visitor),
+            list => list.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            PairValue::Single(value) => visitor.visit_enum(value.into_deserializer()),
+            list => list.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
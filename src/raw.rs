@@ -0,0 +1,312 @@
+use std::ops::Deref;
+
+#[cfg(feature = "jsonschema")]
+use std::sync::Arc;
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value};
+
+use crate::{extract, AppError, FailureClass, Pipeline};
+
+/// Checks applied by [`AppRawJson`], registered as app data.
+///
+/// Limits, error handler and debug settings are read from [`JsonConfig`].
+#[derive(Clone, Default)]
+pub struct RawJsonConfig {
+    max_depth: Option<usize>,
+    #[cfg(feature = "jsonschema")]
+    schema: Option<Arc<jsonschema::Validator>>,
+}
+
+impl RawJsonConfig {
+    /// Reject bodies whose arrays and objects are nested more than `depth` levels deep,
+    /// before they are parsed.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Validate bodies against a JSON Schema. Fails if `schema` is not a valid schema.
+    /// Requires the `jsonschema` feature.
+    #[cfg(feature = "jsonschema")]
+    pub fn schema(mut self, schema: &Value) -> Result<Self, String> {
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|err| format!("invalid JSON Schema: {err}"))?;
+        self.schema = Some(Arc::new(validator));
+        Ok(self)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for Box<serde_json::value::RawValue> {}
+    impl Sealed for serde_json::Value {}
+}
+
+/// JSON types [`AppRawJson`] can extract: `Box<RawValue>` and `serde_json::Value`.
+pub trait RawJson: sealed::Sealed + DeserializeOwned + 'static {}
+
+impl RawJson for Box<RawValue> {}
+impl RawJson for Value {}
+
+/// Extract a JSON body without a payload type, for proxy-style endpoints that forward it
+/// untouched but must still reject garbage early.
+///
+/// The body must be well-formed JSON within the size limit of [`JsonConfig`], and pass
+/// the checks of [`RawJsonConfig`]: a maximum nesting depth and, with the `jsonschema`
+/// feature, a JSON Schema. Failures have the same shape as those of
+/// [`AppJson`](crate::AppJson), with schema violations reported at the path of the
+/// offending value.
+///
+/// ```
+/// use actix_json_validator::{AppRawJson, RawJsonConfig};
+/// use actix_web::{web, App};
+/// use serde_json::value::RawValue;
+///
+/// async fn forward(body: AppRawJson) -> String {
+///     // Forwarded byte for byte.
+///     body.get().to_string()
+/// }
+///
+/// let app = App::new()
+///     .app_data(RawJsonConfig::default().max_depth(16))
+///     .route("/events", web::post().to(forward));
+/// ```
+#[derive(Debug)]
+pub struct AppRawJson<T: RawJson = Box<RawValue>>(pub T);
+
+impl<T: RawJson> AppRawJson<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: RawJson> AsRef<T> for AppRawJson<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: RawJson> Deref for AppRawJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: RawJson> FromRequest for AppRawJson<T> {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let pipeline = Pipeline::new::<T>(req);
+        let raw_config = req.app_data::<RawJsonConfig>().cloned().unwrap_or_default();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                let body = pipeline.collect(payload).await?;
+
+                if let Some(max_depth) = raw_config.max_depth {
+                    if depth(&body) > max_depth {
+                        extract::classify(&req, FailureClass::Security);
+                        let message =
+                            format!("JSON nesting exceeds the maximum depth of {max_depth}");
+                        return Err(pipeline.reject("depth", message));
+                    }
+                }
+
+                let data = pipeline.deserialize::<T>(&body)?;
+
+                #[cfg(feature = "jsonschema")]
+                if let Some(schema) = &raw_config.schema {
+                    // Well-formed, as it just deserialized.
+                    let instance: Value = serde_json::from_slice(&body).unwrap_or_default();
+                    check_schema(schema, &instance, &req)?;
+                }
+
+                Ok(AppRawJson(data))
+            }
+            .await;
+
+            pipeline.finish(result)
+        })
+    }
+}
+
+/// The deepest nesting of arrays and objects in `body`, which does not need to be
+/// well-formed.
+fn depth(body: &[u8]) -> usize {
+    let (mut current, mut deepest) = (0usize, 0);
+    let (mut in_string, mut escaped) = (false, false);
+    for &byte in body {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                current += 1;
+                deepest = deepest.max(current);
+            }
+            b']' | b'}' => current = current.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+/// Report schema violations by the path of the offending value, like validation errors.
+#[cfg(feature = "jsonschema")]
+fn check_schema(
+    schema: &jsonschema::Validator,
+    instance: &Value,
+    req: &HttpRequest,
+) -> Result<(), AppError> {
+    use jsonschema::paths::LocationSegment;
+    use serde_json::Map;
+
+    let mut errors = Map::new();
+    for error in schema.iter_errors(instance) {
+        let path: Vec<String> = error
+            .instance_path()
+            .iter()
+            .map(|segment| match segment {
+                LocationSegment::Property(name) => name.into_owned(),
+                LocationSegment::Index(index) => index.to_string(),
+            })
+            .collect();
+        insert(&mut errors, &path, error.to_string());
+    }
+    if errors.is_empty() {
+        return Ok(());
+    }
+    extract::classify(req, FailureClass::Schema);
+    Err(AppError::ValidationError(errors.into_iter().collect()))
+}
+
+/// Add `message` to the messages at `path`, creating nested objects on the way. Messages
+/// of a value that also has nested errors go under `non_field_errors`.
+#[cfg(feature = "jsonschema")]
+fn insert(errors: &mut serde_json::Map<String, Value>, path: &[String], message: String) {
+    let (key, rest) = match path.split_first() {
+        Some((key, rest)) => (key.as_str(), rest),
+        None => ("non_field_errors", &[][..]),
+    };
+    let entry = errors.entry(key).or_insert_with(|| {
+        if rest.is_empty() {
+            Value::Array(Vec::new())
+        } else {
+            Value::Object(serde_json::Map::new())
+        }
+    });
+    match (entry, rest.is_empty()) {
+        (Value::Array(messages), true) => messages.push(Value::String(message)),
+        (Value::Object(nested), true) => insert(nested, &[], message),
+        (Value::Object(nested), false) => insert(nested, rest, message),
+        (entry, false) => {
+            let messages = entry.take();
+            let mut nested = serde_json::Map::new();
+            nested.insert("non_field_errors".to_string(), messages);
+            insert(&mut nested, rest, message);
+            *entry = Value::Object(nested);
+        }
+        (_, true) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonConfig;
+    use actix_web::{body::MessageBody, http::StatusCode, test, ResponseError};
+    use serde_json::json;
+
+    async fn extract<T: RawJson>(
+        req: test::TestRequest,
+        body: &'static str,
+    ) -> Result<AppRawJson<T>, AppError> {
+        let (req, mut payload) = req
+            .insert_header(("content-type", "application/json"))
+            .set_payload(body)
+            .to_http_parts();
+        AppRawJson::<T>::from_request(&req, &mut payload).await
+    }
+
+    fn body(err: AppError) -> Value {
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_raw_value_is_kept_verbatim() {
+        let raw = extract::<Box<RawValue>>(test::TestRequest::post(), r#"{"b": 1,  "a": [2]}"#)
+            .await
+            .unwrap();
+        assert_eq!(raw.get(), r#"{"b": 1,  "a": [2]}"#);
+
+        let err = extract::<Value>(test::TestRequest::post(), r#"{"b": 1"#)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(body(err)["error"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("Json deserialize error"));
+    }
+
+    #[actix_web::test]
+    async fn test_depth_and_size_limits() {
+        let req = test::TestRequest::post().app_data(RawJsonConfig::default().max_depth(2));
+        assert!(extract::<Value>(req, r#"{"a": ["[[[", 1]}"#).await.is_ok());
+
+        let req = test::TestRequest::post().app_data(RawJsonConfig::default().max_depth(2));
+        let err = extract::<Value>(req, r#"{"a": [[1]]}"#).await.unwrap_err();
+        assert_eq!(
+            body(err),
+            json!({"error": ["JSON nesting exceeds the maximum depth of 2"]})
+        );
+
+        let req = test::TestRequest::post().app_data(JsonConfig::default().limit(8));
+        assert!(extract::<Value>(req, r#"{"a": "too long"}"#).await.is_err());
+    }
+
+    #[cfg(feature = "jsonschema")]
+    #[actix_web::test]
+    async fn test_schema_violations_by_path() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+        let config = RawJsonConfig::default().schema(&schema).unwrap();
+        let req = test::TestRequest::post().app_data(config);
+
+        let err = extract::<Box<RawValue>>(req, r#"{"tags": ["ok", 3]}"#)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            body(err),
+            json!({
+                "non_field_errors": ["\"id\" is a required property"],
+                "tags": {"1": ["3 is not of type \"string\""]}
+            })
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::{ops::Deref, sync::Arc, time::Instant};
+
+use actix_web::{dev::Payload, http::StatusCode, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{unique, AppError, AppJson, Uniqueness, UniquenessChecker};
+
+/// Payloads with values that must refer to existing entities, checked by [`Referenced`]
+/// after validation.
+pub trait ReferenceFields {
+    /// The values to look up, by dotted path.
+    fn reference_fields(&self) -> Vec<(&'static str, String)>;
+}
+
+/// The [`UniquenessChecker`] used by [`Referenced`] to look up referenced values,
+/// registered as app data.
+#[derive(Clone)]
+pub struct References(Uniqueness);
+
+impl References {
+    /// Look values up with `checker`. Missing values are rejected with
+    /// `422 Unprocessable Entity` and the message "does not exist".
+    pub fn new(checker: impl UniquenessChecker) -> Self {
+        References(Uniqueness {
+            checker: Arc::new(checker),
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            message: "does not exist".to_string(),
+        })
+    }
+
+    /// Respond to missing values with `status` instead of `422 Unprocessable Entity`.
+    pub fn status(self, status: StatusCode) -> Self {
+        References(self.0.status(status))
+    }
+
+    /// Report missing values with `message` instead of "does not exist".
+    pub fn message(self, message: impl Into<String>) -> Self {
+        References(self.0.message(message))
+    }
+}
+
+/// Like [`AppJson`], then rejects payloads whose [`ReferenceFields`] do not exist
+/// according to the checker registered with [`References`], e.g. the category of a new
+/// product:
+///
+/// ```
+/// use actix_json_validator::{ReferenceFields, Referenced, References, UniquenessChecker};
+/// use actix_web::{web, App, HttpRequest};
+/// use futures_util::future::LocalBoxFuture;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Product { #[validate(min_length = 3)] name: String, category: u32 }
+///
+/// impl ReferenceFields for Product {
+///     fn reference_fields(&self) -> Vec<(&'static str, String)> {
+///         vec![("category", self.category.to_string())]
+///     }
+/// }
+///
+/// struct Categories;
+///
+/// impl UniquenessChecker for Categories {
+///     fn exists<'a>(
+///         &'a self,
+///         _field: &'a str,
+///         value: &'a str,
+///         _req: &'a HttpRequest,
+///     ) -> LocalBoxFuture<'a, Result<bool, actix_web::Error>> {
+///         // e.g. `SELECT EXISTS (SELECT 1 FROM categories WHERE id = $1)`
+///         Box::pin(async move { Ok(value == "1") })
+///     }
+/// }
+///
+/// async fn create_product(product: Referenced<Product>) -> String {
+///     product.name.clone()
+/// }
+///
+/// let app = App::new()
+///     .app_data(References::new(Categories))
+///     .route("/products", web::post().to(create_product));
+/// ```
+///
+/// Missing values are reported like validation errors, e.g.
+/// `{"category": ["does not exist"]}`, as [`Unique`](crate::Unique) reports taken ones.
+/// Failures of the checker itself, and a missing [`References`], respond with
+/// `500 Internal Server Error`.
+#[derive(Debug)]
+pub struct Referenced<T>(pub T);
+
+impl<T> Referenced<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for Referenced<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for Referenced<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Referenced<T>
+where
+    T: DeserializeOwned + Validate + ReferenceFields + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let started = Instant::now();
+        let extraction = AppJson::<T>::from_request(req, payload);
+        let references = req.app_data::<References>().cloned();
+        let req = req.clone();
+
+        Box::pin(async move {
+            let data = extraction.await?.into_inner();
+            let fields = data.reference_fields();
+            let lookup = references.as_ref().map(|references| &references.0);
+            unique::check::<T>(&req, started, lookup, fields, false).await?;
+            Ok(Referenced(data))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(minimum = 1)]
+        quantity: u32,
+        product: String,
+    }
+
+    impl ReferenceFields for Order {
+        fn reference_fields(&self) -> Vec<(&'static str, String)> {
+            vec![("product", self.product.clone())]
+        }
+    }
+
+    struct Products;
+
+    impl UniquenessChecker for Products {
+        fn exists<'a>(
+            &'a self,
+            _field: &'a str,
+            value: &'a str,
+            _req: &'a HttpRequest,
+        ) -> LocalBoxFuture<'a, Result<bool, actix_web::Error>> {
+            Box::pin(async move { Ok(value == "pizza") })
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_missing_references() {
+        let extract = |references: Option<References>, product: &str| {
+            let mut req =
+                test::TestRequest::post().set_json(json!({"quantity": 2, "product": product}));
+            if let Some(references) = references {
+                req = req.app_data(references);
+            }
+            let (req, mut payload) = req.to_http_parts();
+            async move { Referenced::<Order>::from_request(&req, &mut payload).await }
+        };
+
+        let references = References::new(Products)
+            .status(StatusCode::NOT_FOUND)
+            .message("is not on the menu");
+        assert_eq!(
+            extract(Some(references.clone()), "pizza")
+                .await
+                .unwrap()
+                .quantity,
+            2
+        );
+        let err = extract(Some(references), "sushi").await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            err.error_response().into_body().try_into_bytes().unwrap(),
+            json!({"product": ["is not on the menu"]}).to_string()
+        );
+
+        let err = extract(None, "pizza").await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
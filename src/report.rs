@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{debug::DEBUG_KEY, ErrorMap, Failure, FailureClass};
+
+/// Outcome of extracting a payload.
+///
+/// Every extractor of this crate stores a report in the request extensions once it has
+/// finished, whether the payload was accepted or not, so handlers, error handlers and
+/// middleware can inspect it. When a handler uses several extractors, the report of the
+/// last one to run is kept.
+///
+/// ```
+/// use actix_json_validator::{AppJson, ValidationReport};
+/// use actix_web::{HttpMessage, HttpRequest};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct CreateFood { name: String }
+///
+/// async fn create_food(food: AppJson<CreateFood>, req: HttpRequest) -> String {
+///     let extensions = req.extensions();
+///     let report = extensions.get::<ValidationReport>().unwrap();
+///     format!("validated in {:?}", report.elapsed)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    /// Rust type name of the extracted payload.
+    pub type_name: &'static str,
+    /// Errors, in the same format as the error response. Empty when the payload was accepted.
+    pub errors: ErrorMap,
+    /// Failures of constraints configured with [`Severity::Warning`], in the same format
+    /// as `errors`. These do not cause the payload to be rejected.
+    ///
+    /// [`Severity::Warning`]: crate::Severity::Warning
+    pub warnings: ErrorMap,
+    /// Failures of constraints configured with [`Severity::Info`](crate::Severity::Info).
+    pub info: ErrorMap,
+    /// Why the payload was rejected. `None` when it was accepted.
+    pub class: Option<FailureClass>,
+    /// What each error is about, for branching without matching on messages. Empty when
+    /// the payload was accepted.
+    pub failures: Vec<Failure>,
+    /// Size of the body when it exceeded
+    /// [`JsonConfig::soft_limit`](crate::JsonConfig::soft_limit).
+    pub oversized: Option<usize>,
+    /// Time spent reading, deserializing and validating the payload.
+    pub elapsed: Duration,
+}
+
+impl ValidationReport {
+    /// Whether the payload was accepted.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Number of individual error messages.
+    pub fn error_count(&self) -> usize {
+        count_messages(&self.errors)
+    }
+
+    /// Number of individual warning messages.
+    pub fn warning_count(&self) -> usize {
+        count_messages(&self.warnings)
+    }
+
+    /// Number of individual info messages.
+    pub fn info_count(&self) -> usize {
+        count_messages(&self.info)
+    }
+}
+
+/// A small summary of a [`ValidationReport`], stored in the request extensions next to
+/// it, for access logs and metrics middleware that run after the handler:
+///
+/// ```
+/// use actix_json_validator::ExtractionOutcome;
+/// use actix_web::{
+///     body::MessageBody,
+///     dev::{ServiceRequest, ServiceResponse},
+///     middleware::Next,
+///     HttpMessage,
+/// };
+///
+/// async fn access_log(
+///     req: ServiceRequest,
+///     next: Next<impl MessageBody>,
+/// ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+///     let res = next.call(req).await?;
+///     if let Some(outcome) = res.request().extensions().get::<ExtractionOutcome>() {
+///         log::info!(
+///             "{} errors={} size={:?} took={:?}",
+///             outcome.type_name, outcome.error_count, outcome.payload_size, outcome.elapsed
+///         );
+///     }
+///     Ok(res)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractionOutcome {
+    /// Rust type name of the extracted payload.
+    pub type_name: &'static str,
+    /// Time spent reading, deserializing and validating the payload.
+    pub elapsed: Duration,
+    /// Number of individual error messages, `0` when the payload was accepted.
+    pub error_count: usize,
+    /// Size of the body in bytes, as read or, when the body was not buffered, as
+    /// announced by `Content-Length`. `None` for extractors that read no body.
+    pub payload_size: Option<usize>,
+}
+
+impl ExtractionOutcome {
+    pub(crate) fn of(report: &ValidationReport, payload_size: Option<usize>) -> Self {
+        ExtractionOutcome {
+            type_name: report.type_name,
+            elapsed: report.elapsed,
+            error_count: report.error_count(),
+            payload_size,
+        }
+    }
+
+    /// Whether the payload was accepted.
+    pub fn is_valid(&self) -> bool {
+        self.error_count == 0
+    }
+}
+
+fn count_messages(map: &ErrorMap) -> usize {
+    fn count(value: &Value) -> usize {
+        match value {
+            Value::String(_) => 1,
+            // A message linked to its documentation.
+            Value::Object(message) if message.get("message").is_some_and(Value::is_string) => 1,
+            Value::Array(values) => values.iter().map(count).sum(),
+            Value::Object(map) => map.values().map(count).sum(),
+            _ => 0,
+        }
+    }
+
+    map.iter()
+        .filter(|(key, _)| *key != DEBUG_KEY)
+        .map(|(_, value)| count(value))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, JsonConfig};
+    use actix_service::Service;
+    use actix_web::{
+        error::ErrorBadRequest,
+        http::{header::HeaderName, StatusCode},
+        test, web, App, HttpMessage, HttpRequest,
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Food {
+        #[validate(min_length = 3)]
+        name: String,
+        #[validate(maximum = 10)]
+        rating: u8,
+    }
+
+    async fn create(_food: AppJson<Food>, req: HttpRequest) -> String {
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        format!("{}:{}", report.is_valid(), report.type_name)
+    }
+
+    #[actix_web::test]
+    async fn test_report_available_in_handler() {
+        let app = test::init_service(App::new().route("/", web::post().to(create))).await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"name": "Pizza", "rating": 9}))
+            .to_request();
+
+        let body = test::call_and_read_body(&app, req).await;
+
+        assert_eq!(body, format!("true:{}", std::any::type_name::<Food>()));
+    }
+
+    #[actix_web::test]
+    async fn test_report_available_in_error_handler() {
+        let config = JsonConfig::default().error_handler(|err, req, _type_name| {
+            let report = req.extensions().get::<ValidationReport>().cloned().unwrap();
+            assert!(!report.is_valid());
+            assert_eq!(report.warning_count(), 0);
+            ErrorBadRequest(format!("{} errors: {err:?}", report.error_count()))
+        });
+        let app = test::init_service(
+            App::new()
+                .app_data(config)
+                .route("/", web::post().to(create)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"name": "Pi", "rating": 11}))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(resp).await;
+        assert!(body.starts_with(b"2 errors"));
+    }
+
+    #[actix_web::test]
+    async fn test_outcome_available_after_handler() {
+        let app = test::init_service(
+            App::new()
+                .wrap_fn(|req, srv| {
+                    let res = srv.call(req);
+                    async move {
+                        let mut res = res.await?;
+                        let outcome = res
+                            .request()
+                            .extensions()
+                            .get::<ExtractionOutcome>()
+                            .copied();
+                        let outcome = outcome.unwrap();
+                        let value = format!("{}:{:?}", outcome.error_count, outcome.payload_size);
+                        res.headers_mut()
+                            .insert(HeaderName::from_static("x-outcome"), value.parse().unwrap());
+                        Ok(res)
+                    }
+                })
+                .route("/", web::post().to(create)),
+        )
+        .await;
+        let body = json!({"name": "Pi", "rating": 11}).to_string();
+        let req = test::TestRequest::post()
+            .uri("/")
+            .insert_header(("content-type", "application/json"))
+            .set_payload(body.clone())
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+
+        let outcome = resp.headers().get("x-outcome").unwrap();
+        assert_eq!(outcome, &format!("2:Some({})", body.len()));
+    }
+}
@@ -0,0 +1,277 @@
+use std::{ops::Deref, sync::Arc, time::Instant};
+
+use actix_web::{dev::Payload, error::ErrorInternalServerError, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeSeed;
+use serde_valid::Validate;
+
+use crate::{extract, AppError, ConstraintKind};
+
+type Parse<T> = dyn Fn(&HttpRequest, &[u8]) -> Result<T, serde_json::Error> + Send + Sync;
+
+/// Creates the [`DeserializeSeed`] that [`AppSeededJson<T>`] parses a body with, from the
+/// request. Registered as app data, one per payload type.
+pub struct JsonSeed<T> {
+    parse: Arc<Parse<T>>,
+}
+
+impl<T: 'static> JsonSeed<T> {
+    /// Parse bodies with the seed `seed` creates for each request, e.g. from the tenant
+    /// resolved by middleware.
+    pub fn new<S, F>(seed: F) -> Self
+    where
+        F: Fn(&HttpRequest) -> S + Send + Sync + 'static,
+        S: for<'de> DeserializeSeed<'de, Value = T>,
+    {
+        JsonSeed {
+            parse: Arc::new(move |req, body| {
+                let mut deserializer = serde_json::Deserializer::from_slice(body);
+                let value = seed(req).deserialize(&mut deserializer)?;
+                deserializer.end()?;
+                Ok(value)
+            }),
+        }
+    }
+}
+
+impl<T> Clone for JsonSeed<T> {
+    fn clone(&self) -> Self {
+        JsonSeed {
+            parse: Arc::clone(&self.parse),
+        }
+    }
+}
+
+/// Extractor for payload types that need request context to be deserialized, such as
+/// enums whose variants depend on the tenant or IDs interned in a per-app table. The body
+/// is parsed with the seed of the [`JsonSeed<T>`] registered as app data, then validated
+/// like [`AppJson`](crate::AppJson):
+///
+/// ```
+/// use actix_json_validator::{AppSeededJson, JsonSeed};
+/// use actix_web::{web, App, HttpRequest};
+/// use serde::de::{DeserializeSeed, Deserializer, Error};
+/// use serde::Deserialize;
+/// use serde_valid::Validate;
+///
+/// #[derive(Validate)]
+/// struct Subscription {
+///     plan: String,
+///     #[validate(minimum = 1)]
+///     seats: u32,
+/// }
+///
+/// /// Only accepts the plans sold to the tenant.
+/// struct Plans(Vec<String>);
+///
+/// impl<'de> DeserializeSeed<'de> for Plans {
+///     type Value = Subscription;
+///
+///     fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Subscription, D::Error> {
+///         #[derive(Deserialize)]
+///         struct Draft { plan: String, seats: u32 }
+///
+///         let Draft { plan, seats } = Draft::deserialize(deserializer)?;
+///         if !self.0.contains(&plan) {
+///             return Err(D::Error::custom(format!("unknown plan `{plan}`")));
+///         }
+///         Ok(Subscription { plan, seats })
+///     }
+/// }
+///
+/// fn plans_of(_req: &HttpRequest) -> Vec<String> {
+///     vec!["basic".to_string()]
+/// }
+///
+/// async fn subscribe(subscription: AppSeededJson<Subscription>) -> String {
+///     subscription.plan.clone()
+/// }
+///
+/// let app = App::new()
+///     .app_data(JsonSeed::new(|req| Plans(plans_of(req))))
+///     .route("/subscriptions", web::post().to(subscribe));
+/// ```
+///
+/// Limits, error handler and debug settings are read from [`JsonConfig`](crate::JsonConfig).
+/// Without a registered `JsonSeed<T>`, extraction fails with `500 Internal Server Error`.
+#[derive(Debug)]
+pub struct AppSeededJson<T>(pub T);
+
+impl<T> AppSeededJson<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppSeededJson<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppSeededJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppSeededJson<T>
+where
+    T: Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let seed = req.app_data::<JsonSeed<T>>().cloned();
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                let Some(seed) = seed else {
+                    return Err(AppError::Handled(ErrorInternalServerError(format!(
+                        "no JsonSeed registered for {type_name}"
+                    ))));
+                };
+                let body = extract::json_body(&config, &req, payload, type_name).await?;
+
+                let data = (seed.parse)(&req, &body).map_err(|err| {
+                    extract::fail(&req, ConstraintKind::Deserialization);
+                    let message = format!("Json deserialize error: {err}");
+                    extract::decode_error(&config, type_name, "deserialization", message)
+                })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppSeededJson)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use actix_web::{body::MessageBody, http::StatusCode, test, HttpMessage, ResponseError};
+    use serde::{
+        de::{Deserializer, Error},
+        Deserialize,
+    };
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Validate)]
+    struct Subscription {
+        tenant: String,
+        plan: String,
+        #[validate(minimum = 1)]
+        seats: u32,
+    }
+
+    struct Tenant(String);
+
+    impl<'de> DeserializeSeed<'de> for Tenant {
+        type Value = Subscription;
+
+        fn deserialize<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Subscription, D::Error> {
+            #[derive(Deserialize)]
+            struct Draft {
+                plan: String,
+                seats: u32,
+            }
+
+            let Draft { plan, seats } = Draft::deserialize(deserializer)?;
+            let plans: &[&str] = match self.0.as_str() {
+                "acme" => &["basic", "gold"],
+                _ => &["basic"],
+            };
+            if !plans.contains(&plan.as_str()) {
+                return Err(D::Error::custom(format!("unknown plan `{plan}`")));
+            }
+            Ok(Subscription {
+                tenant: self.0,
+                plan,
+                seats,
+            })
+        }
+    }
+
+    async fn extract(tenant: &str, body: Value) -> Result<Subscription, AppError> {
+        let seed = JsonSeed::new(|req: &HttpRequest| {
+            let tenant = req.headers().get("x-tenant").unwrap().to_str().unwrap();
+            Tenant(tenant.to_string())
+        });
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(seed)
+            .insert_header(("x-tenant", tenant))
+            .set_json(body)
+            .to_http_parts();
+        AppSeededJson::<Subscription>::from_request(&req, &mut payload)
+            .await
+            .map(AppSeededJson::into_inner)
+    }
+
+    fn body(err: AppError) -> Value {
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_seed_depends_on_request() {
+        let subscription = extract("acme", json!({"plan": "gold", "seats": 3}))
+            .await
+            .unwrap();
+        assert_eq!(subscription.tenant, "acme");
+        assert_eq!(subscription.plan, "gold");
+
+        let err = extract("initech", json!({"plan": "gold", "seats": 3}))
+            .await
+            .unwrap_err();
+        assert!(body(err)["error"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("Json deserialize error: unknown plan `gold`"));
+
+        let err = extract("initech", json!({"plan": "basic", "seats": 0}))
+            .await
+            .unwrap_err();
+        assert_eq!(body(err), json!({"seats": ["The number must be `>= 1`."]}));
+    }
+
+    #[actix_web::test]
+    async fn test_seed_errors_are_deserialization_failures() {
+        let seed = JsonSeed::new(|_: &HttpRequest| Tenant("initech".to_string()));
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(seed)
+            .set_json(json!({"plan": "gold", "seats": 3}))
+            .to_http_parts();
+        AppSeededJson::<Subscription>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(report.failures[0].kind, ConstraintKind::Deserialization);
+    }
+
+    #[actix_web::test]
+    async fn test_missing_seed_is_a_server_error() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"plan": "basic", "seats": 1}))
+            .to_http_parts();
+        let err = AppSeededJson::<Subscription>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,226 @@
+//! Splitting validation failures by the severity configured with
+//! [`JsonConfig::severity`](crate::JsonConfig::severity).
+
+use indexmap::IndexMap;
+use serde_valid::validation::{ArrayErrors, Error, Errors as ValidationError, ObjectErrors};
+
+use crate::debug::{constraint_name, join};
+
+/// How seriously a failed constraint is taken.
+///
+/// Only [`Severity::Error`] failures reject the payload. Failures of the other levels
+/// are recorded in the [`ValidationReport`](crate::ValidationReport) and the request
+/// proceeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    /// Reject the payload. The default for every constraint.
+    #[default]
+    Error,
+    /// Accept the payload and report the failure under `warnings`.
+    Warning,
+    /// Accept the payload and report the failure under `info`.
+    Info,
+}
+
+/// Severity assigned to the failures at `path`, optionally only for one constraint.
+#[derive(Debug, Clone)]
+pub(crate) struct SeverityRule {
+    pub(crate) path: String,
+    pub(crate) constraint: Option<String>,
+    pub(crate) severity: Severity,
+}
+
+impl SeverityRule {
+    fn matches(&self, path: &str, constraint: &str) -> bool {
+        let constraint_matches = self
+            .constraint
+            .as_deref()
+            .is_none_or(|expected| expected == constraint);
+        constraint_matches && path_matches(&self.path, path)
+    }
+}
+
+/// Compare dotted paths segment by segment, `*` matching any single segment.
+pub(crate) fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('.');
+    let mut path = path.split('.');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(expected), Some(segment)) if expected == "*" || expected == segment => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Failures grouped by severity, indexed by `Severity as usize`.
+type Split<T> = [Option<T>; 3];
+
+/// Split a validation error tree into one tree per severity level.
+pub(crate) fn split(errors: ValidationError, rules: &[SeverityRule]) -> Split<ValidationError> {
+    split_errors(errors, "", rules)
+}
+
+fn split_errors(
+    errors: ValidationError,
+    path: &str,
+    rules: &[SeverityRule],
+) -> Split<ValidationError> {
+    match errors {
+        ValidationError::Array(array_errors) => {
+            split_array(array_errors, path, rules).map(|part| part.map(ValidationError::Array))
+        }
+        ValidationError::Object(object_errors) => {
+            split_object(object_errors, path, rules).map(|part| part.map(ValidationError::Object))
+        }
+        ValidationError::NewType(vec_errors) => split_vec(vec_errors, path, rules)
+            .map(|part| (!part.is_empty()).then_some(ValidationError::NewType(part))),
+    }
+}
+
+fn split_array(
+    array_errors: ArrayErrors,
+    path: &str,
+    rules: &[SeverityRule],
+) -> Split<ArrayErrors> {
+    let mut errors = split_vec(array_errors.errors, path, rules);
+    let mut items: [IndexMap<usize, ValidationError>; 3] = Default::default();
+    for (index, error) in array_errors.items {
+        let parts = split_errors(error, &join(path, &index.to_string()), rules);
+        for (level, part) in parts.into_iter().enumerate() {
+            if let Some(part) = part {
+                items[level].insert(index, part);
+            }
+        }
+    }
+    std::array::from_fn(|level| {
+        let errors = std::mem::take(&mut errors[level]);
+        let items = std::mem::take(&mut items[level]);
+        (!errors.is_empty() || !items.is_empty()).then(|| ArrayErrors::new(errors, items))
+    })
+}
+
+fn split_object(
+    object_errors: ObjectErrors,
+    path: &str,
+    rules: &[SeverityRule],
+) -> Split<ObjectErrors> {
+    let mut errors = split_vec(object_errors.errors, path, rules);
+    let mut properties: [IndexMap<String, ValidationError>; 3] = Default::default();
+    for (prop, error) in object_errors.properties {
+        let parts = split_errors(error, &join(path, &prop), rules);
+        for (level, part) in parts.into_iter().enumerate() {
+            if let Some(part) = part {
+                properties[level].insert(prop.clone(), part);
+            }
+        }
+    }
+    std::array::from_fn(|level| {
+        let errors = std::mem::take(&mut errors[level]);
+        let properties = std::mem::take(&mut properties[level]);
+        (!errors.is_empty() || !properties.is_empty())
+            .then(|| ObjectErrors::new(errors, properties))
+    })
+}
+
+fn split_vec(errors: Vec<Error>, path: &str, rules: &[SeverityRule]) -> [Vec<Error>; 3] {
+    let mut result: [Vec<Error>; 3] = Default::default();
+    for error in errors {
+        match error {
+            Error::Items(array_errors) => {
+                let parts = split_array(array_errors, path, rules);
+                for (level, part) in parts.into_iter().enumerate() {
+                    result[level].extend(part.map(Error::Items));
+                }
+            }
+            Error::Properties(object_errors) => {
+                let parts = split_object(object_errors, path, rules);
+                for (level, part) in parts.into_iter().enumerate() {
+                    result[level].extend(part.map(Error::Properties));
+                }
+            }
+            error => {
+                let constraint = constraint_name(&error);
+                let severity = rules
+                    .iter()
+                    .rev()
+                    .find(|rule| rule.matches(path, constraint))
+                    .map_or(Severity::Error, |rule| rule.severity);
+                result[severity as usize].push(error);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, JsonConfig, ValidationReport};
+    use actix_web::{test, FromRequest, HttpMessage};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Profile {
+        #[validate(min_length = 3)]
+        #[validate(max_length = 8)]
+        nickname: String,
+        #[validate(max_items = 2)]
+        #[validate(max_length = 4)]
+        tags: Vec<String>,
+    }
+
+    #[actix_web::test]
+    async fn test_lower_severities_do_not_reject() {
+        let config = JsonConfig::default()
+            .constraint_severity("nickname", "max_length", Severity::Warning)
+            .severity("tags.*", Severity::Info);
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"nickname": "a_very_long_name", "tags": ["spicy", "hot"]}))
+            .to_http_parts();
+
+        let profile = AppJson::<Profile>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(profile.nickname, "a_very_long_name");
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert!(report.is_valid());
+        assert_eq!(
+            json!(report.warnings),
+            json!({"nickname": ["The length of the value must be `<= 8`."]})
+        );
+        assert_eq!(
+            json!(report.info),
+            json!({"tags": {"0": ["The length of the value must be `<= 4`."]}})
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_errors_still_reject_alongside_warnings() {
+        let config = JsonConfig::default().severity("tags.*", Severity::Warning);
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"nickname": "ab", "tags": ["a", "b", "spicy"]}))
+            .to_http_parts();
+
+        AppJson::<Profile>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let extensions = req.extensions();
+        let report = extensions.get::<ValidationReport>().unwrap();
+        assert_eq!(
+            json!(report.errors),
+            json!({
+                "nickname": ["The length of the value must be `>= 3`."],
+                "tags": ["The length of the items must be `<= 2`."]
+            })
+        );
+        assert_eq!(report.warning_count(), 1);
+    }
+}
@@ -0,0 +1,104 @@
+//! Error bodies with every field of the payload type, see
+//! [`JsonConfig::constant_shape`](crate::JsonConfig::constant_shape).
+
+use serde_json::{Map, Value};
+
+use crate::{
+    metadata::{collection_depth, FieldMetadata},
+    ErrorMap, TypeMetadata,
+};
+
+/// Add the fields of `metadata` missing from `errors`, with no messages.
+pub(crate) fn fill(errors: &mut ErrorMap, metadata: &TypeMetadata) {
+    for field in &metadata.fields {
+        let value = errors
+            .entry(field.name.to_string())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        fill_value(value, field);
+    }
+}
+
+fn fill_object(errors: &mut Map<String, Value>, metadata: &TypeMetadata) {
+    for field in &metadata.fields {
+        let value = errors
+            .entry(field.name)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        fill_value(value, field);
+    }
+}
+
+/// Nested payloads that are not in a collection get all their fields too.
+fn fill_value(value: &mut Value, field: &FieldMetadata) {
+    let Some(nested) = field
+        .nested
+        .as_ref()
+        .filter(|_| collection_depth(field.ty) == 0)
+    else {
+        return;
+    };
+    match value {
+        Value::Array(messages) if messages.is_empty() => {
+            let mut errors = Map::new();
+            fill_object(&mut errors, nested);
+            *value = Value::Object(errors);
+        }
+        Value::Object(errors) => fill_object(errors, nested),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AppJson, Describe, JsonConfig};
+    use actix_web::{body::MessageBody, test, FromRequest, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate, Describe)]
+    #[allow(dead_code)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        name: String,
+        email: Option<String>,
+        #[validate]
+        address: Address,
+        #[validate]
+        contacts: Vec<Address>,
+    }
+
+    #[derive(Debug, Deserialize, Validate, Describe)]
+    #[allow(dead_code)]
+    struct Address {
+        #[validate(min_length = 2)]
+        city: String,
+        zip: String,
+    }
+
+    #[actix_web::test]
+    async fn test_every_field_is_present() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(JsonConfig::default().constant_shape::<Signup>())
+            .set_json(json!({
+                "name": "Al",
+                "address": {"city": "Lagos", "zip": "100001"},
+                "contacts": []
+            }))
+            .to_http_parts();
+
+        let err = AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({
+                "name": ["The length of the value must be `>= 3`."],
+                "email": [],
+                "address": {"city": [], "zip": []},
+                "contacts": []
+            })
+        );
+    }
+}
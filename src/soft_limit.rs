@@ -0,0 +1,84 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderValue},
+    middleware::Next,
+    HttpMessage,
+};
+
+use crate::ValidationReport;
+
+/// Middleware adding a `Warning: 299` header to responses to requests whose body exceeded
+/// [`JsonConfig::soft_limit`](crate::JsonConfig::soft_limit), for clients and proxies to
+/// log. Register it with [`from_fn`](actix_web::middleware::from_fn).
+pub async fn soft_limit_warning(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let mut res = next.call(req).await?;
+    let oversized = res
+        .request()
+        .extensions()
+        .get::<ValidationReport>()
+        .and_then(|report| report.oversized);
+    if let Some(length) = oversized {
+        let warning =
+            format!("299 - \"Request body of {length} bytes exceeds the soft size limit\"");
+        if let Ok(value) = HeaderValue::from_str(&warning) {
+            res.headers_mut().append(header::WARNING, value);
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, JsonConfig};
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web, App};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Note {
+        #[validate(min_length = 1)]
+        text: String,
+    }
+
+    #[actix_web::test]
+    async fn test_soft_and_hard_limits() {
+        let app = test::init_service(
+            App::new()
+                .app_data(JsonConfig::default().soft_limit(32).limit(64))
+                .wrap(from_fn(soft_limit_warning))
+                .route("/", web::post().to(|_: AppJson<Note>| async { "ok" })),
+        )
+        .await;
+        let send = |text: &str| {
+            test::TestRequest::post()
+                .uri("/")
+                .set_json(json!({"text": text}))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, send("short")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::WARNING).is_none());
+
+        let res = test::call_service(&app, send(&"a".repeat(40))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::WARNING).unwrap(),
+            "299 - \"Request body of 51 bytes exceeds the soft size limit\""
+        );
+
+        let res = test::call_service(&app, send(&"a".repeat(80))).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            test::read_body(res).await,
+            json!({"error": ["JSON payload (91 bytes) is larger than allowed (limit: 64 bytes)."]})
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,489 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use actix_web::{web, HttpRequest, HttpResponse, Resource};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{extract, severity::path_matches, ConstraintKind, JsonConfig, ValidationReport};
+
+/// Payload sizes kept for the percentiles of [`ValidationStats`].
+const SIZE_SAMPLES: usize = 1024;
+
+/// Counters of the extractions of an app, for environments without a metrics stack.
+/// Registered as app data, or with [`JsonConfig::stats`], and served as JSON by
+/// [`validation_stats_service`]:
+///
+/// ```
+/// use actix_json_validator::{validation_stats_service, ValidationStats};
+/// use actix_web::{web, App};
+///
+/// // Create once, outside the `HttpServer` factory, so workers share the counters.
+/// let stats = ValidationStats::new();
+/// let app = App::new()
+///     .app_data(stats.clone())
+///     .service(web::scope("/admin").service(validation_stats_service("/validation")));
+/// ```
+///
+/// Extractions and rejections are counted by route, rejections by path and constraint
+/// (or business code), with array indexes folded into `*`. Payload sizes come from the
+/// `Content-Length` header of the last 1024 requests that had one.
+///
+/// With [`sample_payloads`](ValidationStats::sample_payloads), accepted payloads are
+/// also summarized by field, to tell which new constraints existing clients would pass.
+#[derive(Clone, Default)]
+pub struct ValidationStats {
+    inner: Arc<Mutex<Stats>>,
+    sample_every: Option<u64>,
+}
+
+#[derive(Default, Serialize)]
+struct Stats {
+    extractions: u64,
+    rejected: u64,
+    routes: BTreeMap<String, RouteStats>,
+    #[serde(skip)]
+    sizes: VecDeque<usize>,
+}
+
+#[derive(Default, Serialize)]
+struct RouteStats {
+    extractions: u64,
+    rejected: u64,
+    failures: BTreeMap<String, BTreeMap<String, u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payloads: Option<Payloads>,
+}
+
+/// Aggregates of sampled payloads, never the values themselves.
+#[derive(Default, Serialize)]
+struct Payloads {
+    #[serde(skip)]
+    accepted: u64,
+    samples: u64,
+    fields: BTreeMap<String, FieldStats>,
+}
+
+#[derive(Default, Serialize)]
+struct FieldStats {
+    /// Samples having the field, at least once for fields in arrays.
+    present: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+    /// Characters of strings, items of arrays.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct Sizes {
+    samples: usize,
+    p50: usize,
+    p90: usize,
+    p99: usize,
+    max: usize,
+}
+
+impl ValidationStats {
+    /// Create empty counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Summarize one in `every` payloads accepted by each route: how often each field is
+    /// present, the range of numbers and the lengths of strings and arrays.
+    ///
+    /// ```
+    /// use actix_json_validator::{JsonConfig, ValidationStats};
+    ///
+    /// let stats = ValidationStats::new().sample_payloads(100);
+    /// // Only the presence of redacted fields is counted.
+    /// let config = JsonConfig::default().redact("password");
+    /// ```
+    ///
+    /// Sampled payloads are read whole before parsing, like audited ones, and their
+    /// summary is served under `"payloads"` of each route by [`snapshot`](Self::snapshot).
+    pub fn sample_payloads(mut self, every: u64) -> Self {
+        self.sample_every = Some(every.max(1));
+        self
+    }
+
+    /// The counters as served by [`validation_stats_service`]:
+    ///
+    /// ```json
+    /// {
+    ///     "extractions": 1520,
+    ///     "rejected": 37,
+    ///     "routes": {
+    ///         "POST /orders": {
+    ///             "extractions": 1200,
+    ///             "rejected": 30,
+    ///             "failures": {"lines.*.quantity": {"minimum": 22}, "": {"deserialization": 8}},
+    ///             "payloads": {
+    ///                 "samples": 12,
+    ///                 "fields": {
+    ///                     "lines": {"present": 12, "min_length": 1, "max_length": 40},
+    ///                     "lines.*.quantity": {"present": 12, "min": 1.0, "max": 250.0},
+    ///                     "coupon": {"present": 3, "min_length": 6, "max_length": 6}
+    ///                 }
+    ///             }
+    ///         }
+    ///     },
+    ///     "payload_sizes": {"samples": 1024, "p50": 312, "p90": 2048, "p99": 9113, "max": 30210}
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> serde_json::Value {
+        let stats = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut snapshot = serde_json::to_value(&*stats).unwrap_or_default();
+        let mut sizes: Vec<usize> = stats.sizes.iter().copied().collect();
+        sizes.sort_unstable();
+        let percentile = |p: usize| sizes[(sizes.len() - 1) * p / 100];
+        let sizes = (!sizes.is_empty()).then(|| Sizes {
+            samples: sizes.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: percentile(100),
+        });
+        snapshot["payload_sizes"] = serde_json::to_value(sizes).unwrap_or_default();
+        snapshot
+    }
+
+    fn record(&self, req: &HttpRequest, report: &ValidationReport) {
+        let route = route(req);
+        let size = extract::content_length(req);
+
+        let mut stats = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let rejected = u64::from(report.class.is_some());
+        stats.extractions += 1;
+        stats.rejected += rejected;
+        if let Some(size) = size {
+            if stats.sizes.len() == SIZE_SAMPLES {
+                stats.sizes.pop_front();
+            }
+            stats.sizes.push_back(size);
+        }
+        let route = stats.routes.entry(route).or_default();
+        route.extractions += 1;
+        route.rejected += rejected;
+        for failure in &report.failures {
+            let kind = match &failure.kind {
+                ConstraintKind::Custom(code) => code.clone(),
+                kind => kind.name().to_string(),
+            };
+            *route
+                .failures
+                .entry(fold_indexes(&failure.path))
+                .or_default()
+                .entry(kind)
+                .or_default() += 1;
+        }
+    }
+}
+
+/// `METHOD pattern` of the route matched by `req`, or its path when none did.
+fn route(req: &HttpRequest) -> String {
+    format!(
+        "{} {}",
+        req.method(),
+        req.match_pattern()
+            .unwrap_or_else(|| req.path().to_string())
+    )
+}
+
+impl Payloads {
+    fn add(&mut self, payload: &Value, redactions: &[String]) {
+        let mut seen = BTreeMap::new();
+        observe(payload, String::new(), redactions, &mut seen);
+        self.samples += 1;
+        for (path, observed) in seen {
+            let field = self.fields.entry(path).or_default();
+            field.present += 1;
+            field.min = bound(field.min, observed.min, f64::min);
+            field.max = bound(field.max, observed.max, f64::max);
+            field.min_length = bound(field.min_length, observed.min_length, usize::min);
+            field.max_length = bound(field.max_length, observed.max_length, usize::max);
+        }
+    }
+}
+
+/// Combine two optional bounds with `pick`.
+fn bound<T>(a: Option<T>, b: Option<T>, pick: fn(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(pick(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Record the fields of `value` under `path` into `seen`, folding array indexes into `*`
+/// and only noting the presence of redacted fields.
+fn observe(
+    value: &Value,
+    path: String,
+    redactions: &[String],
+    seen: &mut BTreeMap<String, FieldStats>,
+) {
+    let redacted = redactions
+        .iter()
+        .any(|pattern| path_matches(pattern, &path));
+    if !path.is_empty() {
+        let field = seen.entry(path.clone()).or_default();
+        let length = match value {
+            Value::String(text) => Some(text.chars().count()),
+            Value::Array(items) => Some(items.len()),
+            _ => None,
+        };
+        let number = value.as_f64();
+        if !redacted {
+            field.min = bound(field.min, number, f64::min);
+            field.max = bound(field.max, number, f64::max);
+            field.min_length = bound(field.min_length, length, usize::min);
+            field.max_length = bound(field.max_length, length, usize::max);
+        }
+    }
+    if redacted {
+        return;
+    }
+    let child = |key: &str| match path.as_str() {
+        "" => key.to_string(),
+        path => format!("{path}.{key}"),
+    };
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                observe(value, child(key), redactions, seen);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                observe(item, child("*"), redactions, seen);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether the [`ValidationStats`] of the app sample payloads, which must then be
+/// buffered.
+pub(crate) fn sampling(config: &JsonConfig, req: &HttpRequest) -> bool {
+    registered(config, req).is_some_and(|stats| stats.sample_every.is_some())
+}
+
+/// The counters registered as app data, or else those of [`JsonConfig::stats`].
+fn registered<'a>(config: &'a JsonConfig, req: &'a HttpRequest) -> Option<&'a ValidationStats> {
+    req.app_data::<ValidationStats>().or(config.stats.as_ref())
+}
+
+/// Summarize the accepted `body` of `req` into the [`ValidationStats`] of the app, if it
+/// is due for sampling.
+pub(crate) fn sample(config: &JsonConfig, req: &HttpRequest, body: &[u8]) {
+    let Some(stats) = registered(config, req) else {
+        return;
+    };
+    let Some(every) = stats.sample_every else {
+        return;
+    };
+    let route = route(req);
+    let mut inner = stats
+        .inner
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let payloads = inner
+        .routes
+        .entry(route)
+        .or_default()
+        .payloads
+        .get_or_insert_with(Payloads::default);
+    payloads.accepted += 1;
+    if (payloads.accepted - 1) % every != 0 {
+        return;
+    }
+    // Already deserialized into the payload type, so well-formed.
+    let payload: Value = serde_json::from_slice(body).unwrap_or_default();
+    payloads.add(&payload, &config.redactions);
+}
+
+/// `lines.3.quantity` as `lines.*.quantity`.
+fn fold_indexes(path: &str) -> String {
+    path.split('.')
+        .map(|segment| match segment.parse::<usize>() {
+            Ok(_) => "*",
+            Err(_) => segment,
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Count the extraction of `report` in the [`ValidationStats`] of the app, if any.
+pub(crate) fn record(config: &JsonConfig, req: &HttpRequest, report: &ValidationReport) {
+    if let Some(stats) = registered(config, req) {
+        stats.record(req, report);
+    }
+}
+
+/// A `GET` resource at `path` serving the [`ValidationStats`] registered as app data, or
+/// set with [`JsonConfig::stats`], as JSON, see [`ValidationStats::snapshot`]. Responds
+/// with `404 Not Found` when there are none.
+pub fn validation_stats_service(path: &str) -> Resource {
+    web::resource(path).route(web::get().to(|req: HttpRequest| async move {
+        let stats = match req.app_data::<ValidationStats>() {
+            Some(stats) => Some(stats.clone()),
+            None => extract::config(&req).stats,
+        };
+        match stats {
+            Some(stats) => HttpResponse::Ok().json(stats.snapshot()),
+            None => HttpResponse::NotFound().finish(),
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppJson;
+    use actix_web::{test, App};
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate]
+        lines: Vec<Line>,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Line {
+        #[validate(minimum = 1)]
+        quantity: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_counts_by_route_and_path() {
+        let stats = ValidationStats::new();
+        let app = test::init_service(
+            App::new()
+                .app_data(stats.clone())
+                .route(
+                    "/orders/{id}",
+                    web::post()
+                        .to(|order: AppJson<Order>| async move { order.lines.len().to_string() }),
+                )
+                .service(validation_stats_service("/stats")),
+        )
+        .await;
+
+        for body in [
+            json!({"lines": [{"quantity": 1}]}),
+            json!({"lines": [{"quantity": 0}, {"quantity": 2}, {"quantity": 0}]}),
+            json!({"lines": "none"}),
+        ] {
+            let req = test::TestRequest::post()
+                .uri("/orders/7")
+                .set_json(body)
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        let req = test::TestRequest::get().uri("/stats").to_request();
+        let snapshot: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(snapshot["extractions"], 3);
+        assert_eq!(snapshot["rejected"], 2);
+        assert_eq!(
+            snapshot["routes"]["POST /orders/{id}"]["failures"],
+            json!({
+                "lines.*.quantity": {"minimum": 2},
+                "": {"deserialization": 1}
+            })
+        );
+        assert_eq!(snapshot["payload_sizes"]["samples"], 3);
+        assert_eq!(snapshot["payload_sizes"]["max"], 56);
+    }
+
+    #[actix_web::test]
+    async fn test_counts_into_config_stats() {
+        let app = test::init_service(
+            App::new()
+                .app_data(JsonConfig::from_profile(crate::Profile::Production))
+                .route(
+                    "/orders",
+                    web::post().to(|_: AppJson<Order>| async { "ok" }),
+                )
+                .service(validation_stats_service("/stats")),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/orders")
+            .set_json(json!({"lines": [{"quantity": 0}]}))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::get().uri("/stats").to_request();
+        let snapshot: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(snapshot["extractions"], 1);
+        assert_eq!(snapshot["rejected"], 1);
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[allow(dead_code)]
+        name: String,
+        #[allow(dead_code)]
+        password: String,
+        #[allow(dead_code)]
+        tags: Option<Vec<String>>,
+    }
+
+    #[actix_web::test]
+    async fn test_samples_accepted_payloads() {
+        let stats = ValidationStats::new().sample_payloads(2);
+        let app = test::init_service(
+            App::new()
+                .app_data(stats.clone())
+                .app_data(JsonConfig::default().redact("password"))
+                .route(
+                    "/signup",
+                    web::post().to(|_: AppJson<Signup>| async { HttpResponse::Ok().finish() }),
+                ),
+        )
+        .await;
+
+        for body in [
+            json!({"name": "Ada", "password": "hunter2", "tags": ["a", "bc"]}),
+            json!({"name": "Skipped by the rate", "password": "x"}),
+            json!({"name": "Grace Hopper", "password": "correct horse"}),
+        ] {
+            let req = test::TestRequest::post()
+                .uri("/signup")
+                .set_json(body)
+                .to_request();
+            test::call_service(&app, req).await;
+        }
+
+        assert_eq!(
+            stats.snapshot()["routes"]["POST /signup"]["payloads"],
+            json!({
+                "samples": 2,
+                "fields": {
+                    "name": {"present": 2, "min_length": 3, "max_length": 12},
+                    "password": {"present": 2},
+                    "tags": {"present": 1, "min_length": 2, "max_length": 2},
+                    "tags.*": {"present": 1, "min_length": 1, "max_length": 2}
+                }
+            })
+        );
+    }
+}
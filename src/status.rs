@@ -0,0 +1,128 @@
+//! Classification of rejected payloads, used to pick the response status configured
+//! with [`JsonConfig::status`](crate::JsonConfig::status).
+
+use serde_valid::validation::{Error, Errors as ValidationError};
+
+use crate::format;
+
+/// Broad category of a rejected payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureClass {
+    /// The body could not be read or deserialized, or had the wrong content type.
+    Payload,
+    /// A built-in `serde_valid` constraint (length, range, pattern, ...) failed.
+    Schema,
+    /// Only `custom` validators failed, which is where business rules live.
+    Semantic,
+    /// A guard against abusive requests was tripped, such as the payload size limit.
+    Security,
+}
+
+/// Classify validation failures: any failed built-in constraint makes them a schema
+/// problem, since business rules rarely mean much on a malformed payload.
+pub(crate) fn classify(errors: &ValidationError) -> FailureClass {
+    if has_schema_errors(errors) {
+        FailureClass::Schema
+    } else {
+        FailureClass::Semantic
+    }
+}
+
+fn has_schema_errors(errors: &ValidationError) -> bool {
+    let mut schema = false;
+    format::walk(errors, "", &mut |_, error| {
+        schema |= !matches!(error, Error::Custom(_));
+    });
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, JsonConfig, ValidationReport};
+    use actix_web::{
+        body::MessageBody, http::StatusCode, test, FromRequest, HttpMessage, ResponseError,
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+    use serde_valid::Validate;
+
+    fn in_stock(quantity: &u32) -> Result<(), serde_valid::validation::Error> {
+        if *quantity > 5 {
+            return Err(serde_valid::validation::Error::Custom(
+                "Not enough items in stock.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(min_length = 3)]
+        item: String,
+        #[validate(custom = in_stock)]
+        quantity: u32,
+    }
+
+    fn config() -> JsonConfig {
+        JsonConfig::default()
+            .limit(64)
+            .status(FailureClass::Semantic, StatusCode::UNPROCESSABLE_ENTITY)
+            .status(FailureClass::Security, StatusCode::FORBIDDEN)
+    }
+
+    async fn status_for(body: serde_json::Value) -> (StatusCode, Option<FailureClass>) {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config())
+            .set_json(body)
+            .to_http_parts();
+
+        let err = AppJson::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let class = req.extensions().get::<ValidationReport>().unwrap().class;
+        (err.status_code(), class)
+    }
+
+    #[actix_web::test]
+    async fn test_status_follows_failure_class() {
+        assert_eq!(
+            status_for(json!({"item": "Pizza", "quantity": 9})).await,
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Some(FailureClass::Semantic)
+            )
+        );
+        assert_eq!(
+            status_for(json!({"item": "P", "quantity": 9})).await,
+            (StatusCode::BAD_REQUEST, Some(FailureClass::Schema))
+        );
+        assert_eq!(
+            status_for(json!({"item": "P".repeat(100), "quantity": 1})).await,
+            (StatusCode::FORBIDDEN, Some(FailureClass::Security))
+        );
+        assert_eq!(
+            status_for(json!({"item": "Pizza"})).await,
+            (StatusCode::BAD_REQUEST, Some(FailureClass::Payload))
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_mapped_status_keeps_error_body() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config())
+            .set_json(json!({"item": "Pizza", "quantity": 9}))
+            .to_http_parts();
+
+        let err = AppJson::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            json!({"quantity": ["Not enough items in stock."]})
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::{marker::PhantomData, ops::Deref};
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{
+    extract,
+    json::{self, Inline},
+    AppError, JsonConfig,
+};
+
+/// Adjusts the [`JsonConfig`] of a request for one endpoint, through the strategy
+/// parameter of [`AppJsonWith`].
+pub trait Strategy {
+    fn apply(config: JsonConfig) -> JsonConfig;
+}
+
+/// Report only the first failing constraint of each field, as with
+/// [`first_error_only`](JsonConfig::first_error_only).
+pub struct FailFast;
+
+impl Strategy for FailFast {
+    fn apply(config: JsonConfig) -> JsonConfig {
+        config.first_error_only(true)
+    }
+}
+
+/// Report every message of every field, whatever
+/// [`first_error_only`](JsonConfig::first_error_only),
+/// [`messages_per_field`](JsonConfig::messages_per_field),
+/// [`join_messages`](JsonConfig::join_messages) and
+/// [`truncate_errors`](JsonConfig::truncate_errors) say.
+pub struct Verbose;
+
+impl Strategy for Verbose {
+    fn apply(mut config: JsonConfig) -> JsonConfig {
+        config.message_limit = None;
+        config.error_cap = None;
+        config.first_error_only(false).join_messages(false)
+    }
+}
+
+/// [`AppJson`](crate::AppJson) with the config of the request adjusted by a
+/// [`Strategy`], so an endpoint states its validation behavior in its signature:
+///
+/// ```
+/// use actix_json_validator::{AppJsonWith, FailFast, Verbose};
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Signup { #[validate(min_length = 8)] password: String }
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Import { #[validate(min_items = 1)] rows: Vec<String> }
+///
+/// // One message per field for the signup form.
+/// async fn signup(form: AppJsonWith<Signup, FailFast>) -> &'static str {
+///     "welcome"
+/// }
+///
+/// // Everything wrong at once for the bulk import tool.
+/// async fn import(rows: AppJsonWith<Import, Verbose>) -> &'static str {
+///     "imported"
+/// }
+/// ```
+pub struct AppJsonWith<T, S>(pub T, PhantomData<S>);
+
+impl<T, S> AppJsonWith<T, S> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, S> AsRef<T> for AppJsonWith<T, S> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, S> Deref for AppJsonWith<T, S> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: std::fmt::Debug, S> std::fmt::Debug for AppJsonWith<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AppJsonWith").field(&self.0).finish()
+    }
+}
+
+impl<T, S> FromRequest for AppJsonWith<T, S>
+where
+    T: DeserializeOwned + Validate + 'static,
+    S: Strategy,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        json::extract_with::<T, Inline>(req, payload, S::apply(extract::config(req)))
+            .map(|result| result.map(|data| AppJsonWith(data, PhantomData)))
+            .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 8)]
+        #[validate(pattern = "[0-9]")]
+        password: String,
+    }
+
+    async fn errors<S: Strategy>(config: JsonConfig) -> Value {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .set_json(json!({"password": "abc"}))
+            .to_http_parts();
+        let body = AppJsonWith::<Signup, S>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err()
+            .error_response()
+            .into_body()
+            .try_into_bytes()
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_strategies_override_config() {
+        assert_eq!(
+            errors::<FailFast>(JsonConfig::default()).await,
+            json!({"password": ["The length of the value must be `>= 8`."]})
+        );
+        let terse = JsonConfig::default()
+            .first_error_only(true)
+            .messages_per_field(1);
+        assert_eq!(
+            errors::<Verbose>(terse).await["password"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::{io, panic};
+
+use actix_web::{
+    dev::{Decompress, Payload},
+    error::{JsonPayloadError, PayloadError},
+    http::header,
+    web::Bytes,
+    HttpRequest,
+};
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use serde_valid::validation::Errors as ValidationErrors;
+use tokio::sync::mpsc;
+
+use crate::{extract, JsonConfig};
+
+/// Chunks in flight between the worker and the parsing thread.
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+/// Whether the body of `req` is parsed as it arrives, see
+/// [`JsonConfig::streaming_threshold`].
+pub(crate) fn streams(req: &HttpRequest, config: &JsonConfig) -> bool {
+    let Some(threshold) = config.streaming_threshold else {
+        return false;
+    };
+    extract::content_length(req).is_none_or(|length| length >= threshold)
+}
+
+/// Deserialize and validate the body on the blocking thread pool while it is read, so
+/// no more than a few chunks of it are held at once. Failures to read the body take
+/// precedence over the parse errors they cause.
+pub(crate) async fn parse_streamed<T>(
+    req: &HttpRequest,
+    config: &JsonConfig,
+    payload: Payload,
+    validate: impl Fn(&T) -> Result<(), ValidationErrors> + Send + 'static,
+) -> Result<(T, Result<(), ValidationErrors>), JsonPayloadError>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let limit = config.limit;
+    let length = extract::content_length(req);
+    if let Some(length) = length.filter(|&length| length > limit) {
+        return Err(JsonPayloadError::OverflowKnownLength { length, limit });
+    }
+
+    let (sender, receiver) = mpsc::channel(CHUNKS_IN_FLIGHT);
+    let parse = actix_web::rt::task::spawn_blocking(move || {
+        // serde_json reads a byte at a time, which must not each cost a trip to the channel.
+        let reader = io::BufReader::new(ChannelReader {
+            receiver,
+            chunk: Bytes::new(),
+        });
+        let data =
+            serde_json::from_reader::<_, T>(reader).map_err(JsonPayloadError::Deserialize)?;
+        let outcome = validate(&data);
+        Ok((data, outcome))
+    });
+
+    let mut payload = Decompress::from_headers(payload, req.headers());
+    let mut size = 0;
+    let mut read = Ok(());
+    while let Some(chunk) = payload.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                read = Err(JsonPayloadError::Payload(err));
+                break;
+            }
+        };
+        size += chunk.len();
+        if size > limit {
+            read = Err(JsonPayloadError::Overflow { limit });
+            break;
+        }
+        // The parser stopped early, on a syntax error it reports itself.
+        if sender.send(chunk).await.is_err() {
+            break;
+        }
+    }
+    drop(sender);
+    extract::soft_limit(config, req, size);
+    // The length of an encoded body says nothing about the decoded one.
+    let encoded = req
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding != "identity");
+    if read.is_ok() && !encoded && length.is_some_and(|length| size < length) {
+        read = Err(JsonPayloadError::Payload(PayloadError::Incomplete(None)));
+    }
+
+    let parsed = match parse.await {
+        Ok(result) => result,
+        Err(err) => panic::resume_unwind(err.into_panic()),
+    };
+    read?;
+    parsed
+}
+
+/// Reads the chunks sent by the worker, from the blocking thread pool.
+struct ChannelReader {
+    receiver: mpsc::Receiver<Bytes>,
+    chunk: Bytes,
+}
+
+impl io::Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.chunk = chunk,
+                None => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len());
+        buf[..len].copy_from_slice(&self.chunk.split_to(len));
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OffloadedAppJson;
+    use actix_web::{body::MessageBody, test, FromRequest, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use serde_valid::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Import {
+        #[validate(max_items = 3)]
+        rows: Vec<u32>,
+    }
+
+    async fn extract(config: JsonConfig, chunks: &[&str], length: Option<usize>) -> Value {
+        let chunks: Vec<Result<Bytes, PayloadError>> = chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk.as_bytes())))
+            .collect();
+        let mut req = test::TestRequest::post()
+            .app_data(config)
+            .insert_header(("content-type", "application/json"));
+        if let Some(length) = length {
+            req = req.insert_header(("content-length", length));
+        }
+        let req = req.to_http_request();
+        let mut payload = Payload::from(futures_util::stream::iter(chunks).boxed_local());
+        match OffloadedAppJson::<Import>::from_request(&req, &mut payload).await {
+            Ok(import) => json!(import.rows),
+            Err(err) => {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_streamed_bodies() {
+        let config = JsonConfig::default().streaming_threshold(16);
+        let chunks = ["{\"rows\": [1, ", "2, 3", "]}"];
+        assert_eq!(
+            extract(config.clone(), &chunks, None).await,
+            json!([1, 2, 3])
+        );
+        assert_eq!(
+            extract(config.clone(), &["{\"rows\": [1, 2, 3, 4]}"], Some(21)).await,
+            json!({"rows": ["The length of the items must be `<= 3`."]})
+        );
+        assert_eq!(
+            extract(config.clone(), &chunks[..2], Some(24)).await,
+            json!({"error": ["The request body was cut short before it was complete"]})
+        );
+        assert_eq!(
+            extract(config.limit(12), &chunks, None).await,
+            json!({"error": ["JSON payload has exceeded limit (12 bytes)."]})
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! The checks of [`JsonConfig::strict_json`](crate::JsonConfig::strict_json).
+
+use std::{collections::HashSet, fmt};
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+/// Check that `body` is a JSON text as strict as RFC 8259 allows: UTF-8, every string
+/// valid Unicode, without lone surrogate escapes, and no duplicate keys in an object.
+///
+/// `serde_json` only decodes the strings it keeps, so fields that a payload type
+/// ignores, `RawValue`s and maps would otherwise let such bodies through, or keep the
+/// last of duplicate keys.
+pub(crate) fn check(body: &[u8]) -> Result<(), serde_json::Error> {
+    serde_json::from_slice::<Strict>(body).map(|_| ())
+}
+
+/// Any JSON value, with every string decoded and the keys of each object compared.
+struct Strict;
+
+impl<'de> Deserialize<'de> for Strict {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(StrictVisitor)
+    }
+}
+
+struct StrictVisitor;
+
+impl<'de> Visitor<'de> for StrictVisitor {
+    type Value = Strict;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Strict, E> {
+        Ok(Strict)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Strict, E> {
+        Ok(Strict)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Strict, E> {
+        Ok(Strict)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Strict, E> {
+        Ok(Strict)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<Strict, E> {
+        Ok(Strict)
+    }
+
+    fn visit_unit<E>(self) -> Result<Strict, E> {
+        Ok(Strict)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Strict, A::Error> {
+        while seq.next_element::<Strict>()?.is_some() {}
+        Ok(Strict)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Strict, A::Error> {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if keys.contains(&key) {
+                return Err(de::Error::custom(format!("duplicate key `{key}`")));
+            }
+            map.next_value::<Strict>()?;
+            keys.insert(key);
+        }
+        Ok(Strict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppJson, AppRawJson, JsonConfig};
+    use actix_web::{test::TestRequest, FromRequest};
+
+    #[test]
+    fn test_rejects_lenient_edge_cases() {
+        assert!(check(
+            r#"{"a": ["😀", "\ud83d\ude00", 1.5e3, null], "b": {"a": true}}"#.as_bytes()
+        )
+        .is_ok());
+
+        for (body, error) in [
+            (
+                &br#"{"a": 1, "b": "\ud800"}"#[..],
+                "unexpected end of hex escape at line 1 column 22",
+            ),
+            (
+                br#"{"a": ["\udc00"]}"#,
+                "lone leading surrogate in hex escape at line 1 column 14",
+            ),
+            (
+                br#"{"a": 1, "b": {"c": 2, "c": 3}}"#,
+                "duplicate key `c` at line 1 column 26",
+            ),
+            (
+                b"{\"a\": \"\xff\"}",
+                "invalid unicode code point at line 1 column 8",
+            ),
+        ] {
+            assert_eq!(check(body).unwrap_err().to_string(), error);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_extractors_agree() {
+        #[derive(Debug, serde::Deserialize, serde_valid::Validate)]
+        struct Note {
+            #[allow(dead_code)]
+            title: String,
+        }
+
+        let body = r#"{"title": "Hi", "tags": ["\udc00"]}"#;
+        for strict in [false, true] {
+            let request = || {
+                TestRequest::post()
+                    .app_data(JsonConfig::default().strict_json(strict))
+                    .insert_header(("content-type", "application/json"))
+                    .set_payload(body)
+                    .to_http_parts()
+            };
+            let (req, mut payload) = request();
+            let note = AppJson::<Note>::from_request(&req, &mut payload).await;
+            let (req, mut payload) = request();
+            let raw =
+                AppRawJson::<Box<serde_json::value::RawValue>>::from_request(&req, &mut payload)
+                    .await;
+            assert_eq!(note.is_ok(), !strict);
+            assert_eq!(raw.is_ok(), !strict);
+        }
+    }
+}
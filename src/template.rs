@@ -0,0 +1,259 @@
+//! The crate's own messages for the built-in constraints, so that upgrading
+//! `serde_valid` cannot change the error bodies clients rely on.
+
+use serde_valid::validation::Error;
+
+use crate::debug::constraint_name;
+
+/// Message templates by constraint, `{}` standing for the parameter.
+const TEMPLATES: [(&str, &str); 14] = [
+    ("minimum", "The number must be `>= {}`."),
+    ("maximum", "The number must be `<= {}`."),
+    ("exclusive_minimum", "The number must be `> {}`."),
+    ("exclusive_maximum", "The number must be `< {}`."),
+    ("multiple_of", "The value must be multiple of `{}`."),
+    ("min_length", "The length of the value must be `>= {}`."),
+    ("max_length", "The length of the value must be `<= {}`."),
+    ("pattern", "The value must match the pattern of \"{}\"."),
+    ("min_items", "The length of the items must be `>= {}`."),
+    ("max_items", "The length of the items must be `<= {}`."),
+    ("unique_items", "The items must be unique."),
+    (
+        "min_properties",
+        "The size of the properties must be `>= {}`.",
+    ),
+    (
+        "max_properties",
+        "The size of the properties must be `<= {}`.",
+    ),
+    ("enumerate", "The value must be in [{}]."),
+];
+
+/// The crate's message for `error`, or `None` when its message was customized with
+/// `message = "..."` (or is not one of a built-in constraint).
+pub(crate) fn render(error: &Error) -> Option<String> {
+    let parameter = parameter(error)?;
+    let (_, template) = TEMPLATES
+        .iter()
+        .find(|(code, _)| *code == constraint_name(error))?;
+    Some(template.replacen("{}", &parameter, 1))
+}
+
+/// The parameter of a built-in constraint as the templates write it, e.g. `3` or
+/// `"a", "b"` joined for `enumerate`, and empty for `unique_items`. `None` when the
+/// message was customized with `message = "..."`, or the error has no parameter.
+///
+/// `serde_valid` keeps the fields of its errors private, so they are read from their
+/// derived `Debug` form, e.g.
+/// `Message { error: MinimumError { minimum: I64(3) }, format: Default }`, never from
+/// the message. A form that cannot be read fails debug builds, and the tests below,
+/// rather than letting the upstream message through.
+pub(crate) fn parameter(error: &Error) -> Option<String> {
+    let debug = match error {
+        Error::Minimum(message) => format!("{message:?}"),
+        Error::Maximum(message) => format!("{message:?}"),
+        Error::ExclusiveMinimum(message) => format!("{message:?}"),
+        Error::ExclusiveMaximum(message) => format!("{message:?}"),
+        Error::MultipleOf(message) => format!("{message:?}"),
+        Error::MinLength(message) => format!("{message:?}"),
+        Error::MaxLength(message) => format!("{message:?}"),
+        Error::Pattern(message) => format!("{message:?}"),
+        Error::MinItems(message) => format!("{message:?}"),
+        Error::MaxItems(message) => format!("{message:?}"),
+        Error::UniqueItems(message) => format!("{message:?}"),
+        Error::MinProperties(message) => format!("{message:?}"),
+        Error::MaxProperties(message) => format!("{message:?}"),
+        Error::Enumerate(message) => format!("{message:?}"),
+        _ => return None,
+    };
+    if CUSTOMIZED.iter().any(|format| debug.contains(format)) {
+        return None;
+    }
+    let parameter = debug
+        .strip_prefix("Message { error: ")
+        .and_then(|debug| debug.strip_suffix(", format: Default }"))
+        .and_then(|fields| read(error, fields));
+    debug_assert!(
+        parameter.is_some(),
+        "cannot read the parameter of `{debug}`, check the templates against serde_valid"
+    );
+    parameter
+}
+
+/// How the `Debug` form of customized messages starts their format.
+const CUSTOMIZED: [&str; 3] = [
+    ", format: Message(",
+    ", format: MessageFn(",
+    ", format: Fluent(",
+];
+
+/// The parameter in the `Debug` form of an error, such as `MinimumError { minimum: I64(3) }`.
+fn read(error: &Error, fields: &str) -> Option<String> {
+    if let Error::UniqueItems(_) = error {
+        return (fields == "UniqueItemsError").then(String::new);
+    }
+    let (_, value) = fields.strip_suffix(" }")?.split_once(" { ")?;
+    let (_, value) = value.split_once(": ")?;
+    let (parameter, rest) = match error {
+        Error::Pattern(_) => quoted(value, '"')?,
+        Error::Enumerate(_) => {
+            let mut literals = Vec::new();
+            let mut rest = value.strip_prefix('[')?;
+            while !rest.starts_with(']') {
+                let (literal, after) = literal(rest)?;
+                literals.push(literal);
+                rest = after.strip_prefix(", ").unwrap_or(after);
+            }
+            (literals.join(", "), &rest[1..])
+        }
+        Error::Minimum(_)
+        | Error::Maximum(_)
+        | Error::ExclusiveMinimum(_)
+        | Error::ExclusiveMaximum(_)
+        | Error::MultipleOf(_) => number(value)?,
+        _ => (value.to_string(), ""),
+    };
+    rest.is_empty().then_some(parameter)
+}
+
+/// A `Number` such as `I64(3)`, as it displays, and what follows it.
+fn number(debug: &str) -> Option<(String, &str)> {
+    let (_, value) = debug.split_once('(')?;
+    let (value, rest) = value.split_once(')')?;
+    Some((value.to_string(), rest))
+}
+
+/// A `Literal` such as `String("a")` or `Number(I64(3))`, as it displays, and what
+/// follows it.
+fn literal(debug: &str) -> Option<(String, &str)> {
+    if let Some(rest) = debug.strip_prefix("Null") {
+        return Some(("null".to_string(), rest));
+    }
+    let (kind, value) = debug.split_once('(')?;
+    let (literal, rest) = match kind {
+        "String" => quoted(value, '"')?,
+        "Char" => quoted(value, '\'')?,
+        "Number" => number(value)?,
+        "Bool" => {
+            let (value, rest) = value.split_once(')')?;
+            return Some((value.to_string(), rest));
+        }
+        _ => return None,
+    };
+    Some((literal, rest.strip_prefix(')')?))
+}
+
+/// The text of a string or char written with `Debug`, escapes resolved, and what
+/// follows it.
+fn quoted(debug: &str, quote: char) -> Option<(String, &str)> {
+    let mut chars = debug.strip_prefix(quote)?.char_indices();
+    let mut text = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            c if c == quote => return Some((text, &debug[index + 2 * quote.len_utf8()..])),
+            '\\' => text.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'u' => {
+                    let (_, rest) = chars.as_str().split_once('{')?;
+                    let (hex, _) = rest.split_once('}')?;
+                    for _ in 0..hex.len() + 2 {
+                        chars.next();
+                    }
+                    char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+                }
+                escaped => escaped,
+            }),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_valid::Validate;
+
+    #[derive(Deserialize, Validate)]
+    #[allow(dead_code)]
+    struct Everything {
+        #[validate(minimum = -1.5)]
+        minimum: f64,
+        #[validate(maximum = 10)]
+        maximum: i32,
+        #[validate(exclusive_minimum = 0)]
+        exclusive_minimum: i32,
+        #[validate(exclusive_maximum = 100)]
+        exclusive_maximum: i32,
+        #[validate(multiple_of = 5)]
+        multiple_of: i32,
+        #[validate(min_length = 3)]
+        min_length: String,
+        #[validate(max_length = 2)]
+        max_length: String,
+        #[validate(pattern = r#"^"\d+"$"#)]
+        pattern: String,
+        #[validate(min_items = 2)]
+        min_items: Vec<u8>,
+        #[validate(max_items = 1)]
+        max_items: Vec<u8>,
+        #[validate(unique_items)]
+        unique_items: Vec<u8>,
+        #[validate(min_properties = 1)]
+        min_properties: std::collections::HashMap<String, u8>,
+        #[validate(max_properties = 0)]
+        max_properties: std::collections::HashMap<String, u8>,
+        #[validate(enumerate = ["a", "b"])]
+        enumerate: String,
+        #[validate(enumerate = [1, 2])]
+        enumerate_numbers: i32,
+        #[validate(min_length = 3, message = "Too short `>= 3`.")]
+        customized: String,
+    }
+
+    /// Fails when a `serde_valid` upgrade changes a default message, so the templates
+    /// above can be checked against it instead of drifting silently.
+    #[test]
+    fn test_templates_cover_serde_valid_defaults() {
+        let payload: Everything = serde_json::from_value(serde_json::json!({
+            "minimum": -2, "maximum": 11, "exclusive_minimum": 0, "exclusive_maximum": 100,
+            "multiple_of": 3, "min_length": "ab", "max_length": "abc", "pattern": "x",
+            "min_items": [1], "max_items": [1, 2], "unique_items": [1, 1],
+            "min_properties": {}, "max_properties": {"a": 1}, "enumerate": "c",
+            "enumerate_numbers": 3, "customized": "ab"
+        }))
+        .unwrap();
+        let serde_valid::validation::Errors::Object(errors) = payload.validate().unwrap_err()
+        else {
+            panic!("expected field errors");
+        };
+
+        for (field, errors) in &errors.properties {
+            let serde_valid::validation::Errors::NewType(errors) = errors else {
+                panic!("expected errors of {field}");
+            };
+            let error = &errors[0];
+            match field.as_str() {
+                "customized" => assert_eq!(render(error), None),
+                _ => assert_eq!(render(error), Some(error.to_string()), "{field}"),
+            }
+        }
+        assert_eq!(errors.properties.len(), 16);
+    }
+
+    #[test]
+    fn test_debug_literals_are_read() {
+        assert_eq!(
+            literal(r#"String("say \"hi\" \u{301}"), Null]"#),
+            Some(("say \"hi\" \u{301}".to_string(), ", Null]"))
+        );
+        assert_eq!(literal(r"Char('\'')"), Some(("'".to_string(), "")));
+        assert_eq!(literal("Number(F64(-1.5))"), Some(("-1.5".to_string(), "")));
+        assert_eq!(literal("Bool(true)"), Some(("true".to_string(), "")));
+        assert_eq!(literal("Unknown(1)"), None);
+    }
+}
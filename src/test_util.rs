@@ -0,0 +1,559 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use actix_http::Request;
+use actix_web::{
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    error::ErrorServiceUnavailable,
+    test::{self, TestRequest},
+    HttpRequest,
+};
+use futures_util::future::LocalBoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use serde_valid::Validate;
+
+use crate::{
+    constraints_of, examples_of, format::format_errors, Describe, InvalidExample, UniquenessChecker,
+};
+
+/// Test helpers for [`TestRequest`]. Requires the `test-util` feature.
+pub trait TestRequestExt {
+    /// Like [`TestRequest::set_json`], but panics unless `value` deserializes into a `T`
+    /// that passes validation, so fixtures keep up with the constraints of `T`:
+    ///
+    /// ```
+    /// use actix_json_validator::TestRequestExt;
+    /// use actix_web::test::TestRequest;
+    /// use serde_json::json;
+    /// # #[derive(serde::Deserialize, serde_valid::Validate)]
+    /// # struct Signup { #[validate(min_length = 3)] name: String }
+    ///
+    /// let req = TestRequest::post().set_valid_json::<Signup>(&json!({"name": "Ada"}));
+    /// ```
+    fn set_valid_json<T>(self, value: &impl Serialize) -> Self
+    where
+        T: DeserializeOwned + Validate;
+}
+
+impl TestRequestExt for TestRequest {
+    #[track_caller]
+    fn set_valid_json<T>(self, value: &impl Serialize) -> Self
+    where
+        T: DeserializeOwned + Validate,
+    {
+        let type_name = std::any::type_name::<T>();
+        let json = serde_json::to_value(value)
+            .unwrap_or_else(|err| panic!("fixture is not serializable to JSON: {err}"));
+        let data = serde_json::from_value::<T>(json.clone())
+            .unwrap_or_else(|err| panic!("fixture does not deserialize into {type_name}: {err}"));
+        if let Err(errors) = data.validate() {
+            panic!(
+                "fixture is not a valid {type_name}: {}",
+                serde_json::json!(format_errors(errors))
+            );
+        }
+        self.set_json(json)
+    }
+}
+
+/// A [`UniquenessChecker`] backed by an in-memory set of existing values, so extractor
+/// tests do not need a database. Requires the `test-util` feature.
+///
+/// It serves both kinds of lookup: with [`Uniqueness`](crate::Uniqueness), the values it
+/// holds are taken, and with [`References`](crate::References), they are the entities
+/// that payloads may refer to:
+///
+/// ```
+/// use actix_json_validator::{InMemoryChecker, References, Uniqueness};
+/// use actix_web::App;
+///
+/// let users = InMemoryChecker::new().taken("email", "ada@example.com");
+/// let categories = InMemoryChecker::new().existing("category", "1");
+/// let app = App::new()
+///     .app_data(Uniqueness::new(users.clone()))
+///     .app_data(References::new(categories));
+///
+/// // Later, e.g. after a signup succeeded:
+/// users.insert("email", "grace@example.com");
+/// ```
+///
+/// Clones share the same values.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryChecker {
+    values: Arc<Mutex<HashSet<(String, String)>>>,
+    unavailable: bool,
+}
+
+impl InMemoryChecker {
+    /// A checker with no values taken.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `value` as taken for `field`.
+    pub fn taken(self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(field, value);
+        self
+    }
+
+    /// Record an entity with `value` for `field`, e.g. for [`References`](crate::References)
+    /// to find. The same as [`taken`](InMemoryChecker::taken), under the name that reads
+    /// right for existence checks.
+    pub fn existing(self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.taken(field, value)
+    }
+
+    /// Mark `value` as taken for `field`, through a shared reference.
+    pub fn insert(&self, field: impl Into<String>, value: impl Into<String>) {
+        let mut values = self.values.lock().unwrap_or_else(|err| err.into_inner());
+        values.insert((field.into(), value.into()));
+    }
+
+    /// Fail every lookup with `503 Service Unavailable`, like a database that is down.
+    pub fn unavailable(mut self) -> Self {
+        self.unavailable = true;
+        self
+    }
+}
+
+impl UniquenessChecker for InMemoryChecker {
+    fn exists<'a>(
+        &'a self,
+        field: &'a str,
+        value: &'a str,
+        _req: &'a HttpRequest,
+    ) -> LocalBoxFuture<'a, Result<bool, actix_web::Error>> {
+        Box::pin(async move {
+            if self.unavailable {
+                return Err(ErrorServiceUnavailable("uniqueness checker unavailable"));
+            }
+            let values = self.values.lock().unwrap_or_else(|err| err.into_inner());
+            Ok(values.contains(&(field.to_string(), value.to_string())))
+        })
+    }
+}
+
+/// Post each [invalid example](crate::examples_of) of `T` to `route` of `app`, and panic
+/// unless every one is rejected with a `4xx` status and messages at the path of the
+/// violated constraint, or under it. Every example is posted before panicking, and the
+/// panic names each constraint that was not enforced. Requires the `test-util` feature;
+/// usually called through [`generate_validation_tests!`](crate::generate_validation_tests).
+///
+/// `pattern` and `custom` constraints have no generated example, so they fail the
+/// assertion until one is supplied to [`assert_rejects_examples`].
+pub async fn assert_rejects_invalid_examples<T, S, B>(app: &S, route: &str)
+where
+    T: Describe,
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    assert_rejects_examples::<T, S, B>(app, route, Vec::new()).await;
+}
+
+/// Like [`assert_rejects_invalid_examples`], with `supplied` examples posted too, e.g.
+/// for the `pattern` and `custom` constraints of `T`:
+///
+/// ```no_run
+/// use actix_json_validator::{assert_rejects_examples, Describe, InvalidExample};
+/// # use actix_web::{test, App};
+/// use serde_json::json;
+///
+/// #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// struct Coupon {
+///     #[validate(pattern = r"^[A-Z]{4}$")]
+///     code: String,
+/// }
+///
+/// # async fn run() {
+/// # let app = test::init_service(App::new()).await;
+/// let invalid = vec![InvalidExample {
+///     path: "code".to_string(),
+///     kind: "pattern",
+///     payload: json!({"code": "save10"}),
+/// }];
+/// assert_rejects_examples::<Coupon, _, _>(&app, "/coupons", invalid).await;
+/// # }
+/// ```
+pub async fn assert_rejects_examples<T, S, B>(app: &S, route: &str, supplied: Vec<InvalidExample>)
+where
+    T: Describe,
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let failures = rejection_failures::<T, S, B>(app, route, supplied).await;
+    if !failures.is_empty() {
+        panic!(
+            "{} constraints of {} were not enforced as expected:\n{}",
+            failures.len(),
+            std::any::type_name::<T>(),
+            failures.join("\n")
+        );
+    }
+}
+
+/// Constraints that [`examples_of`] cannot violate.
+const UNSOLVABLE: [&str; 2] = ["pattern", "custom"];
+
+/// A description of each example of `T` or of `supplied` that `app` did not reject as
+/// expected, and of each `pattern` or `custom` constraint without a supplied example.
+async fn rejection_failures<T, S, B>(
+    app: &S,
+    route: &str,
+    supplied: Vec<InvalidExample>,
+) -> Vec<String>
+where
+    T: Describe,
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let mut failures: Vec<_> = constraints_of::<T>()
+        .iter()
+        .filter(|constraint| UNSOLVABLE.contains(&constraint.kind))
+        .filter(|constraint| {
+            !supplied
+                .iter()
+                .any(|example| example.path == constraint.path && example.kind == constraint.kind)
+        })
+        .map(|constraint| {
+            format!(
+                "`{}` at `{}` has no invalid example, supply one to `assert_rejects_examples`",
+                constraint.kind, constraint.path
+            )
+        })
+        .collect();
+    for example in examples_of::<T>().invalid.into_iter().chain(supplied) {
+        let req = TestRequest::post()
+            .uri(route)
+            .set_json(&example.payload)
+            .to_request();
+        let problem = match test::try_call_service(app, req).await {
+            Err(err) => Some(format!("failed: {err}")),
+            Ok(res) => {
+                let status = res.status();
+                let body = test::read_body(res).await;
+                let body: Value = serde_json::from_slice(&body).unwrap_or_default();
+                if !status.is_client_error() {
+                    Some(format!("answered {status}"))
+                } else if !has_messages(&body, &example.path) {
+                    Some(format!("has no messages at `{}`: {body}", example.path))
+                } else {
+                    None
+                }
+            }
+        };
+        if let Some(problem) = problem {
+            failures.push(format!(
+                "`{}` violated at `{}` {problem}\n  payload: {}",
+                example.kind, example.path, example.payload
+            ));
+        }
+    }
+    failures
+}
+
+/// Whether the error `body` has messages at or under the dotted `path`, `*` matching any
+/// index. Constraints of collections applied to their elements report by index below the
+/// path of the field.
+fn has_messages(body: &Value, path: &str) -> bool {
+    let mut nodes = vec![body];
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        nodes = nodes
+            .into_iter()
+            .filter_map(Value::as_object)
+            .flat_map(|object| match segment {
+                "*" => object.values().collect(),
+                key => object.get(key).into_iter().collect::<Vec<_>>(),
+            })
+            .collect();
+    }
+    nodes.into_iter().any(|node| match node {
+        Value::Array(messages) => !messages.is_empty(),
+        Value::Object(object) => object.values().any(|child| has_messages(child, "")),
+        _ => false,
+    })
+}
+
+/// Generate an actix test posting each [invalid example](crate::examples_of) of a payload
+/// type to a route, asserting that every declared constraint is enforced there with an
+/// error body naming the field. Requires the `test-util` feature.
+///
+/// ```no_run
+/// use actix_json_validator::{generate_validation_tests, AppJson, Describe, InvalidExample};
+/// use actix_web::{web, App, HttpResponse};
+/// use serde_json::json;
+///
+/// #[derive(serde::Deserialize, serde_valid::Validate, Describe)]
+/// struct CreateFood {
+///     #[validate(min_length = 3)]
+///     name: String,
+///     #[validate(minimum = 0)]
+///     calories: i32,
+///     #[validate(pattern = r"^[A-Z]{3}$")]
+///     origin: String,
+/// }
+///
+/// async fn create_food(food: AppJson<CreateFood>) -> HttpResponse {
+///     HttpResponse::Created().finish()
+/// }
+///
+/// // Against the real app, with a test name of your choice.
+/// generate_validation_tests!(
+///     CreateFood,
+///     route = "/foods",
+///     app = App::new().route("/foods", web::post().to(create_food)),
+///     test = create_food_rejects_invalid_examples,
+///     invalid = vec![InvalidExample {
+///         path: "origin".to_string(),
+///         kind: "pattern",
+///         payload: json!({"name": "Pizza", "calories": 800, "origin": "italy"}),
+///     }],
+/// );
+/// # fn main() {}
+/// ```
+///
+/// Without `app`, the examples are posted to an `AppJson` route of their own; without
+/// `test`, the test is named `rejects_invalid_examples`. `pattern` and `custom`
+/// constraints have no generated example: supply one for each in `invalid`, or the test
+/// fails. The test posts every example before failing, naming each constraint that was
+/// not enforced, see [`assert_rejects_examples`](crate::assert_rejects_examples).
+#[macro_export]
+macro_rules! generate_validation_tests {
+    (
+        $payload:ty,
+        route = $route:expr
+        $(, app = $app:expr)?
+        $(, test = $test:ident)?
+        $(, invalid = $invalid:expr)?
+        $(,)?
+    ) => {
+        $crate::generate_validation_tests!(
+            @app $payload,
+            $route,
+            [$($app)?],
+            [$($test)?],
+            [$($invalid)?]
+        );
+    };
+    (@app $payload:ty, $route:expr, [], $test:tt, $invalid:tt) => {
+        $crate::generate_validation_tests!(
+            @test $payload,
+            $route,
+            ::actix_web::App::new().route(
+                $route,
+                ::actix_web::web::post().to(|_: $crate::AppJson<$payload>| async {
+                    ::actix_web::HttpResponse::Ok().finish()
+                }),
+            ),
+            $test,
+            $invalid
+        );
+    };
+    (@app $payload:ty, $route:expr, [$app:expr], $test:tt, $invalid:tt) => {
+        $crate::generate_validation_tests!(@test $payload, $route, $app, $test, $invalid);
+    };
+    (@test $payload:ty, $route:expr, $app:expr, [], $invalid:tt) => {
+        $crate::generate_validation_tests!(
+            @test $payload,
+            $route,
+            $app,
+            [rejects_invalid_examples],
+            $invalid
+        );
+    };
+    (@test $payload:ty, $route:expr, $app:expr, [$test:ident], []) => {
+        $crate::generate_validation_tests!(
+            @test $payload,
+            $route,
+            $app,
+            [$test],
+            [::std::vec::Vec::new()]
+        );
+    };
+    (@test $payload:ty, $route:expr, $app:expr, [$test:ident], [$invalid:expr]) => {
+        #[::actix_web::test]
+        async fn $test() {
+            let app = ::actix_web::test::init_service($app).await;
+            $crate::assert_rejects_examples::<$payload, _, _>(&app, $route, $invalid).await;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AppJson, ReferenceFields, Referenced, References, Unique, UniqueFields, Uniqueness,
+    };
+    use actix_web::{http::StatusCode, web, App, FromRequest, HttpResponse, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        name: String,
+    }
+
+    #[actix_web::test]
+    async fn test_valid_fixture_is_sent() {
+        let (req, mut payload) = TestRequest::post()
+            .set_valid_json::<Signup>(&json!({"name": "Ada"}))
+            .to_http_parts();
+        let signup = AppJson::<Signup>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(signup.name, "Ada");
+    }
+
+    #[test]
+    #[should_panic(expected = "The length of the value must be `>= 3`.")]
+    fn test_invalid_fixture_panics() {
+        TestRequest::post().set_valid_json::<Signup>(&json!({"name": "Al"}));
+    }
+
+    impl UniqueFields for Signup {
+        fn unique_fields(&self) -> Vec<(&'static str, String)> {
+            vec![("name", self.name.clone())]
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_in_memory_checker() {
+        async fn status(checker: &InMemoryChecker, name: &str) -> StatusCode {
+            let (req, mut payload) = TestRequest::post()
+                .app_data(Uniqueness::new(checker.clone()))
+                .set_json(json!({"name": name}))
+                .to_http_parts();
+            match Unique::<Signup>::from_request(&req, &mut payload).await {
+                Ok(_) => StatusCode::OK,
+                Err(err) => err.status_code(),
+            }
+        }
+
+        let users = InMemoryChecker::new().taken("name", "Ada");
+        assert_eq!(status(&users, "Ada").await, StatusCode::CONFLICT);
+        assert_eq!(status(&users, "Grace").await, StatusCode::OK);
+        users.insert("name", "Grace");
+        assert_eq!(status(&users, "Grace").await, StatusCode::CONFLICT);
+        assert_eq!(
+            status(&users.unavailable(), "Alan").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Product {
+        #[validate(min_length = 3)]
+        name: String,
+        category: String,
+    }
+
+    impl ReferenceFields for Product {
+        fn reference_fields(&self) -> Vec<(&'static str, String)> {
+            vec![("category", self.category.clone())]
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_in_memory_references() {
+        let categories = InMemoryChecker::new().existing("category", "pizza");
+        let extract = |category: &str| {
+            let (req, mut payload) = TestRequest::post()
+                .app_data(References::new(categories.clone()))
+                .set_json(json!({"name": "Margherita", "category": category}))
+                .to_http_parts();
+            async move { Referenced::<Product>::from_request(&req, &mut payload).await }
+        };
+
+        assert_eq!(extract("pizza").await.unwrap().category, "pizza");
+        let err = extract("sushi").await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            err.error_response().into_body().try_into_bytes().unwrap(),
+            json!({"category": ["does not exist"]}).to_string()
+        );
+        categories.insert("category", "sushi");
+        assert!(extract("sushi").await.is_ok());
+    }
+
+    #[derive(Debug, Deserialize, Validate, Describe)]
+    struct Food {
+        #[validate(min_length = 3)]
+        name: String,
+        #[validate(minimum = 0)]
+        calories: i32,
+        #[validate(min_items = 1)]
+        #[validate(max_length = 10)]
+        tags: Vec<String>,
+    }
+
+    crate::generate_validation_tests!(Food, route = "/foods");
+
+    #[actix_web::test]
+    #[should_panic(expected = "`minimum` violated at `calories` answered 200 OK")]
+    async fn test_unenforced_constraints_fail() {
+        let app = test::init_service(App::new().route(
+            "/foods",
+            web::post().to(|| async { HttpResponse::Ok().finish() }),
+        ))
+        .await;
+        assert_rejects_invalid_examples::<Food, _, _>(&app, "/foods").await;
+    }
+
+    #[actix_web::test]
+    async fn test_every_unenforced_constraint_is_named() {
+        let app = test::init_service(App::new().route(
+            "/foods",
+            web::post().to(|| async { HttpResponse::Ok().finish() }),
+        ))
+        .await;
+        let failures = rejection_failures::<Food, _, _>(&app, "/foods", Vec::new()).await;
+        let named: Vec<_> = failures
+            .iter()
+            .map(|failure| failure.split(" answered").next().unwrap())
+            .collect();
+        assert_eq!(
+            named,
+            [
+                "`min_length` violated at `name`",
+                "`minimum` violated at `calories`",
+                "`min_items` violated at `tags`",
+                "`max_length` violated at `tags`",
+            ]
+        );
+    }
+
+    #[derive(Debug, Deserialize, Validate, Describe)]
+    struct Coupon {
+        #[validate(pattern = r"^[A-Z]{4}$")]
+        code: String,
+        #[validate(maximum = 50)]
+        percent: u8,
+    }
+
+    crate::generate_validation_tests!(
+        Coupon,
+        route = "/coupons",
+        test = coupon_rejects_invalid_examples,
+        invalid = vec![InvalidExample {
+            path: "code".to_string(),
+            kind: "pattern",
+            payload: json!({"code": "save", "percent": 10}),
+        }],
+    );
+
+    #[actix_web::test]
+    #[should_panic(expected = "`pattern` at `code` has no invalid example")]
+    async fn test_patterns_need_supplied_examples() {
+        let app = test::init_service(App::new().route(
+            "/coupons",
+            web::post().to(|_: AppJson<Coupon>| async { HttpResponse::Ok().finish() }),
+        ))
+        .await;
+        assert_rejects_invalid_examples::<Coupon, _, _>(&app, "/coupons").await;
+    }
+}
@@ -0,0 +1,64 @@
+//! Summaries of error maps cut down by [`JsonConfig::truncate_errors`].
+//!
+//! [`JsonConfig::truncate_errors`]: crate::JsonConfig::truncate_errors
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+use serde_valid::validation::Errors as ValidationError;
+
+use crate::{code, debug::constraint_name, format, ErrorMap};
+
+/// Failures of a payload by their business code, or constraint when they have none.
+pub(crate) fn by_code(errors: &ValidationError) -> BTreeMap<String, usize> {
+    let mut by_code = BTreeMap::new();
+    count(&mut by_code, errors);
+    by_code
+}
+
+/// Keep the first `cap` messages of `errors`, adding `_truncated`, `_total_errors` and
+/// the `_by_code` counts of [`by_code`]. Maps within the cap are left untouched.
+pub(crate) fn truncate(
+    mut errors: ErrorMap,
+    by_code: BTreeMap<String, usize>,
+    cap: usize,
+) -> ErrorMap {
+    let total: usize = by_code.values().sum();
+    if total <= cap {
+        return errors;
+    }
+
+    let mut budget = cap;
+    errors.retain(|_, value| keep(value, &mut budget));
+    errors.insert("_truncated".to_string(), Value::Bool(true));
+    errors.insert("_total_errors".to_string(), json!(total));
+    errors.insert("_by_code".to_string(), json!(by_code));
+    errors
+}
+
+/// Drop the messages of `value` past `budget`, returning whether any are left.
+fn keep(value: &mut Value, budget: &mut usize) -> bool {
+    match value {
+        Value::Array(messages) => {
+            messages.truncate(*budget);
+            *budget -= messages.len();
+            !messages.is_empty()
+        }
+        Value::Object(nested) => {
+            nested.retain(|_, value| keep(value, budget));
+            !nested.is_empty()
+        }
+        _ if *budget > 0 => {
+            *budget -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn count(by_code: &mut BTreeMap<String, usize>, errors: &ValidationError) {
+    format::walk(errors, "", &mut |_, error| {
+        let code = code::code(error).unwrap_or_else(|| constraint_name(error));
+        *by_code.entry(code.to_string()).or_default() += 1;
+    });
+}
@@ -0,0 +1,94 @@
+use std::convert::Infallible;
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{AppError, AppJson};
+
+/// Like [`AppJson`], but never rejects the request: the handler receives the outcome of
+/// extraction and decides what to do with failures, e.g. fall back to defaults or
+/// report partial success.
+///
+/// ```
+/// use actix_json_validator::TryAppJson;
+/// # #[derive(serde::Deserialize, serde_valid::Validate, Default)]
+/// # struct Settings { #[validate(maximum = 100)] volume: u8 }
+///
+/// async fn update_settings(settings: TryAppJson<Settings>) -> String {
+///     let settings = settings.into_result().unwrap_or_default();
+///     format!("volume set to {}", settings.volume)
+/// }
+/// ```
+///
+/// The error is the one [`AppJson`] would have responded with, so a custom error handler
+/// registered on [`JsonConfig`](crate::JsonConfig) still applies.
+#[derive(Debug)]
+pub struct TryAppJson<T>(pub Result<T, AppError>);
+
+impl<T> TryAppJson<T> {
+    /// Deconstruct to the extraction result
+    pub fn into_result(self) -> Result<T, AppError> {
+        self.0
+    }
+}
+
+impl<T> FromRequest for TryAppJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = Infallible;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        AppJson::<T>::from_request(req, payload)
+            .map(|res| Ok(TryAppJson(res.map(AppJson::into_inner))))
+            .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, web, App};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Settings {
+        #[validate(maximum = 100)]
+        volume: u8,
+    }
+
+    async fn update(settings: TryAppJson<Settings>) -> String {
+        match settings.into_result() {
+            Ok(settings) => format!("volume {}", settings.volume),
+            Err(AppError::ValidationError(errors)) => format!("kept defaults: {}", json!(errors)),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_handler_receives_result() {
+        let app = test::init_service(App::new().route("/", web::post().to(update))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"volume": 30}))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "volume 30");
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(json!({"volume": 130}))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(
+            body,
+            r#"kept defaults: {"volume":["The number must be `<= 100`."]}"#
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use std::{ops::Deref, sync::Arc, time::Instant};
+
+use actix_web::{
+    dev::Payload, error::ErrorInternalServerError, http::StatusCode, FromRequest, HttpRequest,
+};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{extract, format, AppError, AppJson, ErrorMap, FailureClass};
+
+/// Looks up whether a value is already taken, e.g. in the database.
+pub trait UniquenessChecker: 'static {
+    /// Whether `value` already exists for `field`, the dotted path of the value in the
+    /// payload (e.g. `"email"`).
+    fn exists<'a>(
+        &'a self,
+        field: &'a str,
+        value: &'a str,
+        req: &'a HttpRequest,
+    ) -> LocalBoxFuture<'a, Result<bool, actix_web::Error>>;
+}
+
+/// Payloads with values that must be unique, checked by [`Unique`] after validation.
+pub trait UniqueFields {
+    /// The values to check, by dotted path.
+    fn unique_fields(&self) -> Vec<(&'static str, String)>;
+}
+
+/// The [`UniquenessChecker`] used by [`Unique`], registered as app data.
+#[derive(Clone)]
+pub struct Uniqueness {
+    pub(crate) checker: Arc<dyn UniquenessChecker>,
+    pub(crate) status: StatusCode,
+    pub(crate) message: String,
+}
+
+impl Uniqueness {
+    /// Check values with `checker`. Taken values are rejected with `409 Conflict` and
+    /// the message "already exists".
+    pub fn new(checker: impl UniquenessChecker) -> Self {
+        Uniqueness {
+            checker: Arc::new(checker),
+            status: StatusCode::CONFLICT,
+            message: "already exists".to_string(),
+        }
+    }
+
+    /// Respond to taken values with `status` instead of `409 Conflict`.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Report taken values with `message` instead of "already exists".
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+}
+
+/// Like [`AppJson`], then rejects payloads whose [`UniqueFields`] are already taken
+/// according to the [`Uniqueness`] checker registered as app data:
+///
+/// ```
+/// use actix_json_validator::{Unique, UniqueFields, Uniqueness, UniquenessChecker};
+/// use actix_web::{web, App, HttpRequest};
+/// use futures_util::future::LocalBoxFuture;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Signup { #[validate(min_length = 3)] email: String }
+///
+/// impl UniqueFields for Signup {
+///     fn unique_fields(&self) -> Vec<(&'static str, String)> {
+///         vec![("email", self.email.to_lowercase())]
+///     }
+/// }
+///
+/// struct Users;
+///
+/// impl UniquenessChecker for Users {
+///     fn exists<'a>(
+///         &'a self,
+///         field: &'a str,
+///         value: &'a str,
+///         _req: &'a HttpRequest,
+///     ) -> LocalBoxFuture<'a, Result<bool, actix_web::Error>> {
+///         // e.g. `SELECT EXISTS (SELECT 1 FROM users WHERE email = $1)`
+///         Box::pin(async move { Ok(field == "email" && value == "taken@example.com") })
+///     }
+/// }
+///
+/// async fn signup(signup: Unique<Signup>) -> String {
+///     signup.email.clone()
+/// }
+///
+/// let app = App::new()
+///     .app_data(Uniqueness::new(Users))
+///     .route("/signup", web::post().to(signup));
+/// ```
+///
+/// Taken values are reported like validation errors, e.g. `{"email": ["already exists"]}`,
+/// and recorded in the [`ValidationReport`](crate::ValidationReport). Failures of the
+/// checker itself, and a missing [`Uniqueness`], respond with
+/// `500 Internal Server Error`.
+#[derive(Debug)]
+pub struct Unique<T>(pub T);
+
+impl<T> Unique<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for Unique<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for Unique<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for Unique<T>
+where
+    T: DeserializeOwned + Validate + UniqueFields + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let started = Instant::now();
+        let extraction = AppJson::<T>::from_request(req, payload);
+        let uniqueness = req.app_data::<Uniqueness>().cloned();
+        let req = req.clone();
+
+        Box::pin(async move {
+            let data = extraction.await?.into_inner();
+            let fields = data.unique_fields();
+            check::<T>(&req, started, uniqueness.as_ref(), fields, true).await?;
+            Ok(Unique(data))
+        })
+    }
+}
+
+/// Look up `fields` of a `T` that passed validation with the checker of `lookup`, and
+/// reject those that exist, if `existing` is set, or else those that do not, like
+/// validation errors.
+pub(crate) async fn check<T>(
+    req: &HttpRequest,
+    started: Instant,
+    lookup: Option<&Uniqueness>,
+    fields: Vec<(&'static str, String)>,
+    existing: bool,
+) -> Result<(), AppError> {
+    let config = extract::config(req);
+    let type_name = std::any::type_name::<T>();
+    let Some(lookup) = lookup else {
+        let registration = if existing { "Uniqueness" } else { "References" };
+        return Err(AppError::Handled(ErrorInternalServerError(format!(
+            "no {registration} registered for {type_name}"
+        ))));
+    };
+
+    let mut errors = ErrorMap::new();
+    for (field, value) in fields {
+        let exists = lookup
+            .checker
+            .exists(field, &value, req)
+            .await
+            .map_err(AppError::Handled)?;
+        if exists == existing {
+            format::insert(&mut errors, field, lookup.message.clone());
+        }
+    }
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    extract::classify(req, FailureClass::Semantic);
+    let result = extract::finish(
+        &config,
+        req,
+        type_name,
+        started,
+        Err(AppError::ValidationError(errors)),
+    );
+    result.map_err(|err| match err {
+        AppError::ValidationError(_) => extract::with_status(err, lookup.status),
+        err => err,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use actix_web::{body::MessageBody, test, HttpMessage, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Signup {
+        #[validate(min_length = 3)]
+        username: String,
+        email: String,
+    }
+
+    impl UniqueFields for Signup {
+        fn unique_fields(&self) -> Vec<(&'static str, String)> {
+            vec![
+                ("username", self.username.clone()),
+                ("email", self.email.to_lowercase()),
+            ]
+        }
+    }
+
+    struct Taken(&'static [&'static str]);
+
+    impl UniquenessChecker for Taken {
+        fn exists<'a>(
+            &'a self,
+            _field: &'a str,
+            value: &'a str,
+            _req: &'a HttpRequest,
+        ) -> LocalBoxFuture<'a, Result<bool, actix_web::Error>> {
+            Box::pin(async move { Ok(self.0.contains(&value)) })
+        }
+    }
+
+    async fn extract(
+        uniqueness: Uniqueness,
+        body: Value,
+    ) -> (HttpRequest, Result<Unique<Signup>, AppError>) {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(uniqueness)
+            .set_json(body)
+            .to_http_parts();
+        let result = Unique::<Signup>::from_request(&req, &mut payload).await;
+        (req, result)
+    }
+
+    fn body(err: &AppError) -> Value {
+        let body = err.error_response().into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_taken_values_conflict() {
+        let uniqueness = Uniqueness::new(Taken(&["ada", "ada@example.com"]));
+        let (req, result) = extract(
+            uniqueness.clone(),
+            json!({"username": "ada", "email": "Ada@example.com"}),
+        )
+        .await;
+        let err = result.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+        assert_eq!(
+            body(&err),
+            json!({"username": ["already exists"], "email": ["already exists"]})
+        );
+        let report = req.extensions().get::<ValidationReport>().cloned().unwrap();
+        assert_eq!(report.error_count(), 2);
+
+        let (_, result) = extract(
+            uniqueness,
+            json!({"username": "grace", "email": "grace@example.com"}),
+        )
+        .await;
+        assert_eq!(result.unwrap().username, "grace");
+    }
+
+    #[actix_web::test]
+    async fn test_status_and_message_are_configurable() {
+        let uniqueness = Uniqueness::new(Taken(&["ada"]))
+            .status(StatusCode::BAD_REQUEST)
+            .message("is already taken");
+        let (_, result) = extract(
+            uniqueness.clone(),
+            json!({"username": "ada", "email": "a@example.com"}),
+        )
+        .await;
+        let err = result.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(body(&err), json!({"username": ["is already taken"]}));
+
+        // Invalid payloads are rejected before the checker runs.
+        let (_, result) = extract(
+            uniqueness,
+            json!({"username": "ad", "email": "a@example.com"}),
+        )
+        .await;
+        assert_eq!(
+            body(&result.unwrap_err()),
+            json!({"username": ["The length of the value must be `>= 3`."]})
+        );
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Membership {
+        #[validate]
+        team: Team,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Team {
+        #[validate(min_length = 3)]
+        slug: String,
+    }
+
+    impl UniqueFields for Membership {
+        fn unique_fields(&self) -> Vec<(&'static str, String)> {
+            vec![("team.slug", self.team.slug.clone())]
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_nested_fields_are_reported_nested() {
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(Uniqueness::new(Taken(&["core"])))
+            .set_json(json!({"team": {"slug": "core"}}))
+            .to_http_parts();
+        let err = Unique::<Membership>::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(body(&err), json!({"team": {"slug": ["already exists"]}}));
+    }
+}
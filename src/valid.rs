@@ -0,0 +1,136 @@
+use std::ops::Deref;
+
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{AppError, AppJson};
+
+/// A payload that passed validation. It can only be extracted like [`AppJson`] or built
+/// by [`Valid::new`], so service-layer functions taking a `Valid<T>` need no checks of
+/// their own:
+///
+/// ```
+/// use actix_json_validator::Valid;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct CreateOrder { #[validate(minimum = 1)] quantity: u32 }
+///
+/// async fn create_order(order: Valid<CreateOrder>) -> String {
+///     place(order)
+/// }
+///
+/// fn place(order: Valid<CreateOrder>) -> String {
+///     format!("{} items ordered", order.quantity)
+/// }
+/// ```
+///
+/// There is no public constructor skipping validation, and no mutable access:
+///
+/// ```compile_fail
+/// # use actix_json_validator::Valid;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct CreateOrder { #[validate(minimum = 1)] quantity: u32 }
+/// let order = Valid(CreateOrder { quantity: 0 });
+/// ```
+///
+/// [`AppJson`]'s field is public, so [`AppJson::into_valid`] validates again; extract
+/// `Valid<T>` directly to validate once.
+#[derive(Debug, Clone)]
+pub struct Valid<T>(T);
+
+impl<T: Validate> Valid<T> {
+    /// Validate `data`, e.g. a payload built by the service itself. Errors have the same
+    /// shape as those of the extractor.
+    pub fn new(data: T) -> Result<Self, AppError> {
+        data.validate()?;
+        Ok(Valid(data))
+    }
+}
+
+impl<T> Valid<T> {
+    /// Deconstruct to an inner value, giving up the proof of validation
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for Valid<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Validate> AppJson<T> {
+    /// Validate the payload again into a [`Valid`], as the handler may have changed it.
+    pub fn into_valid(self) -> Result<Valid<T>, AppError> {
+        Valid::new(self.0)
+    }
+}
+
+impl<T> FromRequest for Valid<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        AppJson::<T>::from_request(req, payload)
+            .map(|res| res.map(|json| Valid(json.into_inner())))
+            .boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Order {
+        #[validate(minimum = 1)]
+        quantity: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_extracted_and_constructed() {
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"quantity": 3}))
+            .to_http_parts();
+        let order = Valid::<Order>::from_request(&req, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(order.quantity, 3);
+
+        let (req, mut payload) = test::TestRequest::post()
+            .set_json(json!({"quantity": 0}))
+            .to_http_parts();
+        assert!(Valid::<Order>::from_request(&req, &mut payload)
+            .await
+            .is_err());
+
+        let body = Valid::new(Order { quantity: 0 })
+            .unwrap_err()
+            .error_response()
+            .into_body()
+            .try_into_bytes()
+            .unwrap();
+        assert_eq!(
+            body,
+            json!({"quantity": ["The number must be `>= 1`."]}).to_string()
+        );
+        assert!(AppJson(Order { quantity: 2 }).into_valid().is_ok());
+    }
+}
@@ -0,0 +1,260 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{
+    dev::{Decompress, Payload},
+    http::{header::ContentType, StatusCode},
+    FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError,
+};
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use quick_xml::escape::escape;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ConstraintKind, JsonConfig};
+
+/// Extract a validated XML body, sent as `application/xml`, `text/xml` or with a `+xml`
+/// suffix, with the same limits and validation as [`AppJson`](crate::AppJson). Requires
+/// the `xml` feature.
+///
+/// ```
+/// use actix_json_validator::AppXml;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Invoice { #[validate(min_length = 1)] number: String }
+///
+/// // <invoice><number>INV-7</number></invoice>
+/// async fn receive(invoice: AppXml<Invoice>) -> String {
+///     format!("Received {}", invoice.number)
+/// }
+/// ```
+///
+/// Errors are reported as JSON, unless the client asks for [`xml_errors`] through the
+/// negotiated [error formats](JsonConfig::error_format). Content types accepted by the
+/// [`content_type`](JsonConfig::content_type) predicate are accepted too; a body without
+/// a content type is accepted unless
+/// [`content_type_required`](JsonConfig::content_type_required) is set.
+#[derive(Debug)]
+pub struct AppXml<T>(pub T);
+
+impl<T> AppXml<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppXml<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppXml<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppXml<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                if !is_xml(&req, &config) {
+                    extract::fail(&req, ConstraintKind::ContentType);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "content_type",
+                        "Content type error".to_string(),
+                    ));
+                }
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let payload = Decompress::from_headers(payload, req.headers());
+                let body =
+                    extract::read_body(&req, Payload::from(payload.boxed_local()), config.limit)
+                        .await
+                        .map_err(|err| extract::body_error(&config, &req, type_name, err))?;
+                extract::soft_limit(&config, &req, body.len());
+
+                let data = quick_xml::de::from_reader::<_, T>(&body[..]).map_err(|err| {
+                    extract::fail(&req, ConstraintKind::Deserialization);
+                    let message = format!("XML deserialize error: {err}");
+                    extract::decode_error(&config, type_name, "deserialization", message)
+                })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppXml)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+fn is_xml(req: &HttpRequest, config: &JsonConfig) -> bool {
+    match req.mime_type() {
+        Ok(Some(mime)) => {
+            ((mime.type_() == mime::APPLICATION || mime.type_() == mime::TEXT)
+                && mime.subtype() == mime::XML)
+                || mime.suffix() == Some(mime::XML)
+                || config
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|predicate| predicate(mime))
+        }
+        Ok(None) => !config.content_type_required,
+        Err(_) => false,
+    }
+}
+
+/// Render errors as XML, for use with [`JsonConfig::error_format`], typically negotiated
+/// through `Accept` for clients that only speak XML:
+///
+/// ```
+/// use actix_json_validator::{xml_errors, JsonConfig};
+///
+/// // <errors>
+/// //   <field name="number"><message>The length of the value must be `>= 1`.</message></field>
+/// // </errors>
+/// let config = JsonConfig::default()
+///     .error_format("xml", xml_errors)
+///     .error_format_header("Accept");
+/// ```
+///
+/// Fields nest as the error JSON does, and the errors of a payload that could not be
+/// read are under the field named `error`. Errors already turned into responses by an
+/// error handler are left untouched. Requires the `xml` feature.
+pub fn xml_errors(
+    err: &AppError,
+    status: StatusCode,
+    _: &HttpRequest,
+    _: &'static str,
+) -> HttpResponse {
+    let AppError::ValidationError(errors) = err else {
+        return err.error_response();
+    };
+    let mut body = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?><errors>");
+    for (name, value) in errors {
+        write_field(&mut body, name, value);
+    }
+    body.push_str("</errors>");
+    HttpResponse::build(status)
+        .content_type(ContentType(
+            "application/xml".parse().expect("valid media type"),
+        ))
+        .body(body)
+}
+
+fn write_field(body: &mut String, name: &str, value: &Value) {
+    body.push_str(&format!("<field name=\"{}\">", escape(name)));
+    write_value(body, value);
+    body.push_str("</field>");
+}
+
+fn write_value(body: &mut String, value: &Value) {
+    match value {
+        Value::Array(values) => values.iter().for_each(|value| write_value(body, value)),
+        // A message linked to its documentation.
+        Value::Object(message) if message.get("message").is_some_and(Value::is_string) => {
+            let text = message["message"].as_str().unwrap_or_default();
+            match message.get("type").and_then(Value::as_str) {
+                Some(url) => body.push_str(&format!(
+                    "<message type=\"{}\">{}</message>",
+                    escape(url),
+                    escape(text)
+                )),
+                None => body.push_str(&format!("<message>{}</message>", escape(text))),
+            }
+        }
+        Value::Object(fields) => fields
+            .iter()
+            .for_each(|(name, value)| write_field(body, name, value)),
+        Value::String(text) => body.push_str(&format!("<message>{}</message>", escape(text))),
+        other => body.push_str(&format!("<message>{}</message>", escape(other.to_string()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Invoice {
+        #[validate(min_length = 3)]
+        number: String,
+        #[validate(maximum = 100)]
+        quantity: u32,
+    }
+
+    async fn extract(accept: &str, body: &'static str) -> Result<Invoice, (String, String)> {
+        let config = JsonConfig::default()
+            .error_format("xml", xml_errors)
+            .error_format_header("Accept");
+        let (req, mut payload) = test::TestRequest::post()
+            .app_data(config)
+            .insert_header(("content-type", "application/xml"))
+            .insert_header(("accept", accept))
+            .set_payload(body)
+            .to_http_parts();
+        AppXml::<Invoice>::from_request(&req, &mut payload)
+            .await
+            .map(AppXml::into_inner)
+            .map_err(|err| {
+                let response = err.error_response();
+                let content_type = response
+                    .headers()
+                    .get("content-type")
+                    .unwrap()
+                    .to_str()
+                    .unwrap();
+                let content_type = content_type.to_string();
+                let body = response.into_body().try_into_bytes().unwrap();
+                (content_type, String::from_utf8(body.to_vec()).unwrap())
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_xml_bodies() {
+        let body = "<invoice><number>INV-7</number><quantity>3</quantity></invoice>";
+        let invoice = extract("application/xml", body).await.unwrap();
+        assert_eq!((invoice.number.as_str(), invoice.quantity), ("INV-7", 3));
+
+        let body = "<invoice><number>I7</number><quantity>300</quantity></invoice>";
+        let (content_type, errors) = extract("application/json", body).await.unwrap_err();
+        assert_eq!(content_type, "application/json");
+        assert_eq!(
+            serde_json::from_str::<Value>(&errors).unwrap(),
+            serde_json::json!({
+                "number": ["The length of the value must be `>= 3`."],
+                "quantity": ["The number must be `<= 100`."]
+            })
+        );
+
+        let (content_type, errors) = extract("application/xml", body).await.unwrap_err();
+        assert_eq!(content_type, "application/xml");
+        assert_eq!(
+            errors,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><errors>\
+             <field name=\"number\"><message>The length of the value must be `&gt;= 3`.</message></field>\
+             <field name=\"quantity\"><message>The number must be `&lt;= 100`.</message></field>\
+             </errors>"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::{ops::Deref, time::Instant};
+
+use actix_web::{
+    dev::{Decompress, Payload},
+    FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{future::LocalBoxFuture, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_valid::Validate;
+
+use crate::{budget, extract, AppError, ConstraintKind, JsonConfig};
+
+/// Extract a validated YAML body, sent as `application/yaml` or `application/x-yaml`,
+/// such as a config uploaded by an operator, with the same limits, validation and error
+/// body as [`AppJson`](crate::AppJson). Requires the `yaml` feature.
+///
+/// ```
+/// use actix_json_validator::AppYaml;
+/// # #[derive(serde::Deserialize, serde_valid::Validate)]
+/// # struct Pipeline { #[validate(min_items = 1)] stages: Vec<String> }
+///
+/// async fn upload(pipeline: AppYaml<Pipeline>) -> String {
+///     format!("{} stages", pipeline.stages.len())
+/// }
+/// ```
+///
+/// Errors are reported as JSON. `text/yaml`, content types with a `+yaml` suffix and
+/// those accepted by the [`content_type`](JsonConfig::content_type) predicate are
+/// accepted too; a body without a content type is accepted unless
+/// [`content_type_required`](JsonConfig::content_type_required) is set.
+#[derive(Debug)]
+pub struct AppYaml<T>(pub T);
+
+impl<T> AppYaml<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsRef<T> for AppYaml<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for AppYaml<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for AppYaml<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = extract::config(req);
+        let type_name = std::any::type_name::<T>();
+        let started = Instant::now();
+        let req = req.clone();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let result = async {
+                if !is_yaml(&req, &config) {
+                    extract::fail(&req, ConstraintKind::ContentType);
+                    return Err(extract::decode_error(
+                        &config,
+                        type_name,
+                        "content_type",
+                        "Content type error".to_string(),
+                    ));
+                }
+
+                let _permit = budget::reserve(&config, &req, type_name).await?;
+                let payload = Decompress::from_headers(payload, req.headers());
+                let body =
+                    extract::read_body(&req, Payload::from(payload.boxed_local()), config.limit)
+                        .await
+                        .map_err(|err| extract::body_error(&config, &req, type_name, err))?;
+                extract::soft_limit(&config, &req, body.len());
+
+                let data = serde_yaml::from_slice::<T>(&body).map_err(|err| {
+                    extract::fail(&req, ConstraintKind::Deserialization);
+                    let message = format!("YAML deserialize error: {err}");
+                    extract::decode_error(&config, type_name, "deserialization", message)
+                })?;
+
+                extract::validate(data, &config, &req, type_name).map(AppYaml)
+            }
+            .await;
+
+            extract::finish(&config, &req, type_name, started, result)
+        })
+    }
+}
+
+fn is_yaml(req: &HttpRequest, config: &JsonConfig) -> bool {
+    match req.mime_type() {
+        Ok(Some(mime)) => {
+            ((mime.type_() == mime::APPLICATION || mime.type_() == mime::TEXT)
+                && ["yaml", "x-yaml"].contains(&mime.subtype().as_str()))
+                || mime.suffix().is_some_and(|suffix| suffix == "yaml")
+                || config
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|predicate| predicate(mime))
+        }
+        Ok(None) => !config.content_type_required,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::MessageBody, test, ResponseError};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Pipeline {
+        #[validate(min_length = 3)]
+        name: String,
+        #[validate(min_items = 1)]
+        #[validate]
+        stages: Vec<Stage>,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Stage {
+        #[validate(maximum = 3600)]
+        timeout: u32,
+    }
+
+    async fn extract(content_type: &str, body: &'static str) -> Result<Pipeline, Value> {
+        let (req, mut payload) = test::TestRequest::post()
+            .insert_header(("content-type", content_type))
+            .set_payload(body)
+            .to_http_parts();
+        AppYaml::<Pipeline>::from_request(&req, &mut payload)
+            .await
+            .map(AppYaml::into_inner)
+            .map_err(|err| {
+                let body = err.error_response().into_body().try_into_bytes().unwrap();
+                serde_json::from_slice(&body).unwrap()
+            })
+    }
+
+    #[actix_web::test]
+    async fn test_yaml_bodies() {
+        let pipeline = extract(
+            "application/yaml",
+            "name: deploy\nstages:\n  - timeout: 60\n",
+        )
+        .await
+        .unwrap();
+        assert_eq!(pipeline.name, "deploy");
+        assert_eq!(pipeline.stages[0].timeout, 60);
+
+        assert_eq!(
+            extract(
+                "application/x-yaml",
+                "name: ci\nstages:\n  - timeout: 60\n  - timeout: 7200\n"
+            )
+            .await
+            .unwrap_err(),
+            json!({
+                "name": ["The length of the value must be `>= 3`."],
+                "stages": {"1": {"timeout": ["The number must be `<= 3600`."]}}
+            })
+        );
+        assert_eq!(
+            extract("application/json", "name: deploy\nstages: []\n")
+                .await
+                .unwrap_err(),
+            json!({"error": ["Content type error"]})
+        );
+    }
+}